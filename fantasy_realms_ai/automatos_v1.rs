@@ -3,13 +3,25 @@
 //! posible score at the end of its turn. 
 //! For each card checked it finds the maximum score of replacing every card in its hand.
 //! It checks each card in the discard pile.
-//! Then it checks every unknown card, and takes the average score. 
+//! Then it checks every unknown card, and takes the average score 
+//! weighted by the chance of drawing each card from the deck. 
 //! This is the expected evaluation for the deck. 
 //! It then chooses the highest scoring option. 
+//! When denial is turned on, each option is penalised by how much the card it discards would help an opponent.
+//! Instead of its score it can maximise its chance of beating every opponent.
+//! When tempo is turned on, drawing from the deck is favoured when it is ahead, to end the game sooner,
+//! and drawing from the discard pile is favoured when it is behind.
 //! After drawing a card it decids if it is benificial to keep the card drawn or simply discard it.
 
+use rand::{rngs::StdRng, SeedableRng};
 use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
-use super::FantasyRealmsBot;
+use super::{FantasyRealmsBot, GameView};
+use super::belief::BeliefTracker;
+use super::denial::{Denial, DEFAULT_DENIAL_WEIGHT};
+use super::objective::{Evaluator, Objective};
+use super::simulation::END_DISCARD_PILE_SIZE;
+use super::parallel::parallel_map;
+use super::tempo::{Tempo, DEFAULT_TEMPO_WEIGHT};
 
 struct BotTurn {
     draw: DrawCard,
@@ -23,48 +35,82 @@ struct BotTurn {
 
 pub struct AutomatosV1 {
     pub(crate) discard: Option<Card>,
+    pub(crate) denial_weight: f32,
+    pub(crate) objective: Objective,
+    pub(crate) tempo_weight: f32,
+    pub(crate) rng: StdRng,
+    pub(crate) belief: BeliefTracker,
+}
+
+impl AutomatosV1 {
+    /// Creates an AutomatosV1 bot with the default settings.
+    /// # Arguments
+    /// * `objective` - An `Objective` the bot maximises.
+    /// # Returns
+    /// An `AutomatosV1` bot.
+    pub fn new(objective: Objective) -> Self {
+        AutomatosV1 {
+            discard: None,
+            denial_weight: DEFAULT_DENIAL_WEIGHT,
+            objective,
+            tempo_weight: DEFAULT_TEMPO_WEIGHT,
+            rng: StdRng::from_entropy(),
+            belief: BeliefTracker::new(),
+        }
+    }
 }
 
 impl FantasyRealmsBot for AutomatosV1 {
     fn name(&self) -> String {
-        "AutomatosV1".to_string()
+        format!("AutomatosV1{}", self.objective.suffix())
     }
 
-    fn generate_draw(
-        &mut self,
-        hand: &Hand, 
-        discard_pile: &CardCollection, 
-        known_opponent_cards: &Vec<CardCollection>, 
-        _: u8,
-    ) -> DrawCard {
-        let unknown_cards: CardCollection = !(
-            known_opponent_cards.iter().cloned().sum::<CardCollection>() + 
-            *discard_pile +
-            CardCollection::from(hand)
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        let belief = self.belief.current(view, None);
+        let draw_probabilities = belief.deck_draw_probabilities();
+        let evaluator = Evaluator::new(self.objective, hand, discard_pile, known_opponent_cards, &belief, None, &mut self.rng);
+        let denial = Denial::new(self.denial_weight, hand, discard_pile, known_opponent_cards, &belief, None, &mut self.rng);
+        let penalty = |card: Card| -> f32 {
+            evaluator.points(denial.penalty(card))
+        };
+        let deck_bonus = evaluator.points(
+            Tempo::new(self.tempo_weight, hand, discard_pile, known_opponent_cards, &belief, &mut self.rng).deck_bonus()
         );
-        let mut turn = BotTurn::new(DrawCard::Deck, hand[0], hand.score(discard_pile) as f32);
-        for i in 0..7 {
-            for draw in discard_pile {
-                let mut test_hand = hand.clone();
-                test_hand[i] = draw;
-                let new_discard_pile = discard_pile.clone() + hand[i] - draw;
-                let evaluation: f32 = test_hand.score(&new_discard_pile) as f32;
-                if evaluation > turn.evaluation {
-                    turn = BotTurn::new(DrawCard::Discard (draw), hand[i], evaluation);
-                }
+        let deck_draw_ends_game = discard_pile.len() as usize + 1 >= END_DISCARD_PILE_SIZE;
+        let mut turn = BotTurn::new(DrawCard::Deck, hand[0], evaluator.evaluate(hand, discard_pile) + deck_bonus);
+        let discard_draws: Vec<(usize, Card)> = (0..7)
+            .flat_map(|i| discard_pile.iter().map(move |draw| (i, draw)))
+            .collect();
+        let discard_scores: Vec<f32> = parallel_map(&discard_draws, |&(i, draw)| {
+            swap_score(hand, discard_pile, &evaluator, i, draw)
+        });
+        for (&(i, draw), &score) in discard_draws.iter().zip(discard_scores.iter()) {
+            let evaluation: f32 = score - penalty(hand[i]);
+            if evaluation > turn.evaluation {
+                turn = BotTurn::new(DrawCard::Discard (draw), hand[i], evaluation);
             }
         }
-        for i in 0..7 {
-            let mut evaluation: f32 = 0.0;
-            for draw in unknown_cards {
-                let mut test_hand = hand.clone();
-                test_hand[i] = draw;
-                let new_discard_pile = discard_pile.clone() + hand[i] - draw;
-                evaluation += test_hand.score(&new_discard_pile) as f32;
-            }
-            evaluation /= unknown_cards.len() as f32;
-            if evaluation > turn.evaluation {
-                turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation)
+        if !draw_probabilities.is_empty() {
+            let deck_draws: Vec<(usize, Card)> = (0..7)
+                .flat_map(|i| draw_probabilities.iter().map(move |&(draw, _)| (i, draw)))
+                .collect();
+            let deck_scores: Vec<f32> = parallel_map(&deck_draws, |&(i, draw)| {
+                swap_score(hand, discard_pile, &evaluator, i, draw)
+            });
+            for (i, slot_scores) in deck_scores.chunks(draw_probabilities.len()).enumerate() {
+                let mut evaluation: f32 = deck_bonus;
+                for (&(_, probability), &score) in draw_probabilities.iter().zip(slot_scores) {
+                    evaluation += probability * score;
+                }
+                if !deck_draw_ends_game {
+                    evaluation -= penalty(hand[i]);
+                }
+                if evaluation > turn.evaluation {
+                    turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation)
+                }
             }
         }
         if turn.draw == DrawCard::Deck {
@@ -75,27 +121,56 @@ impl FantasyRealmsBot for AutomatosV1 {
         turn.draw
     }
 
-    fn generate_discard(
-            &mut self,
-            hand: &Hand,
-            discard_pile: &CardCollection,
-            _: &Vec<CardCollection>, 
-            _: u8,
-            draw: &Card,
-        ) -> Card {
-        if self.discard.is_some() {
-            return self.discard.unwrap();
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        if let Some(discard) = self.discard.take() {
+            return discard;
         }
-        let mut turn = BotTurn::new(DrawCard::Deck, *draw, hand.score(discard_pile) as f32);
+        let belief = self.belief.current(view, Some(*draw));
+        let evaluator = Evaluator::new(self.objective, hand, discard_pile, known_opponent_cards, &belief, Some(*draw), &mut self.rng);
+        let denial = Denial::new(self.denial_weight, hand, discard_pile, known_opponent_cards, &belief, Some(*draw), &mut self.rng);
+        let penalty = |card: Card| -> f32 {
+            if discard_pile.len() as usize + 1 >= END_DISCARD_PILE_SIZE {
+                0.0
+            } else {
+                evaluator.points(denial.penalty(card))
+            }
+        };
+        let mut turn = BotTurn::new(DrawCard::Deck, *draw, evaluator.evaluate(hand, discard_pile) - penalty(*draw));
         for i in 0..7 {
-            let mut test_hand = hand.clone();
-            test_hand[i] = *draw;
-            let new_discard_pile = discard_pile.clone() + hand[i] - *draw;
-            let evaluation: f32 = test_hand.score(&new_discard_pile) as f32;
+            let evaluation: f32 = swap_score(hand, discard_pile, &evaluator, i, *draw) - penalty(hand[i]);
             if evaluation > turn.evaluation {
                 turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation);
             }
         }
         turn.discard
     }
+
+    fn on_game_start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        self.discard = None;
+        self.belief.start(seat, players, hand);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Helper function for `generate_draw` and `generate_discard`.
+/// Evaluates a hand after drawing a card in place of one of its cards.
+/// # Arguments
+/// * `hand` - A `&Hand` representing the bots current hand.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile.
+/// * `evaluator` - A `&Evaluator` that evaluates hands for the bots objective.
+/// * `i` - A `usize` representing the position of the card discarded.
+/// * `draw` - A `Card` representing the card drawn.
+/// # Returns
+/// A `f32` representing the evaluation of the new hand.
+fn swap_score(hand: &Hand, discard_pile: &CardCollection, evaluator: &Evaluator, i: usize, draw: Card) -> f32 {
+    let mut test_hand = *hand;
+    test_hand[i] = draw;
+    let new_discard_pile = *discard_pile + hand[i] - draw;
+    evaluator.evaluate(&test_hand, &new_discard_pile)
 }
\ No newline at end of file
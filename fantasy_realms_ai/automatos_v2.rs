@@ -1,81 +1,111 @@
 //! # AutomatosV2
 //! AutomatosV2 functions idenicly to AutomatosV1 with an added recursive depth search.
-//! The max depth is set to one move into the future so 
+//! The max depth is set to one move into the future by default so 
 //! that it can be run in a reasonable amount of time. 
 //! This method did not have any noticable improvement over
 //! AutomatosV1 due to the large random factor of the game itself. 
+//! Like AutomatosV1 it can penalise discards that would help an opponent,
+//! and it can maximise either its score or its chance of winning.
+//! It can also hurry the end of the game when it is ahead and stall when it is behind.
+//! Near the end of the game it hands off to the endgame search.
 
+use std::time::Instant;
+use rand::{rngs::StdRng, SeedableRng};
 use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
-use super::FantasyRealmsBot;
+use super::{FantasyRealmsBot, GameView};
+use super::belief::{Belief, BeliefTracker};
+use super::denial::{Denial, DEFAULT_DENIAL_WEIGHT};
+use super::endgame::EndgameSolver;
+use super::simulation::END_DISCARD_PILE_SIZE;
+use super::parallel::parallel_map;
+use super::tempo::{Tempo, DEFAULT_TEMPO_WEIGHT};
+use super::objective::{Evaluator, Objective};
 
-struct BotTurn {
-    draw: DrawCard,
-    discard: Card,
-    evaluation: f32,
+pub(crate) struct BotTurn {
+    pub(crate) draw: DrawCard,
+    pub(crate) discard: Card,
+    pub(crate) evaluation: f32,
 } impl BotTurn {
     fn new(draw: DrawCard, discard: Card, evaluation: f32) -> Self {
         BotTurn {draw, discard, evaluation}
     }
 }
 
+/// The information shared by every part of a search.
+/// # Fields
+/// * `belief` - A `&Belief` representing where the unknown cards are likely to be.
+/// * `evaluator` - A `&Evaluator` that evaluates hands for the bots objective.
+/// * `denial` - A `&Denial` representing the penalty for discarding each card.
+/// * `tempo` - A `&Tempo` representing the bonus for drawing from the deck.
+/// * `deadline` - An `Option<Instant>` after which the search gives up.
+///   The result of a search that runs past its deadline is meaningless.
+pub(crate) struct Search<'a> {
+    pub(crate) belief: &'a Belief,
+    pub(crate) evaluator: &'a Evaluator,
+    pub(crate) denial: &'a Denial,
+    pub(crate) tempo: &'a Tempo,
+    pub(crate) deadline: Option<Instant>,
+}
+
+/// The default number of turns AutomatosV2 searches into the future.
+pub const DEFAULT_MAX_DEPTH: u8 = 1;
+
 pub struct AutomatosV2 {
     pub(crate) discard: Option<Card>,
+    pub(crate) denial_weight: f32,
+    pub(crate) endgame: Option<EndgameSolver>,
+    pub(crate) objective: Objective,
+    pub(crate) tempo_weight: f32,
+    pub(crate) max_depth: u8,
+    pub(crate) rng: StdRng,
+    pub(crate) belief: BeliefTracker,
+}
+
+impl AutomatosV2 {
+    /// Creates an AutomatosV2 bot with the default settings.
+    /// # Arguments
+    /// * `objective` - An `Objective` the bot maximises.
+    /// # Returns
+    /// An `AutomatosV2` bot.
+    pub fn new(objective: Objective) -> Self {
+        AutomatosV2 {
+            discard: None,
+            denial_weight: DEFAULT_DENIAL_WEIGHT,
+            endgame: Some(EndgameSolver::new(objective)),
+            objective,
+            tempo_weight: DEFAULT_TEMPO_WEIGHT,
+            max_depth: DEFAULT_MAX_DEPTH,
+            rng: StdRng::from_entropy(),
+            belief: BeliefTracker::new(),
+        }
+    }
 }
 
 impl FantasyRealmsBot for AutomatosV2 {
     fn name(&self) -> String {
-        "AutomatosV2".to_string()
+        format!("AutomatosV2{}", self.objective.suffix())
     }
 
-    fn generate_draw(
-        &mut self,
-        hand: &Hand, 
-        discard_pile: &CardCollection, 
-        known_opponent_cards: &Vec<CardCollection>, 
-        minimum_turns_remaining: u8,
-    ) -> DrawCard {
-        let unknown_cards: CardCollection = !(
-            known_opponent_cards.iter().cloned().sum::<CardCollection>() + 
-            *discard_pile +
-            CardCollection::from(hand)
-        );
-        let mut turn = BotTurn::new(DrawCard::Deck, hand[0], 
-            evaluate_hand(hand, discard_pile, known_opponent_cards, minimum_turns_remaining)
-        );
-        for i in 0..7 {
-            for draw in discard_pile {
-                let mut test_hand = hand.clone();
-                test_hand[i] = draw;
-                let new_discard_pile = discard_pile.clone() + hand[i] - draw;
-                let evaluation: f32 = evaluate_hand(
-                    &test_hand, 
-                    &new_discard_pile, 
-                    known_opponent_cards, 
-                    minimum_turns_remaining,
-                );
-                if evaluation > turn.evaluation {
-                    turn = BotTurn::new(DrawCard::Discard (draw), hand[i], evaluation);
-                }
-            }
-        }
-        for i in 0..7 {
-            let mut evaluation: f32 = 0.0;
-            for draw in unknown_cards {
-                let mut test_hand = hand.clone();
-                test_hand[i] = draw;
-                let new_discard_pile = discard_pile.clone() + hand[i] - draw;
-                evaluation += evaluate_hand(
-                    &test_hand, 
-                    &new_discard_pile, 
-                    known_opponent_cards, 
-                    minimum_turns_remaining,
-                );
-            }
-            evaluation /= unknown_cards.len() as f32;
-            if evaluation > turn.evaluation {
-                turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation)
-            }
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        let belief = &self.belief.current(view, None);
+        if let Some(turn) = self.endgame.and_then(|endgame| {
+            endgame.solve_draw(hand, discard_pile, known_opponent_cards, belief, &mut self.rng)
+        }) {
+            self.discard = turn.discard;
+            return turn.draw;
         }
+        let search = Search {
+            belief,
+            evaluator: &Evaluator::new(self.objective, hand, discard_pile, known_opponent_cards, belief, None, &mut self.rng),
+            denial: &Denial::new(self.denial_weight, hand, discard_pile, known_opponent_cards, belief, None, &mut self.rng),
+            tempo: &Tempo::new(self.tempo_weight, hand, discard_pile, known_opponent_cards, belief, &mut self.rng),
+            deadline: None,
+        };
+        let depth = view.minimum_turns_remaining().min(self.max_depth);
+        let turn = search_draw(hand, discard_pile, &search, depth);
         if turn.draw == DrawCard::Deck {
             self.discard = None;
         } else {
@@ -84,87 +114,219 @@ impl FantasyRealmsBot for AutomatosV2 {
         turn.draw
     }
 
-    fn generate_discard(
-            &mut self,
-            hand: &Hand,
-            discard_pile: &CardCollection,
-            known_opponent_cards: &Vec<CardCollection>, 
-            minimum_turns_remaining: u8,
-            draw: &Card,
-        ) -> Card {
-        if self.discard.is_some() {
-            return self.discard.unwrap();
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        if let Some(discard) = self.discard.take() {
+            return discard;
+        }
+        let belief = &self.belief.current(view, Some(*draw));
+        if let Some(discard) = self.endgame.and_then(|endgame| {
+            endgame.solve_discard(hand, discard_pile, known_opponent_cards, belief, *draw, &mut self.rng)
+        }) {
+            return discard;
         }
-        let mut turn = BotTurn::new(DrawCard::Deck, *draw, hand.score(discard_pile) as f32);
-        for i in 0..7 {
-            let mut test_hand = hand.clone();
-            test_hand[i] = *draw;
-            let new_discard_pile = discard_pile.clone() + hand[i] - *draw;
-            let evaluation: f32 = evaluate_hand(
-                &test_hand, 
-                &new_discard_pile, 
-                known_opponent_cards, 
-                minimum_turns_remaining,
-            );
+        let search = Search {
+            belief,
+            evaluator: &Evaluator::new(self.objective, hand, discard_pile, known_opponent_cards, belief, Some(*draw), &mut self.rng),
+            denial: &Denial::new(self.denial_weight, hand, discard_pile, known_opponent_cards, belief, Some(*draw), &mut self.rng),
+            tempo: &Tempo::new(0.0, hand, discard_pile, known_opponent_cards, belief, &mut self.rng),
+            deadline: None,
+        };
+        let depth = view.minimum_turns_remaining().min(self.max_depth);
+        search_discard(hand, discard_pile, &search, depth, draw).discard
+    }
+
+    fn on_game_start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        self.discard = None;
+        self.belief.start(seat, players, hand);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Searches for the best draw.
+/// # Arguments
+/// * `hand` - A `&Hand` representing the bots current hand.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile.
+/// * `search` - A `&Search` containing the information shared by the search.
+/// * `depth` - A `u8` representing the number of future turns to search.
+/// # Returns
+/// A `BotTurn` representing the best draw and the card it plans to discard.
+pub(crate) fn search_draw(
+    hand: &Hand,
+    discard_pile: &CardCollection,
+    search: &Search,
+    depth: u8,
+) -> BotTurn {
+    let draw_probabilities = search.belief.draw_probabilities_after(hand);
+    let deck_draw_ends_game = discard_pile.len() as usize + 1 >= END_DISCARD_PILE_SIZE;
+    let penalty = |card: Card| -> f32 {
+        search.evaluator.points(search.denial.penalty(card))
+    };
+    let deck_bonus = search.evaluator.points(search.tempo.deck_bonus());
+    let mut turn = BotTurn::new(DrawCard::Deck, hand[0], 
+        evaluate_hand(hand, discard_pile, search, depth, true) + deck_bonus
+    );
+    let discard_draws: Vec<(usize, Card)> = (0..7)
+        .flat_map(|i| discard_pile.iter().map(move |draw| (i, draw)))
+        .collect();
+    let discard_evaluations: Vec<f32> = parallel_map(&discard_draws, |&(i, draw)| {
+        evaluate_swap(hand, discard_pile, search, depth, i, draw)
+    });
+    for (&(i, draw), &evaluation) in discard_draws.iter().zip(discard_evaluations.iter()) {
+        let evaluation: f32 = evaluation - penalty(hand[i]);
+        if evaluation > turn.evaluation {
+            turn = BotTurn::new(DrawCard::Discard (draw), hand[i], evaluation);
+        }
+    }
+    if !draw_probabilities.is_empty() {
+        let deck_draws: Vec<(usize, Card)> = (0..7)
+            .flat_map(|i| draw_probabilities.iter().map(move |&(draw, _)| (i, draw)))
+            .collect();
+        let deck_evaluations: Vec<f32> = parallel_map(&deck_draws, |&(i, draw)| {
+            evaluate_swap(hand, discard_pile, search, depth, i, draw)
+        });
+        for (i, slot_evaluations) in deck_evaluations.chunks(draw_probabilities.len()).enumerate() {
+            let mut evaluation: f32 = deck_bonus;
+            for (&(_, probability), &slot_evaluation) in draw_probabilities.iter().zip(slot_evaluations) {
+                evaluation += probability * slot_evaluation;
+            }
+            if !deck_draw_ends_game {
+                evaluation -= penalty(hand[i]);
+            }
             if evaluation > turn.evaluation {
-                turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation);
+                turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation)
             }
         }
-        turn.discard
     }
+    turn
 }
 
+/// Searches for the best discard after drawing from the deck.
+/// # Arguments
+/// * `hand` - A `&Hand` representing the bots current hand.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile.
+/// * `search` - A `&Search` containing the information shared by the search.
+/// * `depth` - A `u8` representing the number of future turns to search.
+/// * `draw` - A `&Card` representing the card drawn.
+/// # Returns
+/// A `BotTurn` representing the best discard.
+pub(crate) fn search_discard(
+    hand: &Hand,
+    discard_pile: &CardCollection,
+    search: &Search,
+    depth: u8,
+    draw: &Card,
+) -> BotTurn {
+    let penalty = |card: Card| -> f32 {
+        if discard_pile.len() as usize + 1 >= END_DISCARD_PILE_SIZE {
+            0.0
+        } else {
+            search.evaluator.points(search.denial.penalty(card))
+        }
+    };
+    let mut turn = BotTurn::new(DrawCard::Deck, *draw, 
+        search.evaluator.evaluate(hand, discard_pile) - penalty(*draw)
+    );
+    let slots: Vec<usize> = (0..7).collect();
+    let evaluations: Vec<f32> = parallel_map(&slots, |&i| {
+        evaluate_swap(hand, discard_pile, search, depth, i, *draw)
+    });
+    for (i, &evaluation) in evaluations.iter().enumerate() {
+        let evaluation: f32 = evaluation - penalty(hand[i]);
+        if evaluation > turn.evaluation {
+            turn = BotTurn::new(DrawCard::Deck, hand[i], evaluation);
+        }
+    }
+    turn
+}
+
+/// Evaluates the best expected value a hand can reach.
+/// # Arguments
+/// * `hand` - A `&Hand` representing the hand to evaluate.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile.
+/// * `search` - A `&Search` containing the information shared by the search.
+/// * `depth` - A `u8` representing the number of future turns to search.
+/// * `parallel` - A `bool` that spreads the candidate turns across threads.
+///   Only the outermost call is parallel so that threads are not nested.
+/// # Returns
+/// A `f32` representing the evaluation of the hand.
 fn evaluate_hand(
     hand: &Hand, 
     discard_pile: &CardCollection, 
-    known_opponent_cards: &Vec<CardCollection>, 
-    minimum_turns_remaining: u8,
+    search: &Search,
+    depth: u8,
+    parallel: bool,
 ) -> f32 {
-    if minimum_turns_remaining == 0 {
-        return hand.score(discard_pile) as f32;
-    }
-    if minimum_turns_remaining > 1 {
-        return evaluate_hand(hand, discard_pile, known_opponent_cards, 1);
+    if depth == 0 || search.deadline.is_some_and(|deadline| Instant::now() >= deadline) {
+        return search.evaluator.evaluate(hand, discard_pile);
     }
-    let unknown_cards: CardCollection = !(
-        known_opponent_cards.iter().cloned().sum::<CardCollection>() + 
-        *discard_pile +
-        CardCollection::from(hand)
-    );
-    let mut max_evaluation: f32 = hand.score(discard_pile) as f32;
-    for i in 0..7 {
-        for draw in discard_pile {
-            let mut test_hand = hand.clone();
-            test_hand[i] = draw;
-            let new_discard_pile = discard_pile.clone() + hand[i] - draw;
-            let evaluation: f32 = evaluate_hand(
-                &test_hand,
-                &new_discard_pile,
-                known_opponent_cards,
-                minimum_turns_remaining - 1,
-            );
-            if evaluation > max_evaluation {
-                max_evaluation = evaluation;
-            }
+    let draw_probabilities = search.belief.draw_probabilities_after(hand);
+    let evaluate = |&(i, draw): &(usize, Card)| -> f32 {
+        evaluate_swap(hand, discard_pile, search, depth - 1, i, draw)
+    };
+    let mut max_evaluation: f32 = search.evaluator.evaluate(hand, discard_pile);
+    let discard_draws: Vec<(usize, Card)> = (0..7)
+        .flat_map(|i| discard_pile.iter().map(move |draw| (i, draw)))
+        .collect();
+    let discard_evaluations: Vec<f32> = if parallel {
+        parallel_map(&discard_draws, evaluate)
+    } else {
+        discard_draws.iter().map(&evaluate).collect()
+    };
+    for &evaluation in &discard_evaluations {
+        if evaluation > max_evaluation {
+            max_evaluation = evaluation;
         }
     }
-    for i in 0..7 {
+    if draw_probabilities.is_empty() {
+        return max_evaluation;
+    }
+    let deck_draws: Vec<(usize, Card)> = (0..7)
+        .flat_map(|i| draw_probabilities.iter().map(move |&(draw, _)| (i, draw)))
+        .collect();
+    let deck_evaluations: Vec<f32> = if parallel {
+        parallel_map(&deck_draws, evaluate)
+    } else {
+        deck_draws.iter().map(&evaluate).collect()
+    };
+    for slot_evaluations in deck_evaluations.chunks(draw_probabilities.len()) {
         let mut evaluation: f32 = 0.0;
-        for draw in unknown_cards {
-            let mut test_hand = hand.clone();
-            test_hand[i] = draw;
-            let new_discard_pile = discard_pile.clone() + hand[i] - draw;
-            evaluation += evaluate_hand(
-                &test_hand,
-                &new_discard_pile,
-                known_opponent_cards,
-                minimum_turns_remaining - 1,
-            );
+        for (&(_, probability), &slot_evaluation) in draw_probabilities.iter().zip(slot_evaluations) {
+            evaluation += probability * slot_evaluation;
         }
-        evaluation /= unknown_cards.len() as f32;
         if evaluation > max_evaluation {
             max_evaluation = evaluation;
         }
     }
     max_evaluation
+}
+
+/// Helper function for `evaluate_hand`.
+/// Evaluates a hand after drawing a card in place of one of its cards.
+/// # Arguments
+/// * `hand` - A `&Hand` representing the hand before the swap.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile before the swap.
+/// * `search` - A `&Search` containing the information shared by the search.
+/// * `depth` - A `u8` representing the number of future turns to search after the swap.
+/// * `i` - A `usize` representing the position of the card discarded.
+/// * `draw` - A `Card` representing the card drawn.
+/// # Returns
+/// A `f32` representing the evaluation of the new hand.
+fn evaluate_swap(
+    hand: &Hand,
+    discard_pile: &CardCollection,
+    search: &Search,
+    depth: u8,
+    i: usize,
+    draw: Card,
+) -> f32 {
+    let mut test_hand = *hand;
+    test_hand[i] = draw;
+    let new_discard_pile = *discard_pile + hand[i] - draw;
+    evaluate_hand(&test_hand, &new_discard_pile, search, depth, false)
 }
\ No newline at end of file
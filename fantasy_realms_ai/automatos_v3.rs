@@ -0,0 +1,178 @@
+//! # AutomatosV3
+//! AutomatosV3 uses the same search as AutomatosV2 but is limited by time instead of depth.
+//! It searches one move deeper at a time until its time budget runs out,
+//! then plays the best move from the deepest search it finished.
+//! This uses more depth on fast machines while keeping physical games moving.
+//! Like AutomatosV2 it can maximise either its score or its chance of winning,
+//! and it can hurry or stall the end of the game depending on whether it is ahead.
+//! Near the end of the game it hands off to the endgame search.
+
+use std::time::{Duration, Instant};
+use rand::{rngs::StdRng, SeedableRng};
+use fantasy_realms_unofficial_api::{deck::Card, hand::Hand, DrawCard};
+use super::{FantasyRealmsBot, GameView};
+use super::automatos_v2::{search_discard, search_draw, BotTurn, Search};
+use super::belief::BeliefTracker;
+use super::denial::{Denial, DEFAULT_DENIAL_WEIGHT};
+use super::endgame::EndgameSolver;
+use super::objective::{Evaluator, Objective};
+use super::tempo::{Tempo, DEFAULT_TEMPO_WEIGHT};
+
+/// The default time spent searching for each decision.
+pub const DEFAULT_TIME_BUDGET: Duration = Duration::from_secs(2);
+
+pub struct AutomatosV3 {
+    pub(crate) discard: Option<Card>,
+    pub(crate) denial_weight: f32,
+    pub(crate) endgame: Option<EndgameSolver>,
+    pub(crate) objective: Objective,
+    pub(crate) tempo_weight: f32,
+    pub(crate) time_budget: Duration,
+    pub(crate) rng: StdRng,
+    pub(crate) belief: BeliefTracker,
+}
+
+impl AutomatosV3 {
+    /// Creates an AutomatosV3 bot with the default settings.
+    /// # Arguments
+    /// * `objective` - An `Objective` the bot maximises.
+    /// # Returns
+    /// An `AutomatosV3` bot.
+    pub fn new(objective: Objective) -> Self {
+        AutomatosV3 {
+            discard: None,
+            denial_weight: DEFAULT_DENIAL_WEIGHT,
+            endgame: Some(EndgameSolver::new(objective)),
+            objective,
+            tempo_weight: DEFAULT_TEMPO_WEIGHT,
+            time_budget: DEFAULT_TIME_BUDGET,
+            rng: StdRng::from_entropy(),
+            belief: BeliefTracker::new(),
+        }
+    }
+}
+
+impl FantasyRealmsBot for AutomatosV3 {
+    fn name(&self) -> String {
+        format!("AutomatosV3{}", self.objective.suffix())
+    }
+
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        let belief = &self.belief.current(view, None);
+        if let Some(turn) = self.endgame.and_then(|endgame| {
+            endgame.solve_draw(hand, discard_pile, known_opponent_cards, belief, &mut self.rng)
+        }) {
+            self.discard = turn.discard;
+            return turn.draw;
+        }
+        let deadline = Instant::now() + self.time_budget;
+        let evaluator = Evaluator::new(self.objective, hand, discard_pile, known_opponent_cards, belief, None, &mut self.rng);
+        let denial = Denial::new(self.denial_weight, hand, discard_pile, known_opponent_cards, belief, None, &mut self.rng);
+        let tempo = Tempo::new(self.tempo_weight, hand, discard_pile, known_opponent_cards, belief, &mut self.rng);
+        let turn = deepen(view.minimum_turns_remaining(), deadline, |depth, deadline| {
+            let search = Search {belief, evaluator: &evaluator, denial: &denial, tempo: &tempo, deadline};
+            search_draw(hand, discard_pile, &search, depth)
+        });
+        if turn.draw == DrawCard::Deck {
+            self.discard = None;
+        } else {
+            self.discard = Some(turn.discard);
+        }
+        turn.draw
+    }
+
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        if let Some(discard) = self.discard.take() {
+            return discard;
+        }
+        let belief = &self.belief.current(view, Some(*draw));
+        if let Some(discard) = self.endgame.and_then(|endgame| {
+            endgame.solve_discard(hand, discard_pile, known_opponent_cards, belief, *draw, &mut self.rng)
+        }) {
+            return discard;
+        }
+        let deadline = Instant::now() + self.time_budget;
+        let evaluator = Evaluator::new(self.objective, hand, discard_pile, known_opponent_cards, belief, Some(*draw), &mut self.rng);
+        let denial = Denial::new(self.denial_weight, hand, discard_pile, known_opponent_cards, belief, Some(*draw), &mut self.rng);
+        let tempo = Tempo::new(0.0, hand, discard_pile, known_opponent_cards, belief, &mut self.rng);
+        deepen(view.minimum_turns_remaining(), deadline, |depth, deadline| {
+            let search = Search {belief, evaluator: &evaluator, denial: &denial, tempo: &tempo, deadline};
+            search_discard(hand, discard_pile, &search, depth, draw)
+        }).discard
+    }
+
+    fn on_game_start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        self.discard = None;
+        self.belief.start(seat, players, hand);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Runs a search at increasing depths until the deadline passes.
+/// The search with no depth always finishes so that there is a move to play.
+/// # Arguments
+/// * `max_depth` - A `u8` representing the deepest search to attempt.
+/// * `deadline` - An `Instant` after which no deeper search is started.
+/// * `search` - A `Fn(u8, Option<Instant>) -> BotTurn` that searches at a depth.
+/// # Returns
+/// A `BotTurn` from the deepest search that finished before the deadline.
+fn deepen<F: Fn(u8, Option<Instant>) -> BotTurn>(max_depth: u8, deadline: Instant, search: F) -> BotTurn {
+    let mut turn = search(0, None);
+    for depth in 1..=max_depth {
+        if Instant::now() >= deadline {
+            break;
+        }
+        let deeper = search(depth, Some(deadline));
+        if Instant::now() >= deadline {
+            break;
+        }
+        turn = deeper;
+    }
+    turn
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A search that takes `step` for each level of depth, or until its deadline, and records its depth as the discard.
+    fn timed_search(step: Duration) -> impl Fn(u8, Option<Instant>) -> BotTurn {
+        move |depth, deadline| {
+            let finish = Instant::now() + step * depth as u32;
+            let stop = deadline.map_or(finish, |deadline| finish.min(deadline));
+            std::thread::sleep(stop.saturating_duration_since(Instant::now()));
+            BotTurn {draw: DrawCard::Deck, discard: Card::from(depth), evaluation: 0.0}
+        }
+    }
+
+    #[test]
+    fn the_deepest_search_to_finish_before_the_deadline_is_used() {
+        let start = Instant::now();
+        let deadline = start + Duration::from_millis(100);
+        let turn = deepen(5, deadline, timed_search(Duration::from_millis(30)));
+        assert_eq!(turn.discard, Card::from(2));
+        assert!(start.elapsed() < Duration::from_millis(150));
+    }
+
+    #[test]
+    fn no_search_goes_deeper_than_the_turns_remaining() {
+        let deadline = Instant::now() + Duration::from_secs(60);
+        let turn = deepen(2, deadline, timed_search(Duration::from_millis(1)));
+        assert_eq!(turn.discard, Card::from(2));
+    }
+
+    #[test]
+    fn a_move_is_made_even_when_the_deadline_has_passed() {
+        let turn = deepen(5, Instant::now(), timed_search(Duration::from_millis(30)));
+        assert_eq!(turn.discard, Card::from(0));
+    }
+}
@@ -0,0 +1,463 @@
+//! # Belief
+//! Tracks the probability that each opponent holds each unknown card.
+//! Every unknown card is either hidden in an opponents hand or in the deck,
+//! so the probabilities for each card sum to one and the probabilities
+//! for each opponent sum to the number of hidden cards in their hand.
+//! Observed draws and discards move probability between the deck and opponents hands.
+//! Picking a card up from the discard pile makes the other cards of its suit more likely
+//! to be in that opponents hand, and discarding a card makes them less likely.
+//! The deck probabilities give the chance of each card being drawn from the deck,
+//! which excludes the cards opponents are likely to be holding.
+//! Bots keep one belief for the whole game in a `BeliefTracker`.
+
+use rand::Rng;
+use fantasy_realms_unofficial_api::{card_collection::{CardCollection, CardCollectionContains}, deck::{Card, Suit}, hand::Hand, DrawCard};
+use super::{GameView, ObservedTurn};
+
+/// The number of rescaling passes used to keep the probabilities consistent.
+const NORMALISE_PASSES: usize = 20;
+/// How much more likely an opponent is to hold each card of a suit
+/// after picking a card of that suit up from the discard pile.
+const PICK_UP_LIKELIHOOD: f32 = 2.0;
+/// How likely an opponent is to hold each card of a suit after discarding a card of that suit,
+/// compared to before.
+const DISCARD_LIKELIHOOD: f32 = 0.75;
+/// Every suit in the game.
+const SUITS: [Suit; 11] = [
+    Suit::Land, Suit::Flood, Suit::Weather, Suit::Flame, Suit::Army, Suit::Wizard,
+    Suit::Leader, Suit::Beast, Suit::Weapon, Suit::Artifact, Suit::Wild,
+];
+
+/// A belief about where the unknown cards are.
+/// # Fields
+/// * `cards` - A `Vec<Card>` containing every card whose location is unknown.
+/// * `holdings` - A `Vec<Vec<f32>>` containing, for each opponent, the probability they hold each card.
+/// * `deck` - A `Vec<f32>` containing the probability each card is in the deck.
+/// * `hidden_cards` - A `Vec<usize>` containing the number of unknown cards in each opponents hand.
+#[derive(Clone)]
+pub struct Belief {
+    cards: Vec<Card>,
+    holdings: Vec<Vec<f32>>,
+    deck: Vec<f32>,
+    hidden_cards: Vec<usize>,
+}
+
+impl Belief {
+    /// Creates a belief where each unknown card is equally likely to be in any unknown position.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// # Returns
+    /// A `Belief` with one entry for each opponent.
+    pub fn new(
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+    ) -> Self {
+        let cards: Vec<Card> = (!(
+            known_opponent_cards.iter().cloned().sum::<CardCollection>() +
+            *discard_pile +
+            CardCollection::from(hand)
+        )).iter().collect();
+        let hidden_cards: Vec<usize> = known_opponent_cards
+            .iter()
+            .map(|known| 7 - (known.len() as usize).min(7))
+            .collect();
+        let unknown = cards.len().max(1) as f32;
+        let holdings: Vec<Vec<f32>> = hidden_cards
+            .iter()
+            .map(|&hidden| vec![hidden as f32 / unknown; cards.len()])
+            .collect();
+        let mut belief = Belief {
+            deck: Vec::new(),
+            cards,
+            holdings,
+            hidden_cards,
+        };
+        belief.deck = vec![belief.deck_size() as f32 / unknown; belief.cards.len()];
+        belief
+    }
+
+    /// Gets the cards whose location is unknown.
+    /// # Returns
+    /// A `&Vec<Card>` containing every unknown card.
+    pub fn unknown_cards(&self) -> &Vec<Card> {
+        &self.cards
+    }
+
+    /// Gets the number of cards left in the deck.
+    /// # Returns
+    /// A `usize` representing the unknown cards not hidden in opponents hands.
+    pub fn deck_size(&self) -> usize {
+        self.cards.len().saturating_sub(self.hidden_cards.iter().sum())
+    }
+
+    /// Gets the probability that an opponent holds a card.
+    /// # Arguments
+    /// * `opponent` - A `usize` representing the index of the opponent.
+    /// * `card` - A `Card` representing the card.
+    /// # Returns
+    /// A `f32` that is 0 for any card that is not unknown.
+    pub fn holding_probability(&self, opponent: usize, card: Card) -> f32 {
+        match self.index_of(card) {
+            Some(i) => self.holdings[opponent][i],
+            None => 0.0,
+        }
+    }
+
+    /// Gets the probability that a card is in the deck.
+    /// # Arguments
+    /// * `card` - A `Card` representing the card.
+    /// # Returns
+    /// A `f32` that is 0 for any card that is not unknown.
+    pub fn deck_probability(&self, card: Card) -> f32 {
+        match self.index_of(card) {
+            Some(i) => self.deck[i],
+            None => 0.0,
+        }
+    }
+
+    /// Gets the probability of drawing each unknown card from the top of the deck.
+    /// # Returns
+    /// A `Vec<(Card, f32)>` pairing each unknown card with the probability it is drawn.
+    /// The vector is empty if the deck is empty.
+    pub fn deck_draw_probabilities(&self) -> Vec<(Card, f32)> {
+        let deck_size = self.deck_size();
+        if deck_size == 0 {
+            return Vec::new();
+        }
+        self.cards
+            .iter()
+            .zip(self.deck.iter())
+            .map(|(&card, &probability)| (card, probability / deck_size as f32))
+            .collect()
+    }
+
+    /// Gets the probability of drawing each unknown card from the deck once a hand has been reached.
+    /// Cards in the hand must have come off the deck, so they are left out and the rest are rescaled.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing a hand the bot could have later in its search.
+    /// # Returns
+    /// A `Vec<(Card, f32)>` pairing each card that could still be drawn with the probability it is drawn.
+    pub fn draw_probabilities_after(&self, hand: &Hand) -> Vec<(Card, f32)> {
+        let probabilities: Vec<(Card, f32)> = self.deck_draw_probabilities()
+            .into_iter()
+            .filter(|(card, _)| !hand.contains(card))
+            .collect();
+        let total: f32 = probabilities.iter().map(|(_, probability)| probability).sum();
+        if total <= 0.0 {
+            return probabilities;
+        }
+        probabilities
+            .into_iter()
+            .map(|(card, probability)| (card, probability / total))
+            .collect()
+    }
+
+    /// Updates the belief after an opponent plays a turn.
+    /// Besides moving the cards drawn and discarded, a card picked up from the discard pile
+    /// makes the rest of its suit more likely to be held, and a card discarded makes its suit less likely.
+    /// # Arguments
+    /// * `opponent` - A `usize` representing the index of the opponent.
+    /// * `turn` - A `&ObservedTurn` representing the turn they played.
+    pub fn observe_turn(&mut self, opponent: usize, turn: &ObservedTurn) {
+        if let DrawCard::Discard (card) = turn.draw {
+            let suit = suit_of(card);
+            self.weigh(opponent, |unknown| if suit_of(unknown) == suit { PICK_UP_LIKELIHOOD } else { 1.0 });
+        }
+        self.observe_draw(opponent, turn.draw);
+        self.observe_discard(opponent, turn.discard);
+        let suit = suit_of(turn.discard);
+        self.weigh(opponent, |unknown| if suit_of(unknown) == suit { DISCARD_LIKELIHOOD } else { 1.0 });
+    }
+
+    /// Updates the belief after an opponent draws a card.
+    /// A draw from the deck moves an unknown card into their hand.
+    /// A draw from the discard pile is a known card, so nothing unknown changes.
+    /// # Arguments
+    /// * `opponent` - A `usize` representing the index of the opponent.
+    /// * `draw` - A `DrawCard` representing where the opponent drew from.
+    pub fn observe_draw(&mut self, opponent: usize, draw: DrawCard) {
+        if draw != DrawCard::Deck {
+            return;
+        }
+        let deck_size = self.deck_size();
+        if deck_size == 0 {
+            return;
+        }
+        for i in 0..self.cards.len() {
+            let moved = self.deck[i] / deck_size as f32;
+            self.deck[i] -= moved;
+            self.holdings[opponent][i] += moved;
+        }
+        self.hidden_cards[opponent] += 1;
+    }
+
+    /// Updates the belief after an opponent discards a card.
+    /// If the card was unknown it must have been hidden in their hand.
+    /// # Arguments
+    /// * `opponent` - A `usize` representing the index of the opponent.
+    /// * `discard` - A `Card` representing the card discarded.
+    pub fn observe_discard(&mut self, opponent: usize, discard: Card) {
+        if let Some(i) = self.index_of(discard) {
+            self.hidden_cards[opponent] = self.hidden_cards[opponent].saturating_sub(1);
+            self.remove(i);
+        }
+    }
+
+    /// Updates the belief after a card is revealed outside opponents hands,
+    /// such as a card the bot drew from the deck.
+    /// # Arguments
+    /// * `card` - A `Card` representing the card revealed.
+    pub fn reveal(&mut self, card: Card) {
+        if let Some(i) = self.index_of(card) {
+            self.remove(i);
+        }
+    }
+
+    /// Applies evidence about an opponents hand using Bayes rule.
+    /// # Arguments
+    /// * `opponent` - A `usize` representing the index of the opponent.
+    /// * `likelihood` - A `Fn(Card) -> f32` giving the relative likelihood of the evidence
+    ///   if the opponent holds the card.
+    pub fn weigh<F: Fn(Card) -> f32>(&mut self, opponent: usize, likelihood: F) {
+        for i in 0..self.cards.len() {
+            self.holdings[opponent][i] *= likelihood(self.cards[i]).max(0.0);
+        }
+        self.normalise();
+    }
+
+    /// Samples the hidden cards in each opponents hand.
+    /// Cards are dealt to each opponent in turn, weighted by how likely they are to hold them.
+    /// # Arguments
+    /// * `rng` - A `&mut R` random number generator.
+    /// # Returns
+    /// A `Vec<Vec<Card>>` containing the hidden cards sampled for each opponent.
+    pub fn sample_hidden_cards<R: Rng>(&self, rng: &mut R) -> Vec<Vec<Card>> {
+        let mut remaining: Vec<usize> = (0..self.cards.len()).collect();
+        let mut hidden_hands: Vec<Vec<Card>> = Vec::new();
+        for (holding, &hidden) in self.holdings.iter().zip(self.hidden_cards.iter()) {
+            let mut cards: Vec<Card> = Vec::new();
+            while cards.len() < hidden && !remaining.is_empty() {
+                let total: f32 = remaining.iter().map(|&i| holding[i]).sum();
+                let mut target: f32 = rng.gen::<f32>() * total;
+                let mut chosen = rng.gen_range(0..remaining.len());
+                if total > 0.0 {
+                    for (position, &i) in remaining.iter().enumerate() {
+                        chosen = position;
+                        target -= holding[i];
+                        if target <= 0.0 {
+                            break;
+                        }
+                    }
+                }
+                cards.push(self.cards[remaining.remove(chosen)]);
+            }
+            hidden_hands.push(cards);
+        }
+        hidden_hands
+    }
+
+    /// Helper function for `BeliefTracker`.
+    /// Removes every unknown card that has since become known
+    /// and sets the hidden cards of each opponent from the cards known to be in their hand.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    fn sync(&mut self, hand: &Hand, discard_pile: &CardCollection, known_opponent_cards: &[CardCollection]) {
+        let known: CardCollection = known_opponent_cards.iter().copied().sum::<CardCollection>() +
+            *discard_pile +
+            CardCollection::from(hand);
+        while let Some(i) = self.cards.iter().position(|&card| known.contains(card)) {
+            self.take_out(i);
+        }
+        for (hidden, known) in self.hidden_cards.iter_mut().zip(known_opponent_cards) {
+            *hidden = 7 - (known.len() as usize).min(7);
+        }
+        self.normalise();
+    }
+
+    /// Helper function for the belief updates.
+    /// Removes an unknown card and rescales the remaining probabilities.
+    fn remove(&mut self, i: usize) {
+        self.take_out(i);
+        self.normalise();
+    }
+
+    /// Helper function for `remove` and `sync`.
+    /// Removes an unknown card without rescaling.
+    fn take_out(&mut self, i: usize) {
+        self.cards.remove(i);
+        self.deck.remove(i);
+        for holding in self.holdings.iter_mut() {
+            holding.remove(i);
+        }
+    }
+
+    /// Helper function for the belief updates.
+    /// Rescales the probabilities so each card sums to one,
+    /// each opponent sums to their hidden cards and the deck sums to its size.
+    fn normalise(&mut self) {
+        let deck_size = self.deck_size() as f32;
+        for _ in 0..NORMALISE_PASSES {
+            for (holding, &hidden) in self.holdings.iter_mut().zip(self.hidden_cards.iter()) {
+                scale_to(holding, hidden as f32);
+            }
+            scale_to(&mut self.deck, deck_size);
+            for i in 0..self.cards.len() {
+                let total: f32 = self.deck[i] + self.holdings.iter().map(|holding| holding[i]).sum::<f32>();
+                if total > 0.0 {
+                    self.deck[i] /= total;
+                    for holding in self.holdings.iter_mut() {
+                        holding[i] /= total;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Helper function to find the position of an unknown card.
+    fn index_of(&self, card: Card) -> Option<usize> {
+        self.cards.iter().position(|unknown| *unknown == card)
+    }
+}
+
+/// A belief kept up to date for a whole game.
+/// It is started with the bots hand in `on_game_start`, and every time the bot decides
+/// it applies the turns in the history of the `GameView` that it has not seen yet.
+/// # Fields
+/// * `seat` - A `usize` representing the bots seat.
+/// * `belief` - An `Option<Belief>` which is `None` until a game starts.
+/// * `observed` - A `usize` representing the number of turns in the history already applied.
+#[derive(Clone, Default)]
+pub struct BeliefTracker {
+    seat: usize,
+    belief: Option<Belief>,
+    observed: usize,
+}
+
+impl BeliefTracker {
+    /// Creates a tracker with no game started.
+    /// # Returns
+    /// A `BeliefTracker`.
+    pub fn new() -> Self {
+        BeliefTracker::default()
+    }
+
+    /// Starts tracking a new game, forgetting the last one.
+    /// # Arguments
+    /// * `seat` - A `usize` representing the bots seat.
+    /// * `players` - A `usize` representing the number of players in the game.
+    /// * `hand` - A `&Hand` representing the bots starting hand.
+    pub fn start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        let known_opponent_cards = vec![CardCollection::new(); players.saturating_sub(1)];
+        self.seat = seat;
+        self.belief = Some(Belief::new(hand, &CardCollection::new(), &known_opponent_cards));
+        self.observed = 0;
+    }
+
+    /// Brings the belief up to date with the view and gets a copy of it.
+    /// If no game was started for this view, such as a bot asked about a single position,
+    /// a belief is created from the view with every unknown card equally likely to be anywhere.
+    /// # Arguments
+    /// * `view` - A `&GameView` representing the game as seen by the bot.
+    /// * `drawn` - An `Option<Card>` representing a card the bot has drawn from the deck.
+    /// # Returns
+    /// A `Belief` about the cards that are still unknown.
+    pub fn current(&mut self, view: &GameView, drawn: Option<Card>) -> Belief {
+        let known_opponent_cards = view.known_opponent_cards();
+        let players = view.players();
+        let mut belief = match self.belief.as_mut() {
+            Some(belief) if self.seat == view.seat &&
+                belief.hidden_cards.len() == known_opponent_cards.len() &&
+                self.observed <= view.history.len() => {
+                for turn in &view.history[self.observed..] {
+                    if turn.seat != self.seat {
+                        belief.observe_turn((turn.seat + players - self.seat - 1) % players, turn);
+                    }
+                }
+                self.observed = view.history.len();
+                belief.sync(&view.hand, &view.discard_pile, &known_opponent_cards);
+                belief.clone()
+            }
+            _ => Belief::new(&view.hand, &view.discard_pile, &known_opponent_cards),
+        };
+        if let Some(card) = drawn {
+            belief.reveal(card);
+        }
+        belief
+    }
+}
+
+/// Helper function for `Belief::observe_turn`.
+/// Finds the suit of a card.
+fn suit_of(card: Card) -> Option<Suit> {
+    SUITS.into_iter().find(|&suit| (CardCollection::new() + suit).contains(card))
+}
+
+/// Helper function for `normalise`.
+/// Scales probabilities so that they sum to a target.
+fn scale_to(probabilities: &mut [f32], target: f32) {
+    let total: f32 = probabilities.iter().sum();
+    if total > 0.0 {
+        for probability in probabilities.iter_mut() {
+            *probability *= target / total;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card(name: &str) -> Card {
+        name.parse().unwrap()
+    }
+
+    fn hand(names: [&str; 7]) -> Hand {
+        Hand::new(names.map(card))
+    }
+
+    #[test]
+    fn picking_up_a_flood_makes_other_floods_more_likely() {
+        let mut tracker = BeliefTracker::new();
+        tracker.start(0, 3, &hand(["great flood", "knights", "elven archers", "light cavalry", "dwarvish infantry", "rangers", "collector"]));
+        let history = vec![
+            ObservedTurn {seat: 0, draw: DrawCard::Deck, discard: card("great flood")},
+            ObservedTurn {seat: 1, draw: DrawCard::Discard (card("great flood")), discard: card("king")},
+            ObservedTurn {seat: 2, draw: DrawCard::Deck, discard: card("queen")},
+        ];
+        let current_hand = hand(["beastmaster", "knights", "elven archers", "light cavalry", "dwarvish infantry", "rangers", "collector"]);
+        let known_cards = vec![CardCollection::new(), CardCollection::new() + card("great flood"), CardCollection::new()];
+        let discard_pile = CardCollection::new() + card("king") + card("queen");
+        let view = GameView::new(current_hand, 0, known_cards, discard_pile, history);
+
+        let tracked = tracker.current(&view, None);
+        let fresh = Belief::new(&view.hand, &view.discard_pile, &view.known_opponent_cards());
+        for flood in ["island", "swamp", "water elemental", "fountain of life"] {
+            assert!(tracked.holding_probability(0, card(flood)) > fresh.holding_probability(0, card(flood)));
+            assert!(tracked.holding_probability(0, card(flood)) > tracked.holding_probability(1, card(flood)));
+        }
+        assert!(tracked.holding_probability(0, card("great flood")) == 0.0);
+    }
+
+    #[test]
+    fn probabilities_stay_consistent_after_updates() {
+        let mut belief = Belief::new(
+            &hand(["knights", "elven archers", "light cavalry", "dwarvish infantry", "rangers", "collector", "beastmaster"]),
+            &CardCollection::new(),
+            &[CardCollection::new(), CardCollection::new()],
+        );
+        belief.observe_turn(0, &ObservedTurn {seat: 1, draw: DrawCard::Deck, discard: card("island")});
+        for &unknown in belief.unknown_cards() {
+            let total = belief.deck_probability(unknown) +
+                belief.holding_probability(0, unknown) +
+                belief.holding_probability(1, unknown);
+            assert!((total - 1.0).abs() < 1e-3);
+        }
+        let draws: f32 = belief.deck_draw_probabilities().iter().map(|(_, probability)| probability).sum();
+        assert!((draws - 1.0).abs() < 1e-3);
+    }
+}
@@ -0,0 +1,65 @@
+//! # Card Numbers
+//! The numbers cards are passed as to plugins and WebAssembly bots.
+//! The numbers are part of both ABIs, so they are written out in `NUMBERED_CARDS` instead of
+//! following the order of the deck, and cannot change without a new ABI version.
+//! Each suit takes five numbers in a row, from Land at 0 to Artifact at 45, and the wild cards are 50 to 52.
+
+use fantasy_realms_unofficial_api::deck::{Card, Name};
+
+/// The number of cards in the full deck.
+pub const CARD_COUNT: usize = 53;
+
+/// The name of the card with each number.
+pub const NUMBERED_CARDS: [Name; CARD_COUNT] = [
+    Name::Mountain, Name::Cavern, Name::BellTower, Name::Forest, Name::EarthElemental,
+    Name::FountainOfLife, Name::Swamp, Name::GreatFlood, Name::Island, Name::WaterElemental,
+    Name::Rainstorm, Name::Blizzard, Name::Smoke, Name::Whirlwind, Name::AirElemental,
+    Name::Wildfire, Name::Candle, Name::Forge, Name::Lightning, Name::FireElemental,
+    Name::Knights, Name::ElvenArchers, Name::LightCavalry, Name::DwarvishInfantry, Name::Rangers,
+    Name::Collector, Name::Beastmaster, Name::Necromancer, Name::WarlockLord, Name::Enchantress,
+    Name::King, Name::Queen, Name::Princess, Name::Warlord, Name::Empress,
+    Name::Unicorn, Name::Basilisk, Name::Warhorse, Name::Dragon, Name::Hydra,
+    Name::Warship, Name::MagicWand, Name::SwordOfKeth, Name::ElvenLongbow, Name::WarDirigible,
+    Name::ShieldOfKeth, Name::GemOfOrder, Name::WorldTree, Name::BookOfChanges, Name::ProtectionRune,
+    Name::Shapeshifter, Name::Mirage, Name::Doppelganger,
+];
+
+/// Gets the number of a card.
+/// # Arguments
+/// * `card` - A `Card` in the deck.
+/// # Panics
+/// * The card is not in the full deck.
+/// # Returns
+/// A `u8` below `CARD_COUNT`.
+pub fn card_number(card: Card) -> u8 {
+    NUMBERED_CARDS
+        .iter()
+        .position(|&name| Card::from(name) == card)
+        .expect("Card missing from the deck.") as u8
+}
+
+/// Gets the card with a number.
+/// # Arguments
+/// * `number` - A `usize` given by a plugin or WebAssembly bot.
+/// # Returns
+/// An `Option<Card>` which is `None` if no card has the number.
+pub fn numbered_card(number: usize) -> Option<Card> {
+    NUMBERED_CARDS.get(number).map(|&name| Card::from(name))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use fantasy_realms_unofficial_api::card_collection::CardCollection;
+
+    #[test]
+    fn numbers_match_the_order_of_the_deck() {
+        let deck: Vec<Card> = (!CardCollection::new()).iter().collect();
+        assert_eq!(deck.len(), CARD_COUNT);
+        for (number, card) in deck.into_iter().enumerate() {
+            assert_eq!(card_number(card) as usize, number);
+            assert_eq!(numbered_card(number), Some(card));
+        }
+        assert_eq!(numbered_card(CARD_COUNT), None);
+    }
+}
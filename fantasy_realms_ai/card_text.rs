@@ -0,0 +1,124 @@
+//! # Card Text
+//! The text cards, draws and hands are written as, shared by game records and the external bot protocol.
+//! Fields are separated by `|`, lists of cards by `,` and an empty list is written as `-`.
+//! A draw is written as `deck` or as the name of the card taken from the discard pile.
+
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
+
+/// Splits a line into its trimmed fields.
+/// # Arguments
+/// * `line` - A `&str` containing fields separated by `|`.
+/// # Returns
+/// A `Vec<&str>` containing the fields in order.
+pub fn fields(line: &str) -> Vec<&str> {
+    line.trim().split('|').map(str::trim).collect()
+}
+
+/// Writes a list of cards.
+/// # Arguments
+/// * `cards` - The cards to write, in order.
+/// # Returns
+/// A `String` containing the names of the cards separated by `,`, or `-` if there are none.
+pub fn encode_cards<I: IntoIterator<Item = Card>>(cards: I) -> String {
+    let names: Vec<String> = cards.into_iter().map(|card| card.to_string()).collect();
+    if names.is_empty() {
+        "-".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Writes a draw.
+/// # Arguments
+/// * `draw` - A `DrawCard` representing where the card was drawn from.
+/// # Returns
+/// A `String` containing `deck` or the name of the card taken from the discard pile.
+pub fn encode_draw(draw: DrawCard) -> String {
+    match draw {
+        DrawCard::Deck => "deck".to_string(),
+        DrawCard::Discard (card) => card.to_string(),
+    }
+}
+
+/// Reads a card.
+/// Wildfire is written `wild fire` but only read as `wildfire`, so names are also read without spaces.
+/// # Arguments
+/// * `field` - A `&str` containing the name of the card.
+/// # Errors
+/// This function returns an `Err(String)` if the field is not the name of a card.
+/// # Returns
+/// A `Result<Card, String>` containing the card.
+pub fn decode_card(field: &str) -> Result<Card, String> {
+    let field = field.trim();
+    field.parse::<Card>()
+        .or_else(|_| field.replace(' ', "").parse::<Card>())
+        .map_err(|_| format!("Invalid card: {field}"))
+}
+
+/// Reads a draw written by `encode_draw`.
+/// # Arguments
+/// * `field` - A `&str` containing `deck` or the name of a card.
+/// # Errors
+/// This function returns an `Err(String)` if the field is neither `deck` nor the name of a card.
+/// # Returns
+/// A `Result<DrawCard, String>` containing the draw.
+pub fn decode_draw(field: &str) -> Result<DrawCard, String> {
+    if field.trim().eq_ignore_ascii_case("deck") {
+        return Ok(DrawCard::Deck);
+    }
+    decode_card(field).map(DrawCard::Discard)
+}
+
+/// Reads a list of cards written by `encode_cards`.
+/// # Arguments
+/// * `field` - A `&str` containing the names of the cards separated by `,`, or `-`.
+/// # Errors
+/// This function returns an `Err(String)` if any name is not the name of a card.
+/// # Returns
+/// A `Result<Vec<Card>, String>` containing the cards in order.
+pub fn decode_cards(field: &str) -> Result<Vec<Card>, String> {
+    if field.trim() == "-" {
+        return Ok(Vec::new());
+    }
+    field.split(',').map(decode_card).collect()
+}
+
+/// Reads a list of cards into a collection.
+/// # Arguments
+/// * `field` - A `&str` containing the names of the cards separated by `,`, or `-`.
+/// # Errors
+/// This function returns an `Err(String)` if any name is not the name of a card.
+/// # Returns
+/// A `Result<CardCollection, String>` containing the cards.
+pub fn decode_collection(field: &str) -> Result<CardCollection, String> {
+    Ok(decode_cards(field)?
+        .into_iter()
+        .fold(CardCollection::new(), |collection, card| collection + card))
+}
+
+/// Reads a hand of seven cards.
+/// # Arguments
+/// * `field` - A `&str` containing the names of the cards separated by `,`.
+/// # Errors
+/// This function returns an `Err(String)` if any name is not the name of a card, or there are not seven.
+/// # Returns
+/// A `Result<Hand, String>` containing the hand.
+pub fn decode_hand(field: &str) -> Result<Hand, String> {
+    let cards: [Card; 7] = decode_cards(field)?
+        .try_into()
+        .map_err(|_| format!("A hand must have 7 cards: {field}"))?;
+    Ok(Hand::new(cards))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_card_is_read_as_it_is_written() {
+        for index in 0..53 {
+            let card = Card::from(index);
+            assert_eq!(decode_card(&card.to_string()), Ok(card));
+        }
+    }
+}
@@ -0,0 +1,112 @@
+//! # Denial
+//! Estimates how much a discarded card would help the opponents.
+//! Any opponent can pick up a card from the discard pile on their turn,
+//! so discarding a card that completes an opponents hand can lose the game.
+//! The hidden cards in each opponents hand are sampled from a `Belief`,
+//! and the gain of each candidate discard is the average improvement to
+//! the opponents best hand after swapping it in.
+//! Bots subtract a weighted penalty for this gain from their own evaluation.
+
+use rand::Rng;
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand};
+use super::belief::Belief;
+use super::simulation::Simulation;
+use super::score_cache::cached_score;
+
+/// The default weight of an opponents gain compared to the bots own score.
+/// Denial is off by default, and is turned on with the `denial` parameter or the `Denial` aliases, which use 0.5.
+pub const DEFAULT_DENIAL_WEIGHT: f32 = 0.0;
+/// The number of samples of the opponents hidden cards.
+const DENIAL_SAMPLES: usize = 3;
+
+/// The penalty for discarding each candidate card.
+/// # Fields
+/// * `penalties` - A `Vec<(Card, f32)>` pairing each candidate discard with its penalty.
+pub struct Denial {
+    penalties: Vec<(Card, f32)>,
+}
+
+impl Denial {
+    /// Estimates the penalty for discarding each card in a hand, and the card drawn.
+    /// No samples are taken when the weight is zero.
+    /// # Arguments
+    /// * `weight` - A `f32` representing how much an opponents gain is worth compared to the bots own score.
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be.
+    /// * `drawn` - An `Option<Card>` representing a card the bot has drawn from the deck.
+    /// * `rng` - A `&mut R` used to sample the opponents hidden cards.
+    /// # Returns
+    /// A `Denial` holding the penalty of each candidate discard.
+    pub fn new<R: Rng>(
+        weight: f32,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        drawn: Option<Card>,
+        rng: &mut R,
+    ) -> Self {
+        if weight <= 0.0 || known_opponent_cards.is_empty() {
+            return Denial {penalties: Vec::new()};
+        }
+        let samples: Vec<Simulation> = (0..DENIAL_SAMPLES)
+            .map(|_| Simulation::sample(hand, discard_pile, known_opponent_cards, belief, drawn, rng))
+            .collect();
+        let mut candidates: Vec<Card> = hand.to_vec();
+        if let Some(card) = drawn {
+            candidates.push(card);
+        }
+        let penalties = candidates
+            .into_iter()
+            .map(|card| (card, weight * opponent_gain(card, discard_pile, &samples)))
+            .collect();
+        Denial {penalties}
+    }
+
+    /// Gets the penalty for discarding a card.
+    /// # Arguments
+    /// * `card` - A `Card` representing the card discarded.
+    /// # Returns
+    /// A `f32` that is 0 for any card that was not a candidate.
+    pub fn penalty(&self, card: Card) -> f32 {
+        self.penalties
+            .iter()
+            .find(|(candidate, _)| *candidate == card)
+            .map(|(_, penalty)| *penalty)
+            .unwrap_or(0.0)
+    }
+}
+
+/// Helper function for `Denial::new`.
+/// Finds the largest expected gain any opponent gets from picking up a card.
+/// # Arguments
+/// * `card` - A `Card` representing the card discarded.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile before the card is discarded.
+/// * `samples` - A `&[Simulation]` containing sampled opponent hands.
+/// # Returns
+/// A `f32` representing the expected improvement to the opponents score.
+fn opponent_gain(card: Card, discard_pile: &CardCollection, samples: &[Simulation]) -> f32 {
+    let new_discard_pile = *discard_pile + card;
+    let opponents = samples[0].hands.len();
+    (1..opponents)
+        .map(|opponent| {
+            let total: f32 = samples
+                .iter()
+                .map(|sample| {
+                    let hand = &sample.hands[opponent];
+                    let score = cached_score(hand, &new_discard_pile);
+                    let mut max_score = score;
+                    for i in 0..7 {
+                        let mut test_hand = *hand;
+                        test_hand[i] = card;
+                        max_score = max_score.max(cached_score(&test_hand, &(new_discard_pile - card + hand[i])));
+                    }
+                    (max_score - score) as f32
+                })
+                .sum();
+            total / samples.len() as f32
+        })
+        .fold(0.0, f32::max)
+}
@@ -0,0 +1,384 @@
+//! # Endgame
+//! Searches the last turns of a game move by move.
+//! Once the discard pile is close to ending the game, draws from the deck end it,
+//! so the lines that end the game are short.
+//! Every draw from the discard pile, a sample of the cards that could come off the deck,
+//! and every discard is searched for each player in turn.
+//! Each opponent plays the move that gives them the best expected score,
+//! while the bot plays the move that is best for its `Objective`.
+//! Opponents hidden cards are sampled from a `Belief`, and the results are averaged over the samples.
+//! The search is bounded by a number of turns rather than by the discard pile, because drawing
+//! from the discard pile does not bring the end closer. The deepest search that fits in `max_nodes`
+//! is used, up to `max_plies` turns, once the game can end within the turns searched.
+//! The result is exact for every line that ends the game within the turns searched,
+//! and a line that has not ended by then is scored as the hands stand.
+//! Each draw from the deck searches only a few cards off the top of the sampled deck, which is shuffled,
+//! so the budget goes on looking further ahead rather than on every card the deck could hold.
+//! With the default budget the search covers the bots turn and the next players reply
+//! once the discard pile holds eight or nine cards.
+
+use rand::Rng;
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
+use super::belief::Belief;
+use super::objective::Objective;
+use super::simulation::{SimulatedTurn, Simulation, END_DISCARD_PILE_SIZE};
+
+/// The default largest number of positions the solver will search.
+/// Positions at the end of the search score every hand, which takes around a tenth of a millisecond,
+/// so this keeps a decision to around two seconds at most.
+/// Two turns fit once the discard pile holds eight cards, with the default samples and deck draws.
+pub const DEFAULT_MAX_NODES: usize = 20_000;
+/// The default largest number of turns searched, the bots turn and two replies.
+pub const DEFAULT_MAX_PLIES: usize = 3;
+/// The default number of samples of the opponents hidden cards.
+pub const DEFAULT_SAMPLES: usize = 3;
+/// The default number of cards searched for each draw from the deck, taken from the top of the sampled deck.
+pub const DEFAULT_DECK_DRAWS: usize = 3;
+
+/// A turn chosen by the solver.
+/// # Fields
+/// * `draw` - A `DrawCard` representing where to draw from.
+/// * `discard` - An `Option<Card>` representing the card to discard after drawing from the discard pile.
+///   It is `None` after drawing from the deck, since the discard depends on the card drawn.
+#[derive(Clone, Copy)]
+pub struct EndgameTurn {
+    pub draw: DrawCard,
+    pub discard: Option<Card>,
+}
+
+/// The expected result of a position.
+/// # Fields
+/// * `scores` - A `Vec<f32>` containing each players expected final score.
+/// * `objective` - A `f32` representing the expected value of the bots objective.
+#[derive(Clone)]
+struct Outcome {
+    scores: Vec<f32>,
+    objective: f32,
+}
+
+/// A search of the end of the game.
+/// # Fields
+/// * `objective` - An `Objective` the bot is maximising.
+/// * `max_nodes` - A `usize` representing the largest tree the solver will search.
+/// * `max_plies` - A `usize` representing the most turns searched, including the bots own turn.
+/// * `samples` - A `usize` representing the number of samples of the opponents hidden cards.
+/// * `deck_draws` - A `usize` representing the number of cards searched for each draw from the deck.
+#[derive(Clone, Copy)]
+pub struct EndgameSolver {
+    pub objective: Objective,
+    pub max_nodes: usize,
+    pub max_plies: usize,
+    pub samples: usize,
+    pub deck_draws: usize,
+}
+
+impl EndgameSolver {
+    /// Creates a solver with the default size limit.
+    /// # Arguments
+    /// * `objective` - An `Objective` the bot is maximising.
+    /// # Returns
+    /// An `EndgameSolver`.
+    pub fn new(objective: Objective) -> Self {
+        EndgameSolver {
+            objective,
+            max_nodes: DEFAULT_MAX_NODES,
+            max_plies: DEFAULT_MAX_PLIES,
+            samples: DEFAULT_SAMPLES,
+            deck_draws: DEFAULT_DECK_DRAWS,
+        }
+    }
+
+    /// Estimates the size of the game tree searched to a number of turns.
+    /// Draws from the deck that end the game are counted as leaves.
+    /// # Arguments
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `deck_size` - A `usize` representing the number of cards left in the deck.
+    /// * `plies` - A `usize` representing the number of turns searched.
+    /// # Returns
+    /// A `usize` representing an upper bound on the positions searched for all samples.
+    pub fn estimate_nodes(&self, discard_pile: &CardCollection, deck_size: usize, plies: usize) -> usize {
+        let deck_draws = deck_size.min(self.deck_draws.max(1));
+        self.samples.max(1).saturating_mul(tree_size(discard_pile.len() as usize, deck_draws, plies))
+    }
+
+    /// Finds how many turns to search.
+    /// # Arguments
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `deck_size` - A `usize` representing the number of cards left in the deck.
+    /// # Returns
+    /// An `Option<usize>` containing the most turns, up to `max_plies`, that fit in `max_nodes`,
+    /// or `None` if the game cannot end within that many turns.
+    pub fn plies(&self, discard_pile: &CardCollection, deck_size: usize) -> Option<usize> {
+        let turns_to_end = END_DISCARD_PILE_SIZE.saturating_sub(discard_pile.len() as usize);
+        (1..=self.max_plies.max(1))
+            .take_while(|&plies| self.estimate_nodes(discard_pile, deck_size, plies) <= self.max_nodes)
+            .last()
+            .filter(|&plies| plies >= turns_to_end)
+    }
+
+    /// Solves the draw if the remaining tree is small enough.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be.
+    /// * `rng` - A `&mut R` used to sample the opponents hidden cards.
+    /// # Returns
+    /// An `Option<EndgameTurn>` which is:
+    /// * `Some(EndgameTurn)` containing the best draw.
+    /// * `None` if the tree is larger than `max_nodes`.
+    pub fn solve_draw<R: Rng>(
+        &self,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        rng: &mut R,
+    ) -> Option<EndgameTurn> {
+        let (samples, plies) = self.sample(hand, discard_pile, known_opponent_cards, belief, None, rng)?;
+        let mut candidates: Vec<(EndgameTurn, f32)> = Vec::new();
+        for card in *discard_pile {
+            for i in 0..7 {
+                let turn = SimulatedTurn {draw: DrawCard::Discard (card), card, discard: hand[i]};
+                let value = self.average(&samples, |simulation| self.after_turn(simulation, turn, plies));
+                candidates.push((EndgameTurn {draw: turn.draw, discard: Some(turn.discard)}, value));
+            }
+        }
+        if samples.iter().all(|simulation| !simulation.deck.is_empty()) {
+            let value = self.average(&samples, |simulation| self.deck_draw(simulation, plies));
+            candidates.push((EndgameTurn {draw: DrawCard::Deck, discard: None}, value));
+        }
+        candidates
+            .into_iter()
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(turn, _)| turn)
+    }
+
+    /// Solves the discard after drawing from the deck if the remaining tree is small enough.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be,
+    ///   with the card drawn already revealed.
+    /// * `draw` - A `Card` representing the card drawn.
+    /// * `rng` - A `&mut R` used to sample the opponents hidden cards.
+    /// # Returns
+    /// An `Option<Card>` which is:
+    /// * `Some(Card)` containing the best discard.
+    /// * `None` if the tree is larger than `max_nodes`.
+    pub fn solve_discard<R: Rng>(
+        &self,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        draw: Card,
+        rng: &mut R,
+    ) -> Option<Card> {
+        let (samples, plies) = self.sample(hand, discard_pile, known_opponent_cards, belief, Some(draw), rng)?;
+        let mut candidates: Vec<Card> = hand.to_vec();
+        candidates.push(draw);
+        candidates
+            .into_iter()
+            .map(|discard| {
+                let turn = SimulatedTurn {draw: DrawCard::Deck, card: draw, discard};
+                (discard, self.average(&samples, |simulation| self.after_turn(simulation, turn, plies)))
+            })
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+            .map(|(discard, _)| discard)
+    }
+
+    /// Helper function for the solve functions.
+    /// Samples the opponents hidden cards and finds how many turns to search, if the tree is small enough.
+    fn sample<R: Rng>(
+        &self,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        drawn: Option<Card>,
+        rng: &mut R,
+    ) -> Option<(Vec<Simulation>, usize)> {
+        let samples: Vec<Simulation> = (0..self.samples.max(1))
+            .map(|_| Simulation::sample(hand, discard_pile, known_opponent_cards, belief, drawn, rng))
+            .collect();
+        let plies = self.plies(discard_pile, samples[0].deck.len())?;
+        Some((samples, plies))
+    }
+
+    /// Helper function for the solve functions.
+    /// Averages the bots objective over every sample.
+    fn average<F: Fn(&Simulation) -> Outcome>(&self, samples: &[Simulation], solve: F) -> f32 {
+        let total: f32 = samples.iter().map(|simulation| solve(simulation).objective).sum();
+        total / samples.len() as f32
+    }
+
+    /// Helper function for the solve functions.
+    /// Plays the bots turn and solves the position after it.
+    fn after_turn(&self, simulation: &Simulation, turn: SimulatedTurn, plies: usize) -> Outcome {
+        let mut next = simulation.clone();
+        next.play_turn(turn);
+        self.solve(&next, plies.saturating_sub(1))
+    }
+
+    /// Solves a position by searching every turn the current player can play.
+    /// # Arguments
+    /// * `simulation` - A `&Simulation` representing the position.
+    /// * `plies` - A `usize` representing the number of turns left to search.
+    /// # Returns
+    /// An `Outcome` representing the expected result when every player plays their best move,
+    /// with the hands scored as they stand if the game has not ended within `plies` turns.
+    fn solve(&self, simulation: &Simulation, plies: usize) -> Outcome {
+        if plies == 0 || simulation.over() {
+            return self.final_outcome(simulation);
+        }
+        let player = simulation.current_turn;
+        let hand = simulation.hands[player];
+        let mut best: Option<Outcome> = None;
+        for card in simulation.discard_pile {
+            for i in 0..7 {
+                let mut next = simulation.clone();
+                next.play_turn(SimulatedTurn {draw: DrawCard::Discard (card), card, discard: hand[i]});
+                let outcome = self.solve(&next, plies - 1);
+                best = Some(self.better(best, outcome, player));
+            }
+        }
+        let outcome = self.deck_draw(simulation, plies);
+        self.better(best, outcome, player)
+    }
+
+    /// Helper function for `solve`.
+    /// Averages the outcome of drawing each of the top `deck_draws` cards of the deck, discarding the best card after each.
+    /// The sampled deck is shuffled, so these are a random sample of the cards that could come off it.
+    fn deck_draw(&self, simulation: &Simulation, plies: usize) -> Outcome {
+        let player = simulation.current_turn;
+        let hand = simulation.hands[player];
+        let mut total = Outcome {scores: vec![0.0; simulation.hands.len()], objective: 0.0};
+        let first = simulation.deck.len().saturating_sub(self.deck_draws.max(1));
+        for k in first..simulation.deck.len() {
+            let mut drawn = simulation.clone();
+            let top = drawn.deck.len() - 1;
+            drawn.deck.swap(k, top);
+            let card = drawn.deck[top];
+            let mut best: Option<Outcome> = None;
+            for discard in hand.iter().copied().chain(std::iter::once(card)) {
+                let mut next = drawn.clone();
+                next.play_turn(SimulatedTurn {draw: DrawCard::Deck, card, discard});
+                let outcome = self.solve(&next, plies - 1);
+                best = Some(self.better(best, outcome, player));
+            }
+            if let Some(best) = best {
+                for (sum, score) in total.scores.iter_mut().zip(best.scores.iter()) {
+                    *sum += score;
+                }
+                total.objective += best.objective;
+            }
+        }
+        let draws = (simulation.deck.len() - first).max(1) as f32;
+        for score in total.scores.iter_mut() {
+            *score /= draws;
+        }
+        total.objective /= draws;
+        total
+    }
+
+    /// Helper function for `solve`.
+    /// Chooses the outcome the current player prefers.
+    /// The bot compares its objective while opponents compare their own score.
+    fn better(&self, best: Option<Outcome>, outcome: Outcome, player: usize) -> Outcome {
+        let value = |outcome: &Outcome| if player == 0 {
+            outcome.objective
+        } else {
+            outcome.scores[player]
+        };
+        match best {
+            Some(best) if value(&best) >= value(&outcome) => best,
+            _ => outcome,
+        }
+    }
+
+    /// Helper function for `solve`.
+    /// Scores every player at the end of the game or of the search.
+    fn final_outcome(&self, simulation: &Simulation) -> Outcome {
+        let scores: Vec<i16> = (0..simulation.hands.len())
+            .map(|player| simulation.score(player))
+            .collect();
+        Outcome {
+            objective: self.objective.evaluate(&scores),
+            scores: scores.into_iter().map(|score| score as f32).collect(),
+        }
+    }
+}
+
+/// Helper function for `EndgameSolver::estimate_nodes`.
+/// Counts the positions in the tree searched to a number of turns.
+/// Drawing from the discard pile leaves the pile the same size, while drawing from the deck grows it
+/// and ends the game once it reaches `END_DISCARD_PILE_SIZE`.
+/// # Arguments
+/// * `discard_pile` - A `usize` representing the number of cards in the discard pile.
+/// * `deck_draws` - A `usize` representing the number of cards searched for each draw from the deck.
+/// * `plies` - A `usize` representing the number of turns left to search.
+/// # Returns
+/// A `usize` representing the number of positions, saturating instead of overflowing.
+fn tree_size(discard_pile: usize, deck_draws: usize, plies: usize) -> usize {
+    if plies == 0 || discard_pile >= END_DISCARD_PILE_SIZE {
+        return 1;
+    }
+    let discard_turns = (7 * discard_pile).saturating_mul(tree_size(discard_pile, deck_draws, plies - 1));
+    let deck_turns = (8 * deck_draws).saturating_mul(tree_size(discard_pile + 1, deck_draws, plies - 1));
+    discard_turns.saturating_add(deck_turns).saturating_add(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_deepest_search_that_fits_is_used() {
+        let solver = EndgameSolver {max_nodes: 1_500_000, ..EndgameSolver::new(Objective::Score)};
+        let nine: CardCollection = (0..9).fold(CardCollection::new(), |pile, i| pile + Card::from(i));
+        let eight: CardCollection = (0..8).fold(CardCollection::new(), |pile, i| pile + Card::from(i));
+        assert!(solver.plies(&nine, 20).unwrap() >= 2);
+        assert!(solver.plies(&eight, 20).unwrap() >= 2);
+        assert_eq!(solver.plies(&nine, 2), Some(DEFAULT_MAX_PLIES));
+        assert_eq!(solver.plies(&CardCollection::new(), 30), None);
+        let seven = (0..7).fold(CardCollection::new(), |pile, i| pile + Card::from(i));
+        let default = EndgameSolver::new(Objective::Score);
+        assert_eq!(solver.plies(&seven, 20), Some(3));
+        assert_eq!(default.plies(&seven, 20), None);
+        assert_eq!(default.plies(&nine, 30), Some(2));
+        assert_eq!(default.plies(&eight, 30), Some(2));
+    }
+
+    #[test]
+    fn the_discard_that_ends_the_game_best_is_chosen() {
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let pile: CardCollection = (20..29).fold(CardCollection::new(), |pile, i| pile + Card::from(i));
+        let known = vec![CardCollection::new()];
+        let draw = Card::from(40);
+        let mut belief = Belief::new(&hand, &pile, &known);
+        belief.reveal(draw);
+        let mut rng = rand::thread_rng();
+        let solver = EndgameSolver::new(Objective::Score);
+        let discard = solver.solve_discard(&hand, &pile, &known, &belief, draw, &mut rng).unwrap();
+
+        let score_after = |discard: Card| {
+            let mut kept = hand;
+            if let Some(i) = (0..7).find(|&i| kept[i] == discard) {
+                kept[i] = draw;
+            }
+            kept.score(&(pile + discard))
+        };
+        let best = hand.iter().copied().chain(std::iter::once(draw)).map(score_after).max().unwrap();
+        assert_eq!(score_after(discard), best);
+    }
+
+    #[test]
+    fn draws_that_end_the_game_are_leaves() {
+        assert_eq!(tree_size(9, 3, 1), 1 + 7 * 9 + 8 * 3);
+        assert_eq!(tree_size(9, 3, 2), 1 + 7 * 9 * tree_size(9, 3, 1) + 8 * 3);
+        assert_eq!(tree_size(8, 3, 2), 1 + 7 * 8 * tree_size(8, 3, 1) + 8 * 3 * tree_size(9, 3, 1));
+        assert_eq!(tree_size(END_DISCARD_PILE_SIZE, 3, 3), 1);
+    }
+}
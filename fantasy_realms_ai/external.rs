@@ -0,0 +1,409 @@
+//! # External
+//! Plays bots that run as separate processes, so bots can be written in any language.
+//! The host and the bot talk over the bots stdin and stdout, one message per line.
+//! Every message is a command followed by fields separated by `|`.
+//! Lists of cards are separated by `,` and an empty list is written as `-`.
+//! A draw is written as `deck` or as the name of the card taken from the discard pile.
+//!
+//! | Host sends | Bot replies |
+//! | --- | --- |
+//! | `fantasyrealms \| <version>` | `ready \| <name> \| <version>` |
+//! | `seed \| <seed>` | nothing |
+//! | `newgame \| <seat> \| <players> \| <hand>` | nothing |
+//! | `turn \| <seat> \| <draw> \| <discard>` | nothing |
+//! | `draw \| <seat> \| <hand> \| <discard pile> \| <known cards of seat 0> \| ...` | `<draw>` |
+//! | `discard \| <card drawn> \| <seat> \| <hand> \| <discard pile> \| <known cards of seat 0> \| ...` | `<card>` |
+//! | `gameover \| <scores> \| <hand of seat 0> \| ...` | nothing |
+//! | `quit` | nothing |
+//!
+//! Scores are separated by spaces. Seat 0 plays first, and cards drawn from the deck by
+//! other players are hidden, so their turns show `deck`.
+//! A bot replies to the greeting with the version of the protocol it speaks, and a bot that leaves it out speaks version 1.
+//! From version 2 a seeded game sends `seed` before `newgame`. A bot that makes random choices
+//! should make all of them from a generator seeded with it, so that the game can be replayed,
+//! and a bot that makes no random choices ignores it.
+//! If the process exits, takes longer than `REPLY_TIMEOUT` to reply or replies with an illegal move,
+//! the bot falls back to drawing from the deck and discarding the card drawn for the rest of the game.
+//! The bot is given another chance when the next game starts.
+
+use std::io::{BufRead, BufReader, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError};
+use std::thread;
+use std::time::Duration;
+use fantasy_realms_unofficial_api::{deck::Card, hand::Hand, DrawCard};
+use super::{FantasyRealmsBot, GameView, ObservedTurn};
+use super::card_text::{decode_card, decode_collection, decode_draw, decode_hand, encode_cards, encode_draw, fields};
+
+/// The version of the protocol spoken by the host.
+pub const PROTOCOL_VERSION: u32 = 2;
+/// The prefix of a bot name that runs an external bot.
+pub const EXTERNAL_PREFIX: &str = "external:";
+/// How long the host waits for a reply before the bot is treated as failed.
+pub const REPLY_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// A bot running in another process.
+/// # Fields
+/// * `name` - A `String` representing the name the bot gave.
+/// * `process` - A `Child` representing the bots process.
+/// * `input` - A `ChildStdin` the host writes messages to.
+/// * `replies` - A `Receiver<Result<String, String>>` receiving every line the bot writes,
+///   read on another thread so that a bot that stops replying cannot block the host.
+/// * `version` - A `u32` representing the version of the protocol the bot speaks.
+/// * `seed` - An `Option<u64>` holding the seed for the next game until it is sent with `newgame`.
+/// * `failed` - A `bool` that is set once the bot stops following the protocol, until the next game.
+pub struct ExternalBot {
+    name: String,
+    process: Child,
+    input: ChildStdin,
+    replies: Receiver<Result<String, String>>,
+    version: u32,
+    seed: Option<u64>,
+    failed: bool,
+}
+
+impl ExternalBot {
+    /// Starts an external bot and checks that it speaks the protocol.
+    /// # Arguments
+    /// * `command` - A `&str` containing the path to the executable, followed by any arguments.
+    ///   A path or argument containing spaces is written in double quotes.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The command is empty, has an unclosed quote or the process cannot be started.
+    /// * The bot does not reply to the greeting with `ready` in time.
+    /// # Returns
+    /// A `Result<ExternalBot, String>` which is:
+    /// * `Ok(ExternalBot)` if the bot is ready to play.
+    /// * `Err(String)` containing an error message if it is not.
+    pub fn spawn(command: &str) -> Result<Self, String> {
+        let words = split_command(command)?;
+        let (path, arguments) = words.split_first().ok_or("No external bot command given.".to_string())?;
+        let mut process = Command::new(path)
+            .args(arguments)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to start external bot {path}: {e}"))?;
+        let input = process.stdin.take().ok_or("Failed to open external bot stdin.".to_string())?;
+        let output = process.stdout.take().ok_or("Failed to open external bot stdout.".to_string())?;
+        let (sender, replies) = mpsc::channel();
+        thread::spawn(move || {
+            for line in BufReader::new(output).lines() {
+                let line = line.map_err(|e| format!("Failed to read from external bot: {e}"));
+                if sender.send(line).is_err() {
+                    break;
+                }
+            }
+        });
+        let mut bot = ExternalBot {name: String::new(), process, input, replies, version: 1, seed: None, failed: false};
+        let reply = bot.request(&format!("fantasyrealms | {PROTOCOL_VERSION}"))?;
+        match fields(&reply).as_slice() {
+            ["ready", name] if !name.is_empty() => bot.name = name.to_string(),
+            ["ready", name, version] if !name.is_empty() => {
+                bot.name = name.to_string();
+                bot.version = version.parse::<u32>().map_err(|_| format!("External bot {path} gave an invalid version: {reply}"))?;
+            }
+            _ => return Err(format!("External bot {path} did not reply ready: {reply}")),
+        }
+        Ok(bot)
+    }
+
+    /// Helper function for the bot functions.
+    /// Sends a message that has no reply.
+    fn send(&mut self, message: &str) -> Result<(), String> {
+        writeln!(self.input, "{message}")
+            .and_then(|_| self.input.flush())
+            .map_err(|e| format!("Failed to write to external bot: {e}"))
+    }
+
+    /// Helper function for the bot functions.
+    /// Sends a message and waits for the reply, for at most `REPLY_TIMEOUT`.
+    fn request(&mut self, message: &str) -> Result<String, String> {
+        self.send(message)?;
+        match self.replies.recv_timeout(REPLY_TIMEOUT) {
+            Ok(reply) => reply.map(|reply| reply.trim().to_string()),
+            Err(RecvTimeoutError::Timeout) => {
+                Err(format!("External bot did not reply within {} seconds.", REPLY_TIMEOUT.as_secs()))
+            }
+            Err(RecvTimeoutError::Disconnected) => Err("External bot closed its output.".to_string()),
+        }
+    }
+
+    /// Helper function for the bot functions.
+    /// Sends a message that has no reply unless the bot has already failed.
+    fn notify(&mut self, message: &str) {
+        if self.failed {
+            return;
+        }
+        if let Err(e) = self.send(message) {
+            self.fail(e);
+        }
+    }
+
+    /// Helper function for the bot functions.
+    /// Reports an error and switches to default moves until the next game.
+    fn fail(&mut self, error: String) {
+        eprintln!("{} failed: {error} Playing default moves for the rest of the game.", self.name.trim());
+        self.failed = true;
+    }
+}
+
+impl FantasyRealmsBot for ExternalBot {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        if self.failed {
+            return DrawCard::Deck;
+        }
+        let draw = self.request(&format!("draw | {}", encode_view(view)))
+            .and_then(|reply| decode_draw(&reply))
+            .and_then(|draw| match draw {
+                DrawCard::Discard (card) if !view.discard_pile.iter().any(|pile_card| pile_card == card) => {
+                    Err(format!("Drew {card} which is not in the discard pile."))
+                }
+                _ => Ok(draw),
+            });
+        draw.unwrap_or_else(|e| {
+            self.fail(e);
+            DrawCard::Deck
+        })
+    }
+
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        if self.failed {
+            return *draw;
+        }
+        let discard = self.request(&format!("discard | {draw} | {}", encode_view(view)))
+            .and_then(|reply| decode_card(&reply))
+            .and_then(|card| {
+                if card == *draw || view.hand.to_vec().contains(&card) {
+                    Ok(card)
+                } else {
+                    Err(format!("Discarded {card} which is not in its hand."))
+                }
+            });
+        discard.unwrap_or_else(|e| {
+            self.fail(e);
+            *draw
+        })
+    }
+
+    fn on_game_start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        // A reply that arrived after the bot timed out belongs to the last game.
+        while self.replies.try_recv().is_ok() {}
+        self.failed = false;
+        if let Some(seed) = self.seed.take().filter(|_| self.version >= 2) {
+            self.notify(&format!("seed | {seed}"));
+        }
+        self.notify(&format!("newgame | {seat} | {players} | {}", encode_cards(hand.to_vec())));
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+
+    fn observe_turn(&mut self, turn: &ObservedTurn) {
+        self.notify(&format!("turn | {} | {} | {}", turn.seat, encode_draw(turn.draw), turn.discard));
+    }
+
+    fn on_game_end(&mut self, hands: &[Hand], scores: &[i16]) {
+        let scores: Vec<String> = scores.iter().map(|score| score.to_string()).collect();
+        let hands: Vec<String> = hands.iter().map(|hand| encode_cards(hand.to_vec())).collect();
+        self.notify(&format!("gameover | {} | {}", scores.join(" "), hands.join(" | ")));
+    }
+}
+
+impl Drop for ExternalBot {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+    }
+}
+
+/// Runs a bot as an external bot, answering the host over a reader and a writer.
+/// This is the other side of `ExternalBot`, used by the reference bot.
+/// # Arguments
+/// * `bot` - A `&mut dyn FantasyRealmsBot` representing the bot to play.
+/// * `input` - A `R` the host writes messages to, usually stdin.
+/// * `output` - A `W` the replies are written to, usually stdout.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * A message cannot be read or a reply cannot be written.
+/// * A message is not part of the protocol.
+/// # Returns
+/// A `Result<(), String>` which is:
+/// * `Ok(())` once the host sends `quit` or closes the input.
+/// * `Err(String)` containing an error message if the protocol fails.
+pub fn serve<R: BufRead, W: Write>(bot: &mut dyn FantasyRealmsBot, input: R, mut output: W) -> Result<(), String> {
+    let mut history: Vec<ObservedTurn> = Vec::new();
+    for line in input.lines() {
+        let line = line.map_err(|e| format!("Failed to read from host: {e}"))?;
+        let fields = fields(&line);
+        let reply = match fields.as_slice() {
+            [] | [""] => None,
+            ["fantasyrealms", _] => Some(format!("ready | {} | {PROTOCOL_VERSION}", bot.name())),
+            ["seed", seed] => {
+                bot.seed(seed.parse::<u64>().map_err(|_| format!("Invalid seed: {seed}"))?);
+                None
+            }
+            ["newgame", seat, players, hand] => {
+                history.clear();
+                bot.on_game_start(decode_number(seat)?, decode_number(players)?, &decode_hand(hand)?);
+                None
+            }
+            ["turn", seat, draw, discard] => {
+                let turn = ObservedTurn {
+                    seat: decode_number(seat)?,
+                    draw: decode_draw(draw)?,
+                    discard: decode_card(discard)?,
+                };
+                bot.observe_turn(&turn);
+                history.push(turn);
+                None
+            }
+            ["draw", view @ ..] => {
+                let view = decode_view(view, &history)?;
+                Some(encode_draw(bot.generate_draw(&view)))
+            }
+            ["discard", draw, view @ ..] => {
+                let view = decode_view(view, &history)?;
+                Some(bot.generate_discard(&view, &decode_card(draw)?).to_string())
+            }
+            ["gameover", scores, hands @ ..] => {
+                let scores: Vec<i16> = scores
+                    .split_whitespace()
+                    .map(|score| score.parse::<i16>().map_err(|_| format!("Invalid score: {score}")))
+                    .collect::<Result<_, _>>()?;
+                let hands: Vec<Hand> = hands.iter().map(|hand| decode_hand(hand)).collect::<Result<_, _>>()?;
+                bot.on_game_end(&hands, &scores);
+                None
+            }
+            ["quit"] => return Ok(()),
+            _ => return Err(format!("Unknown message: {line}")),
+        };
+        if let Some(reply) = reply {
+            writeln!(output, "{reply}")
+                .and_then(|_| output.flush())
+                .map_err(|e| format!("Failed to write to host: {e}"))?;
+        }
+    }
+    Ok(())
+}
+
+/// Checks that an external bot command names a program, without starting it.
+/// # Arguments
+/// * `command` - A `&str` containing the command, as given to `ExternalBot::spawn`.
+/// # Errors
+/// This function returns an `Err(String)` if the command is empty or has an unclosed quote.
+pub fn validate_command(command: &str) -> Result<(), String> {
+    match split_command(command)?.first() {
+        Some(_) => Ok(()),
+        None => Err("No external bot command given.".to_string()),
+    }
+}
+
+/// Helper function for `ExternalBot::spawn` and `validate_command`.
+/// Splits a command into words at whitespace, keeping whitespace inside double quotes.
+/// # Arguments
+/// * `command` - A `&str` containing the command.
+/// # Errors
+/// This function returns an `Err(String)` if a quote is not closed.
+/// # Returns
+/// A `Result<Vec<String>, String>` containing the words without their quotes.
+fn split_command(command: &str) -> Result<Vec<String>, String> {
+    let mut words: Vec<String> = Vec::new();
+    let mut word: Option<String> = None;
+    let mut quoted = false;
+    for character in command.chars() {
+        match character {
+            '"' => {
+                quoted = !quoted;
+                word.get_or_insert_with(String::new);
+            }
+            character if character.is_whitespace() && !quoted => words.extend(word.take()),
+            character => word.get_or_insert_with(String::new).push(character),
+        }
+    }
+    if quoted {
+        return Err(format!("Unclosed quote in external bot command: {command}"));
+    }
+    words.extend(word);
+    Ok(words)
+}
+
+/// Helper function to write messages.
+/// Writes the seat, hand, discard pile and known cards of a view.
+fn encode_view(view: &GameView) -> String {
+    let known_cards: Vec<String> = view.known_cards.iter().map(|&cards| encode_cards(cards)).collect();
+    format!(
+        "{} | {} | {} | {}",
+        view.seat,
+        encode_cards(view.hand.to_vec()),
+        encode_cards(view.discard_pile),
+        known_cards.join(" | "),
+    )
+}
+
+/// Helper function to read messages.
+/// Reads a seat or a number of players.
+fn decode_number(field: &str) -> Result<usize, String> {
+    field.parse::<usize>().map_err(|_| format!("Invalid number: {field}"))
+}
+
+/// Helper function to read messages.
+/// Reads a view written by `encode_view`.
+fn decode_view(fields: &[&str], history: &[ObservedTurn]) -> Result<GameView, String> {
+    match fields {
+        [seat, hand, discard_pile, known_cards @ ..] if !known_cards.is_empty() => Ok(GameView::new(
+            decode_hand(hand)?,
+            decode_number(seat)?,
+            known_cards.iter().map(|cards| decode_collection(cards)).collect::<Result<_, _>>()?,
+            decode_collection(discard_pile)?,
+            history.to_vec(),
+        )),
+        _ => Err(format!("Invalid position: {}", fields.join(" | "))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn commands_keep_quoted_paths_together() {
+        assert_eq!(
+            split_command(r#""C:\Program Files\bot.exe" AutomatosV1  "a b""#),
+            Ok(vec![r"C:\Program Files\bot.exe".to_string(), "AutomatosV1".to_string(), "a b".to_string()]),
+        );
+        assert_eq!(split_command("./bot \"\""), Ok(vec!["./bot".to_string(), String::new()]));
+        assert!(split_command("\"./bot").is_err());
+    }
+
+    #[test]
+    fn the_seed_is_passed_to_the_bot_before_the_game() {
+        struct Seeded (Option<u64>);
+        impl FantasyRealmsBot for Seeded {
+            fn name(&self) -> String {
+                format!("Seeded {:?}", self.0)
+            }
+            fn generate_draw(&mut self, _: &GameView) -> DrawCard {
+                DrawCard::Deck
+            }
+            fn generate_discard(&mut self, _: &GameView, draw: &Card) -> Card {
+                *draw
+            }
+            fn seed(&mut self, seed: u64) {
+                self.0 = Some(seed);
+            }
+        }
+        let mut bot = Seeded (None);
+        let mut output: Vec<u8> = Vec::new();
+        serve(&mut bot, "fantasyrealms | 2\nseed | 42\nquit\n".as_bytes(), &mut output).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), format!("ready | Seeded None | {PROTOCOL_VERSION}\n"));
+        assert_eq!(bot.0, Some(42));
+        assert!(serve(&mut bot, "seed | -1\n".as_bytes(), Vec::new()).is_err());
+    }
+}
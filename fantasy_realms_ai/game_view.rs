@@ -0,0 +1,202 @@
+//! # Game View
+//! Everything a bot is allowed to see when it plays a turn.
+//! Both game runners build a `GameView` for the bot whos turn it is,
+//! so bots can tell which known cards belong to which seat,
+//! the order of the turns played so far, and exactly when the game will end.
+//! Bots written against the old list of arguments can be used through `LegacyAdapter`.
+
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
+use super::{FantasyRealmsBot, ObservedTurn};
+use super::tempo;
+
+/// The state of the game as seen by one bot.
+/// # Fields
+/// * `hand` - A `Hand` representing the bots current hand.
+/// * `seat` - A `usize` representing the bots seat, seat 0 plays first.
+/// * `known_cards` - A `Vec<CardCollection>` containing the cards known to be in each players hand by seat.
+/// * `discard_pile` - A `CardCollection` representing the discard pile.
+/// * `history` - A `Vec<ObservedTurn>` containing every turn played so far in order.
+/// * `turn_number` - A `usize` representing the number of turns played so far.
+/// * `turns_remaining` - A `Vec<usize>` containing the number of turns each player has left by seat,
+///   including this one, if every player draws from the deck.
+#[derive(Clone)]
+pub struct GameView {
+    pub hand: Hand,
+    pub seat: usize,
+    pub known_cards: Vec<CardCollection>,
+    pub discard_pile: CardCollection,
+    pub history: Vec<ObservedTurn>,
+    pub turn_number: usize,
+    pub turns_remaining: Vec<usize>,
+}
+
+impl GameView {
+    /// Creates the view of a bot.
+    /// # Arguments
+    /// * `hand` - A `Hand` representing the bots current hand.
+    /// * `seat` - A `usize` representing the bots seat.
+    /// * `known_cards` - A `Vec<CardCollection>` containing the cards known to be in each players hand by seat.
+    /// * `discard_pile` - A `CardCollection` representing the discard pile.
+    /// * `history` - A `Vec<ObservedTurn>` containing every turn played so far in order.
+    /// # Returns
+    /// A `GameView`.
+    pub fn new(
+        hand: Hand,
+        seat: usize,
+        known_cards: Vec<CardCollection>,
+        discard_pile: CardCollection,
+        history: Vec<ObservedTurn>,
+    ) -> Self {
+        let players = known_cards.len();
+        GameView {
+            hand,
+            seat,
+            turn_number: history.len(),
+            turns_remaining: (0..players)
+                .map(|other| {
+                    let offset = (other + players - seat) % players;
+                    tempo::player_turns_remaining(discard_pile.len() as usize, players, offset)
+                })
+                .collect(),
+            known_cards,
+            discard_pile,
+            history,
+        }
+    }
+
+    /// Gets the number of players in the game.
+    /// # Returns
+    /// A `usize` representing the number of players.
+    pub fn players(&self) -> usize {
+        self.known_cards.len()
+    }
+
+    /// Gets the seats of the opponents in turn order, starting with the next player.
+    /// # Returns
+    /// A `Vec<usize>` containing the seat of each opponent.
+    pub fn opponent_seats(&self) -> Vec<usize> {
+        let players = self.players();
+        (1..players)
+            .map(|offset| (self.seat + offset) % players)
+            .collect()
+    }
+
+    /// Gets the known cards in each opponents hand in turn order, starting with the next player.
+    /// # Returns
+    /// A `Vec<CardCollection>` containing the known cards of each opponent.
+    pub fn known_opponent_cards(&self) -> Vec<CardCollection> {
+        self.opponent_seats()
+            .into_iter()
+            .map(|seat| self.known_cards[seat])
+            .collect()
+    }
+
+    /// Gets the cards discarded so far in order, including cards that were picked up again.
+    /// # Returns
+    /// A `Vec<Card>` containing every discard.
+    pub fn discards(&self) -> Vec<Card> {
+        self.history.iter().map(|turn| turn.discard).collect()
+    }
+
+    /// Gets the number of turns left in the game for all players, including this one,
+    /// if every player draws from the deck.
+    /// # Returns
+    /// A `usize` representing the total number of turns remaining.
+    pub fn total_turns_remaining(&self) -> usize {
+        self.turns_remaining.iter().sum()
+    }
+
+    /// Gets the number of turns the bot is sure to have after this one.
+    /// # Returns
+    /// A `u8` representing the minimum number of turns remaining after the current one.
+    pub fn minimum_turns_remaining(&self) -> u8 {
+        tempo::minimum_turns_remaining(self.discard_pile.len() as usize, self.players())
+    }
+}
+
+/// The functions a bot had to implement before `GameView` was added.
+/// Any bot implementing it can be wrapped in a `LegacyAdapter` to be used as a `FantasyRealmsBot`.
+pub trait LegacyFantasyRealmsBot: Send {
+    /// Gets the name of the bot.
+    /// # Returns
+    /// A `String` representing the name of the bot.
+    fn name(&self) -> String;
+    /// Generates the desision of what card to draw.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands,
+    ///   in turn order starting with the next player.
+    /// * `minimum_turns_remaining` - A `u8` representing the minimum turns the bot has after this one.
+    /// # Returns
+    /// A `DrawCard` representing what the bot drew.
+    fn generate_draw(
+        &mut self,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        minimum_turns_remaining: u8,
+    ) -> DrawCard;
+    /// Generates the desision of what card to discard.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands,
+    ///   in turn order starting with the next player.
+    /// * `minimum_turns_remaining` - A `u8` representing the minimum turns the bot has after this one.
+    /// * `draw` - A `&Card` representing the card drawn.
+    /// # Returns
+    /// A `Card` representing what the bot discarded.
+    fn generate_discard(
+        &mut self,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        minimum_turns_remaining: u8,
+        draw: &Card,
+    ) -> Card;
+}
+
+/// Wraps a `LegacyFantasyRealmsBot` so that it can be played as a `FantasyRealmsBot`.
+/// The `GameView` is split back into the arguments the bot expects.
+pub struct LegacyAdapter<B: LegacyFantasyRealmsBot>(pub B);
+
+impl<B: LegacyFantasyRealmsBot> FantasyRealmsBot for LegacyAdapter<B> {
+    fn name(&self) -> String {
+        self.0.name()
+    }
+
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        self.0.generate_draw(
+            &view.hand,
+            &view.discard_pile,
+            &view.known_opponent_cards(),
+            view.minimum_turns_remaining(),
+        )
+    }
+
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        self.0.generate_discard(
+            &view.hand,
+            &view.discard_pile,
+            &view.known_opponent_cards(),
+            view.minimum_turns_remaining(),
+            draw,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn turns_remaining_follow_the_turn_order() {
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let discard_pile = (7..15u8).fold(CardCollection::new(), |pile, card| pile + Card::from(card));
+        // Eight cards in the discard pile leave two turns, for the bot in seat 1 and the player in seat 2.
+        let view = GameView::new(hand, 1, vec![CardCollection::new(); 3], discard_pile, Vec::new());
+        assert_eq!(view.turns_remaining, vec![0, 1, 1]);
+        assert_eq!(view.total_turns_remaining(), 2);
+    }
+}
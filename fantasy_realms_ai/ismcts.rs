@@ -0,0 +1,295 @@
+//! # ISMCTS
+//! ISMCTS plays using information set Monte Carlo tree search.
+//! Each iteration samples one possible version of the hidden cards,
+//! the opponents hands and the order of the deck, consistent with what the bot knows.
+//! It then searches its own draw and discard sequences to the end of that game.
+//! Opponents and unexplored turns are played greedily from the deck.
+//! The search tree is shared between samples, so the more time it is given the stronger it plays.
+//! A search limited by time depends on the speed of the machine, so it is not reproducible from a seed.
+//! Giving it a number of iterations instead makes seeded games replay exactly.
+//! Near the end of the game it hands off to the endgame search.
+
+use std::time::{Duration, Instant};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::seq::SliceRandom;
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
+use super::{FantasyRealmsBot, GameView};
+use super::belief::{Belief, BeliefTracker};
+use super::endgame::EndgameSolver;
+use super::objective::Objective;
+use super::simulation::{SimulatedTurn, Simulation};
+
+/// The default time spent searching for each decision.
+pub const DEFAULT_TIME_BUDGET: Duration = Duration::from_secs(2);
+/// The highest score a hand can have. Used to scale rewards.
+const MAX_HAND_SCORE: f32 = 397.0;
+/// How strongly the search favours turns it has rarely tried.
+const EXPLORATION: f32 = 0.7;
+
+/// A node in the search tree representing one of the bots turns.
+/// # Fields
+/// * `visits` - A `u32` representing how many iterations passed through the node.
+/// * `availability` - A `u32` representing how many iterations the node could have been chosen.
+/// * `total_reward` - A `f32` representing the sum of rewards of all visits.
+/// * `children` - A `Vec<(SimulatedTurn, Node)>` containing the turns tried after this node.
+struct Node {
+    visits: u32,
+    availability: u32,
+    total_reward: f32,
+    children: Vec<(SimulatedTurn, Node)>,
+} impl Node {
+    fn new() -> Self {
+        Node {visits: 0, availability: 0, total_reward: 0.0, children: Vec::new()}
+    }
+
+    /// Gets the upper confidence bound used to select between children.
+    fn upper_confidence_bound(&self) -> f32 {
+        self.total_reward / self.visits as f32 +
+            EXPLORATION * ((self.availability as f32).ln() / self.visits as f32).sqrt()
+    }
+
+    /// Gets the most visited child playing a draw.
+    fn most_visited(&self, draw: DrawCard) -> Option<SimulatedTurn> {
+        self.children
+            .iter()
+            .filter(|(turn, _)| turn.draw == draw)
+            .max_by_key(|(_, child)| child.visits)
+            .map(|(turn, _)| *turn)
+    }
+}
+
+pub struct Ismcts {
+    pub(crate) time_budget: Duration,
+    pub(crate) iterations: Option<u32>,
+    pub(crate) discard: Option<Card>,
+    pub(crate) endgame: Option<EndgameSolver>,
+    pub(crate) rng: StdRng,
+    pub(crate) belief: BeliefTracker,
+}
+
+impl Ismcts {
+    /// Creates a new ISMCTS bot.
+    /// # Arguments
+    /// * `time_budget` - A `Duration` representing the time spent searching for each decision.
+    /// # Returns
+    /// An `Ismcts` bot.
+    pub fn new(time_budget: Duration) -> Self {
+        Ismcts {
+            time_budget,
+            iterations: None,
+            discard: None,
+            endgame: Some(EndgameSolver::new(Objective::Score)),
+            rng: StdRng::from_entropy(),
+            belief: BeliefTracker::new(),
+        }
+    }
+
+    /// Runs iterations from the current position until the iteration budget is used,
+    /// or the time budget runs out if there is no iteration budget.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be.
+    /// * `drawn` - An `Option<Card>` representing a card already drawn from the deck.
+    /// # Returns
+    /// A `Node` representing the root of the search tree.
+    fn search(
+        &mut self,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        drawn: Option<Card>,
+    ) -> Node {
+        let rng = &mut self.rng;
+        let mut root = Node::new();
+        let start = Instant::now();
+        while root.visits == 0 || match self.iterations {
+            Some(iterations) => root.visits < iterations,
+            None => start.elapsed() < self.time_budget,
+        } {
+            let mut simulation = Simulation::sample(hand, discard_pile, known_opponent_cards, belief, drawn, rng);
+            iterate(&mut root, &mut simulation, drawn.is_some(), rng);
+        }
+        root
+    }
+}
+
+impl FantasyRealmsBot for Ismcts {
+    fn name(&self) -> String {
+        "ISMCTS".to_string()
+    }
+
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        let belief = &self.belief.current(view, None);
+        if let Some(turn) = self.endgame.and_then(|endgame| {
+            endgame.solve_draw(hand, discard_pile, known_opponent_cards, belief, &mut self.rng)
+        }) {
+            self.discard = turn.discard;
+            return turn.draw;
+        }
+        let root = self.search(hand, discard_pile, known_opponent_cards, belief, None);
+        let mut draws: Vec<(DrawCard, u32)> = Vec::new();
+        for (turn, child) in &root.children {
+            match draws.iter_mut().find(|(draw, _)| *draw == turn.draw) {
+                Some((_, visits)) => *visits += child.visits,
+                None => draws.push((turn.draw, child.visits)),
+            }
+        }
+        let draw = draws
+            .into_iter()
+            .max_by_key(|(_, visits)| *visits)
+            .map(|(draw, _)| draw)
+            .unwrap_or(DrawCard::Deck);
+        self.discard = match draw {
+            DrawCard::Discard (_) => root.most_visited(draw).map(|turn| turn.discard),
+            DrawCard::Deck => None,
+        };
+        draw
+    }
+
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        let hand = &view.hand;
+        let discard_pile = &view.discard_pile;
+        let known_opponent_cards = &view.known_opponent_cards();
+        if let Some(discard) = self.discard.take() {
+            return discard;
+        }
+        let belief = &self.belief.current(view, Some(*draw));
+        if let Some(discard) = self.endgame.and_then(|endgame| {
+            endgame.solve_discard(hand, discard_pile, known_opponent_cards, belief, *draw, &mut self.rng)
+        }) {
+            return discard;
+        }
+        let root = self.search(hand, discard_pile, known_opponent_cards, belief, Some(*draw));
+        root.most_visited(DrawCard::Deck)
+            .map(|turn| turn.discard)
+            .unwrap_or(*draw)
+    }
+
+    fn on_game_start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        self.discard = None;
+        self.belief.start(seat, players, hand);
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
+
+/// Runs one iteration of the search from a node.
+/// Selects children while every turn available in this sample has been tried,
+/// then expands one untried turn and plays the game out.
+/// # Arguments
+/// * `node` - A `&mut Node` representing the bots position in the search tree.
+/// * `simulation` - A `&mut Simulation` representing the sampled game at the node.
+/// * `deck_only` - A `bool` that restricts the node to turns drawing from the deck.
+/// * `rng` - A `&mut R` random number generator.
+/// # Returns
+/// A `f32` representing the reward of the iteration.
+fn iterate<R: Rng>(node: &mut Node, simulation: &mut Simulation, deck_only: bool, rng: &mut R) -> f32 {
+    let reward = if simulation.over() {
+        final_reward(simulation)
+    } else {
+        let turns: Vec<SimulatedTurn> = simulation
+            .legal_turns()
+            .into_iter()
+            .filter(|turn| !deck_only || turn.draw == DrawCard::Deck)
+            .collect();
+        for (turn, child) in node.children.iter_mut() {
+            if turns.contains(turn) {
+                child.availability += 1;
+            }
+        }
+        let untried: Vec<SimulatedTurn> = turns
+            .iter()
+            .filter(|turn| !node.children.iter().any(|(tried, _)| tried == *turn))
+            .copied()
+            .collect();
+        match untried.choose(rng) {
+            Some(&turn) => {
+                simulation.play_turn(turn);
+                simulation.play_out();
+                let reward = final_reward(simulation);
+                let mut child = Node::new();
+                child.visits = 1;
+                child.availability = 1;
+                child.total_reward = reward;
+                node.children.push((turn, child));
+                reward
+            }
+            None => {
+                let index = node.children
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, (turn, _))| turns.contains(turn))
+                    .max_by(|(_, (_, a)), (_, (_, b))| {
+                        a.upper_confidence_bound().total_cmp(&b.upper_confidence_bound())
+                    })
+                    .map(|(i, _)| i)
+                    .expect("No turns available in a game that has not ended.");
+                simulation.play_turn(node.children[index].0);
+                simulation.play_opponents();
+                iterate(&mut node.children[index].1, simulation, false, rng)
+            }
+        }
+    };
+    node.visits += 1;
+    node.total_reward += reward;
+    reward
+}
+
+/// Gets the reward for the bot at the end of a simulated game.
+fn final_reward(simulation: &Simulation) -> f32 {
+    simulation.score(0) as f32 / MAX_HAND_SCORE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// An ISMCTS bot in seat 0 of two with a fixed seed and iteration budget, and no endgame search.
+    fn seeded_bot(hand: &Hand) -> Ismcts {
+        let mut bot = Ismcts::new(DEFAULT_TIME_BUDGET);
+        bot.iterations = Some(200);
+        bot.endgame = None;
+        bot.seed(7);
+        bot.on_game_start(0, 2, hand);
+        bot
+    }
+
+    #[test]
+    fn the_same_seed_and_iterations_make_the_same_move() {
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let discard_pile = (20..24).fold(CardCollection::new(), |pile, i| pile + Card::from(i));
+        let view = GameView::new(hand, 0, vec![CardCollection::new(); 2], discard_pile, Vec::new());
+        let mut first = seeded_bot(&hand);
+        let mut second = seeded_bot(&hand);
+        assert_eq!(first.generate_draw(&view), second.generate_draw(&view));
+        let draw = Card::from(40);
+        assert_eq!(first.generate_discard(&view, &draw), second.generate_discard(&view, &draw));
+    }
+
+    #[test]
+    fn the_discard_that_ends_the_game_best_is_taken() {
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let discard_pile = (20..29).fold(CardCollection::new(), |pile, i| pile + Card::from(i));
+        let view = GameView::new(hand, 0, vec![CardCollection::new(); 2], discard_pile, Vec::new());
+        let draw = Card::from(40);
+        let discard = seeded_bot(&hand).generate_discard(&view, &draw);
+
+        let score_after = |discard: Card| {
+            let mut kept = hand;
+            if let Some(i) = (0..7).find(|&i| kept[i] == discard) {
+                kept[i] = draw;
+            }
+            kept.score(&(discard_pile + discard))
+        };
+        let best = hand.iter().copied().chain(std::iter::once(draw)).map(score_after).max().unwrap();
+        assert_eq!(score_after(discard), best);
+    }
+}
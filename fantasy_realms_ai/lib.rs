@@ -1,68 +1,137 @@
-use fantasy_realms_unofficial_api::{DrawCard, deck::Card, hand::Hand, card_collection::CardCollection};
+use fantasy_realms_unofficial_api::{DrawCard, deck::Card, hand::Hand};
 pub mod randy; pub use randy::Randy;
 pub mod automatos_v1; pub use automatos_v1::AutomatosV1;
 pub mod automatos_v2; pub use automatos_v2::AutomatosV2;
+pub mod automatos_v3; pub use automatos_v3::AutomatosV3;
+pub mod ismcts; pub use ismcts::Ismcts;
+pub mod belief; pub use belief::{Belief, BeliefTracker};
+pub mod denial; pub use denial::Denial;
+pub mod score_cache; pub use score_cache::ScoreCache;
+pub mod objective; pub use objective::{Evaluator, Objective};
+pub mod endgame; pub use endgame::EndgameSolver;
+pub mod tempo; pub use tempo::Tempo;
+pub mod game_view; pub use game_view::{GameView, LegacyAdapter, LegacyFantasyRealmsBot};
+pub mod external; pub use external::ExternalBot;
+pub mod card_numbers;
+pub mod card_text;
+pub mod plugin; pub use plugin::{Plugin, PluginBot};
+pub mod wasm; pub use wasm::WasmBot;
+pub mod registry; pub use registry::{BotEntry, BotSpec};
+mod simulation;
+pub mod parallel; pub use parallel::run_sequentially;
+
+/// A turn as seen by every player at the table.
+/// # Fields
+/// * `seat` - A `usize` representing the seat of the player who played the turn.
+/// * `draw` - A `DrawCard` representing where the card was drawn from.
+///   Cards drawn from the deck are hidden, so only `DrawCard::Deck` is shown.
+/// * `discard` - A `Card` representing the card discarded.
+#[derive(Clone, Copy)]
+pub struct ObservedTurn {
+    pub seat: usize,
+    pub draw: DrawCard,
+    pub discard: Card,
+}
 
 /// Defines the functions needed for a bot.
-pub trait FantasyRealmsBot {
+/// Bots must be `Send` so that games and evaluations can be run on other threads.
+/// The lifecycle hooks do nothing by default, so bots only implement the ones they need.
+pub trait FantasyRealmsBot: Send {
     /// Gets the name of the bot.
     /// # Returns
     /// A `Srting` representing the name of the bot.
     fn name(&self) -> String;
     /// Generates the desision of what card to draw.
     /// # Arguments
-    /// * `hand` - A `&Hand` representing the bots current hand.
-    /// * `discard_pile` - A `&CardCollection` representing the discard pile. 
-    /// * `known_opponent_cards` - A `&Vec<CardCollection>` representing the known cards in opponents hands.
-    /// * `minimum_turns_remaining` - A `u8` representing the minimum turns that remain in the game. 
+    /// * `view` - A `&GameView` representing the game as seen by the bot.
     /// # Returns
     /// A `DrawCard` representing what the bot drew. 
-    fn generate_draw(
-        &mut self,
-        hand: &Hand,
-        discard_pile: &CardCollection,
-        known_opponent_cards: &Vec<CardCollection>, 
-        minimum_turns_remaining: u8,
-    ) -> DrawCard;
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard;
     /// Generates the desision of what card to discard.
     /// # Arguments
-    /// * `hand` - A `&Hand` representing the bots current hand.
-    /// * `discard_pile` - A `&CardCollection` representing the discard pile. 
-    /// * `known_opponent_cards` - A `&Vec<CardCollection>` representing the known cards in opponents hands.
-    /// * `minimum_turns_remaining` - A `u8` representing the minimum turns that remain in the game. 
+    /// * `view` - A `&GameView` representing the game as seen by the bot.
     /// * `draw` - A `&Card` representing the card drawn.
     /// # Returns
     /// A `Card` representing what the bot discarded. 
-    fn generate_discard(
-        &mut self,
-        hand: &Hand,
-        discard_pile: &CardCollection,
-        known_opponent_cards: &Vec<CardCollection>, 
-        minimum_turns_remaining: u8,
-        draw: &Card,
-    ) -> Card;
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card;
+    /// Called before the first turn of a game.
+    /// Bots should reset anything left over from a previous game.
+    /// # Arguments
+    /// * `seat` - A `usize` representing the bots seat, seat 0 plays first.
+    /// * `players` - A `usize` representing the number of players in the game.
+    /// * `hand` - A `&Hand` representing the bots starting hand.
+    fn on_game_start(&mut self, _seat: usize, _players: usize, _hand: &Hand) {}
+    /// Called before `on_game_start` when the game is seeded.
+    /// Bots should make every random choice with a generator seeded from it,
+    /// so that a seeded game can be replayed exactly.
+    /// # Arguments
+    /// * `seed` - A `u64` representing the bots seed for the game.
+    fn seed(&mut self, _seed: u64) {}
+    /// Called after every turn of the game, including the bots own turns.
+    /// # Arguments
+    /// * `turn` - A `&ObservedTurn` representing the turn played.
+    fn observe_turn(&mut self, _turn: &ObservedTurn) {}
+    /// Called once the game is over.
+    /// # Arguments
+    /// * `hands` - A `&[Hand]` containing every players final hand by seat.
+    /// * `scores` - A `&[i16]` containing every players final score by seat.
+    fn on_game_end(&mut self, _hands: &[Hand], _scores: &[i16]) {}
 }
 
 /// Creates a new bot instance
 /// # Arguments
-/// * `bot_type` - A `String` representing the name of the bot.
+/// * `bot_type` - A `String` representing the spec of a built in or plugin bot,
+///   such as `AutomatosV2(depth=2, objective=win)`, `external:` followed by the command that runs an external bot,
+///   or `wasm:` followed by the path to a WebAssembly bot.
 /// # Errors
 /// This function returns an `Err(String)` if:
-/// * There is no bot coresponding to the name given
+/// * There is no bot coresponding to the name given, suggesting the closest names
+/// * The parameters are not accepted by the bot
+/// * The external bot cannot be started
+/// * The WebAssembly bot cannot be loaded
+/// * The plugin fails to create the bot
 /// # Returns
 /// A `Result<Box<dyn FantasyRealmsBot>, String>` which is:
 /// * `Ok(Box<dyn FantasyRealmsBot>)` if the bot is successfully initialized.
 /// * `Err(String)` containing an error message if validation fails.
 pub fn new_bot(bot_type: String) -> Result<Box<dyn FantasyRealmsBot>, String> {
-    let candidates = vec![
-        Box::new(Randy) as Box<dyn FantasyRealmsBot>,
-        Box::new(AutomatosV1 {discard: None}) as Box<dyn FantasyRealmsBot>,
-        Box::new(AutomatosV2 {discard: None}) as Box<dyn FantasyRealmsBot>,
-    ];
-    for bot in candidates {
-        if bot.name().trim().to_lowercase() == bot_type.trim().to_lowercase() {
-            return Ok(bot);
-        }
+    if let Some(command) = bot_type.trim().strip_prefix(external::EXTERNAL_PREFIX) {
+        return ExternalBot::spawn(command).map(|bot| Box::new(bot) as Box<dyn FantasyRealmsBot>);
+    }
+    if let Some(path) = bot_type.trim().strip_prefix(wasm::WASM_PREFIX) {
+        return wasm::load_wasm_bot(path).map(|bot| Box::new(bot) as Box<dyn FantasyRealmsBot>);
     }
-    Err(format!("Unknown AI type: {}", bot_type.trim()))
+    registry::create_bot(&bot_type.parse::<BotSpec>()?)
+}
+
+/// Checks that a bot can be created from a spec without creating it,
+/// so that specs can be checked before a batch, tournament or match without starting any bots.
+/// External bots are not started and WebAssembly bots are not compiled, only their command or path is checked.
+/// # Arguments
+/// * `bot_type` - A `&str` representing the spec, as given to `new_bot`.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * There is no bot coresponding to the name given, suggesting the closest names
+/// * The parameters are not accepted by the bot
+/// * The external bot command is empty or has an unclosed quote
+/// * There is no file at the path of the WebAssembly bot
+pub fn validate_bot(bot_type: &str) -> Result<(), String> {
+    if let Some(command) = bot_type.trim().strip_prefix(external::EXTERNAL_PREFIX) {
+        return external::validate_command(command);
+    }
+    if let Some(path) = bot_type.trim().strip_prefix(wasm::WASM_PREFIX) {
+        return wasm::validate_path(path);
+    }
+    registry::validate(&bot_type.parse::<BotSpec>()?)
+}
+
+/// Gets the names of every bot that can be created by name.
+/// # Returns
+/// A `Vec<String>` containing the built in bots followed by the plugin bots.
+pub fn bot_names() -> Vec<String> {
+    registry::registered_bots()
+        .iter()
+        .map(|entry| entry.name.to_string())
+        .chain(plugin::loaded_plugins().iter().map(|plugin| plugin.name().to_string()))
+        .collect()
 }
\ No newline at end of file
@@ -0,0 +1,175 @@
+//! # Objective
+//! Decides what a bot is trying to achieve at the end of a game.
+//! A bot can maximise its own score, or its chance of finishing first.
+//! The chance of finishing first is estimated by sampling the opponents hidden cards
+//! and comparing a hand against every sampled opponent hand.
+//! Maximising it makes a bot play safe when it is ahead,
+//! and go for high risk hands when it is behind.
+
+use rand::Rng;
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand};
+use super::score_cache::cached_score;
+use super::belief::Belief;
+use super::simulation::Simulation;
+
+/// The number of samples of the opponents hidden cards used to estimate the chance of winning.
+const WIN_SAMPLES: usize = 16;
+/// The number of points treated as the difference between winning and losing,
+/// used to compare points such as denial penalties with the chance of winning.
+const WIN_PROBABILITY_SCALE: f32 = 100.0;
+
+/// What a bot values at the end of a game.
+/// # Variants
+/// * `Score` - The bots own final score.
+/// * `WinProbability` - The chance of the bot beating every opponent.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Objective {
+    Score,
+    WinProbability,
+}
+
+impl Objective {
+    /// Evaluates the final scores of a game for the bot.
+    /// # Arguments
+    /// * `scores` - A `&[i16]` containing each players final score, index 0 is the bot.
+    /// # Returns
+    /// A `f32` representing the bots score, or for `WinProbability`
+    /// 1 for a win, a share of 1 for a tie and 0 for a loss.
+    pub fn evaluate(&self, scores: &[i16]) -> f32 {
+        match self {
+            Objective::Score => scores[0] as f32,
+            Objective::WinProbability => {
+                if scores[1..].iter().any(|&score| score > scores[0]) {
+                    return 0.0;
+                }
+                let tied = scores.iter().filter(|&&score| score == scores[0]).count();
+                1.0 / tied as f32
+            }
+        }
+    }
+
+    /// Gets the suffix added to the name of a bot using the objective.
+    /// # Returns
+    /// A `&str` which is empty for `Score` and "Win" for `WinProbability`.
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            Objective::Score => "",
+            Objective::WinProbability => "Win",
+        }
+    }
+}
+
+/// Evaluates hands for a bot according to its objective.
+/// # Fields
+/// * `objective` - An `Objective` the bot is maximising.
+/// * `opponent_scores` - A `Vec<Vec<i16>>` containing the opponents scores in each sample.
+pub struct Evaluator {
+    objective: Objective,
+    opponent_scores: Vec<Vec<i16>>,
+}
+
+impl Evaluator {
+    /// Creates an evaluator for the current position.
+    /// The opponents hidden cards are only sampled for `WinProbability`.
+    /// # Arguments
+    /// * `objective` - An `Objective` the bot is maximising.
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be.
+    /// * `drawn` - An `Option<Card>` representing a card the bot has drawn from the deck.
+    /// * `rng` - A `&mut R` used to sample the opponents hidden cards.
+    /// # Returns
+    /// An `Evaluator`.
+    pub fn new<R: Rng>(
+        objective: Objective,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        drawn: Option<Card>,
+        rng: &mut R,
+    ) -> Self {
+        let opponent_scores = match objective {
+            Objective::Score => Vec::new(),
+            Objective::WinProbability => {
+                (0..WIN_SAMPLES)
+                    .map(|_| {
+                        let simulation = Simulation::sample(hand, discard_pile, known_opponent_cards, belief, drawn, rng);
+                        (1..simulation.hands.len()).map(|player| simulation.score(player)).collect()
+                    })
+                    .collect()
+            }
+        };
+        Evaluator {objective, opponent_scores}
+    }
+
+    /// Evaluates a hand.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the hand to evaluate.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// # Returns
+    /// A `f32` representing the hands score, or its chance of beating every sampled opponent.
+    pub fn evaluate(&self, hand: &Hand, discard_pile: &CardCollection) -> f32 {
+        let score = cached_score(hand, discard_pile);
+        match self.objective {
+            Objective::Score => score as f32,
+            Objective::WinProbability => {
+                if self.opponent_scores.is_empty() {
+                    return 1.0;
+                }
+                let total: f32 = self.opponent_scores
+                    .iter()
+                    .map(|opponent_scores| {
+                        let mut scores: Vec<i16> = vec![score];
+                        scores.extend(opponent_scores);
+                        self.objective.evaluate(&scores)
+                    })
+                    .sum();
+                total / self.opponent_scores.len() as f32
+            }
+        }
+    }
+
+    /// Converts a number of points into the units of the objective.
+    /// # Arguments
+    /// * `points` - A `f32` representing a number of points.
+    /// # Returns
+    /// A `f32` that can be added to an evaluation.
+    pub fn points(&self, points: f32) -> f32 {
+        match self.objective {
+            Objective::Score => points,
+            Objective::WinProbability => points / WIN_PROBABILITY_SCALE,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wins_losses_and_ties_are_valued_by_their_share_of_first_place() {
+        let win = Objective::WinProbability;
+        assert_eq!(win.evaluate(&[120, 80, 119]), 1.0);
+        assert_eq!(win.evaluate(&[120, 121, 80]), 0.0);
+        assert_eq!(win.evaluate(&[120, 120, 80]), 0.5);
+        assert_eq!(win.evaluate(&[120, 120, 120]), 1.0 / 3.0);
+        assert_eq!(win.evaluate(&[120, 120, 121]), 0.0);
+        assert_eq!(Objective::Score.evaluate(&[-40, 200]), -40.0);
+    }
+
+    #[test]
+    fn the_chance_of_winning_is_averaged_over_the_samples() {
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let discard_pile = CardCollection::new();
+        let score = hand.score(&discard_pile);
+        let evaluator = Evaluator {
+            objective: Objective::WinProbability,
+            opponent_scores: vec![vec![score - 1], vec![score + 1], vec![score], vec![score - 10, score]],
+        };
+        let expected = (1.0 + 0.0 + 0.5 + 0.5) / 4.0;
+        assert_eq!(evaluator.evaluate(&hand, &discard_pile), expected);
+        assert_eq!(evaluator.points(50.0), 0.5);
+    }
+}
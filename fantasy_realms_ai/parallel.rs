@@ -0,0 +1,108 @@
+//! # Parallel
+//! Spreads independent candidate evaluations across the CPU cores.
+//! Results are returned in the same order as the candidates,
+//! so reducing them in order gives exactly the same answer as a sequential loop.
+//! Callers that already run one game on each core, such as batches, turn this off for their
+//! threads with `run_sequentially`, so that the number of threads stays at one for each core.
+
+use std::cell::Cell;
+use std::thread;
+
+thread_local! {
+    /// Whether evaluations on this thread are kept on this thread.
+    static SEQUENTIAL: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Runs a function with every `parallel_map` it calls on this thread evaluated sequentially.
+/// # Arguments
+/// * `run` - A `FnOnce() -> R` to run.
+/// # Returns
+/// The `R` returned by `run`.
+pub fn run_sequentially<R, F: FnOnce() -> R>(run: F) -> R {
+    let previous = SEQUENTIAL.with(|sequential| sequential.replace(true));
+    let result = run();
+    SEQUENTIAL.with(|sequential| sequential.set(previous));
+    result
+}
+
+/// Maps a function over candidates using one thread for each core,
+/// or on the calling thread inside `run_sequentially`.
+/// # Arguments
+/// * `candidates` - A `&[T]` containing the candidates to evaluate.
+/// * `evaluate` - A `Fn(&T) -> U` that evaluates one candidate.
+/// # Panics
+/// * An evaluation thread panics.
+/// # Returns
+/// A `Vec<U>` containing the evaluation of each candidate in order.
+pub(crate) fn parallel_map<T, U, F>(candidates: &[T], evaluate: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    let threads = thread::available_parallelism()
+        .map(|threads| threads.get())
+        .unwrap_or(1);
+    if SEQUENTIAL.with(Cell::get) {
+        return candidates.iter().map(evaluate).collect();
+    }
+    map_on_threads(candidates, threads, evaluate)
+}
+
+/// Maps a function over candidates split into one chunk for each thread.
+/// # Arguments
+/// * `candidates` - A `&[T]` containing the candidates to evaluate.
+/// * `threads` - A `usize` representing the most threads to use.
+/// * `evaluate` - A `Fn(&T) -> U` that evaluates one candidate.
+/// # Panics
+/// * An evaluation thread panics.
+/// # Returns
+/// A `Vec<U>` containing the evaluation of each candidate in order.
+fn map_on_threads<T, U, F>(candidates: &[T], threads: usize, evaluate: F) -> Vec<U>
+where
+    T: Sync,
+    U: Send,
+    F: Fn(&T) -> U + Sync,
+{
+    if threads <= 1 || candidates.len() <= 1 {
+        return candidates.iter().map(evaluate).collect();
+    }
+    let chunk_size = candidates.len().div_ceil(threads);
+    let evaluate = &evaluate;
+    thread::scope(|scope| {
+        let handles: Vec<_> = candidates
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(evaluate).collect::<Vec<U>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Evaluation thread panicked."))
+            .collect()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parallel_and_sequential_evaluations_agree_in_order() {
+        let candidates: Vec<u64> = (0..1_000).collect();
+        let evaluate = |&candidate: &u64| candidate * candidate + 1;
+        let expected: Vec<u64> = candidates.iter().map(evaluate).collect();
+        assert_eq!(parallel_map(&candidates, evaluate), expected);
+        for threads in [2, 3, 4, 7] {
+            assert_eq!(map_on_threads(&candidates, threads, evaluate), expected);
+        }
+        assert_eq!(run_sequentially(|| parallel_map(&candidates, evaluate)), expected);
+    }
+
+    #[test]
+    fn sequential_evaluations_stay_on_the_calling_thread() {
+        let candidates: Vec<u64> = (0..100).collect();
+        let caller = thread::current().id();
+        let threads = run_sequentially(|| parallel_map(&candidates, |_| thread::current().id()));
+        assert!(threads.iter().all(|&thread| thread == caller));
+        assert!(!SEQUENTIAL.with(Cell::get));
+    }
+}
@@ -0,0 +1,517 @@
+//! # Plugin
+//! Loads bots from shared libraries so new bots can be added without rebuilding the UI.
+//! At startup every library in the plugins directory is loaded,
+//! and each one adds a bot that can be chosen by name like the built in bots.
+//! Plugins only talk to the host through the C ABI below, so they can be built
+//! with any compiler version, or in any language that can export C functions.
+//!
+//! A plugin exports a function named `fantasy_realms_plugin` that returns a pointer to a
+//! `PluginBotDescriptor`. The descriptor starts with the ABI version it was built for,
+//! and a plugin with a different version is refused instead of being called.
+//! Cards are passed as numbers, given by the table in `card_numbers`.
+//! A draw from the deck is passed as `PLUGIN_DECK_DRAW`.
+//!
+//! A plugin can also export a function named `fantasy_realms_seed`, of type `PluginSeed`,
+//! which is called with a bot and its seed before `on_game_start` of a seeded game.
+//! A bot that makes random choices should make all of them from a generator seeded with it,
+//! so that the game can be replayed. A plugin that makes no random choices does not export it.
+
+use std::ffi::{c_char, c_void, CStr, OsStr};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, OnceLock};
+use libloading::Library;
+use fantasy_realms_unofficial_api::{deck::Card, hand::Hand, DrawCard};
+use super::{FantasyRealmsBot, GameView, ObservedTurn};
+use super::card_numbers::{card_number, numbered_card};
+
+/// The version of the ABI the host expects plugins to be built for.
+pub const PLUGIN_ABI_VERSION: u32 = 1;
+/// The name of the function every plugin exports.
+pub const PLUGIN_ENTRY_POINT: &str = "fantasy_realms_plugin";
+/// The name of the function a plugin exports to be given the seed of each game.
+pub const PLUGIN_SEED_SYMBOL: &str = "fantasy_realms_seed";
+/// The default directory plugins are loaded from.
+pub const DEFAULT_PLUGIN_DIRECTORY: &str = "plugins";
+/// The number used for a draw from the deck.
+pub const PLUGIN_DECK_DRAW: i32 = -1;
+
+/// A list of cards passed to a plugin.
+/// # Fields
+/// * `cards` - A `*const u8` pointing to the number of each card.
+/// * `len` - A `usize` representing the number of cards.
+#[repr(C)]
+pub struct PluginCards {
+    pub cards: *const u8,
+    pub len: usize,
+}
+
+/// The game as seen by a plugin bot, see `GameView`.
+/// Every pointer is only valid until the plugin function returns.
+/// # Fields
+/// * `seat` - A `usize` representing the bots seat, seat 0 plays first.
+/// * `players` - A `usize` representing the number of players.
+/// * `turn_number` - A `usize` representing the number of turns played so far.
+/// * `turns_remaining` - A `usize` representing the turns left for all players if every player draws from the deck.
+/// * `hand` - A `PluginCards` representing the bots hand.
+/// * `discard_pile` - A `PluginCards` representing the discard pile.
+/// * `known_cards` - A `*const PluginCards` pointing to the known cards of each seat, `players` long.
+#[repr(C)]
+pub struct PluginView {
+    pub seat: usize,
+    pub players: usize,
+    pub turn_number: usize,
+    pub turns_remaining: usize,
+    pub hand: PluginCards,
+    pub discard_pile: PluginCards,
+    pub known_cards: *const PluginCards,
+}
+
+/// A turn shown to a plugin bot, see `ObservedTurn`.
+/// # Fields
+/// * `seat` - A `usize` representing the seat of the player who played the turn.
+/// * `draw` - An `i32` representing the card drawn from the discard pile, or `PLUGIN_DECK_DRAW`.
+/// * `discard` - A `u8` representing the card discarded.
+#[repr(C)]
+pub struct PluginTurn {
+    pub seat: usize,
+    pub draw: i32,
+    pub discard: u8,
+}
+
+/// The functions a plugin provides, returned by its entry point.
+/// The lifecycle hooks can be null.
+/// # Fields
+/// * `abi_version` - A `u32` that must equal `PLUGIN_ABI_VERSION`.
+/// * `name` - A `*const c_char` pointing to the bots name as a null terminated UTF-8 string.
+/// * `create` - Creates a bot and returns a pointer to its state.
+/// * `destroy` - Frees the state of a bot.
+/// * `generate_draw` - Returns the card to draw from the discard pile, or `PLUGIN_DECK_DRAW`.
+/// * `generate_discard` - Returns the card to discard after drawing a card.
+/// * `on_game_start` - Called with the bots seat, the number of players and its starting hand.
+/// * `observe_turn` - Called after every turn.
+/// * `on_game_end` - Called with the final score of every seat.
+#[repr(C)]
+pub struct PluginBotDescriptor {
+    pub abi_version: u32,
+    pub name: *const c_char,
+    pub create: unsafe extern "C" fn() -> *mut c_void,
+    pub destroy: unsafe extern "C" fn(bot: *mut c_void),
+    pub generate_draw: unsafe extern "C" fn(bot: *mut c_void, view: *const PluginView) -> i32,
+    pub generate_discard: unsafe extern "C" fn(bot: *mut c_void, view: *const PluginView, draw: u8) -> u8,
+    pub on_game_start: Option<unsafe extern "C" fn(bot: *mut c_void, seat: usize, players: usize, hand: PluginCards)>,
+    pub observe_turn: Option<unsafe extern "C" fn(bot: *mut c_void, turn: *const PluginTurn)>,
+    pub on_game_end: Option<unsafe extern "C" fn(bot: *mut c_void, scores: *const i16, players: usize)>,
+}
+
+/// The type of the function every plugin exports.
+pub type PluginEntryPoint = unsafe extern "C" fn() -> *const PluginBotDescriptor;
+/// The type of the function a plugin exports to be given the seed of each game.
+pub type PluginSeed = unsafe extern "C" fn(bot: *mut c_void, seed: u64);
+
+/// A loaded plugin library.
+/// # Fields
+/// * `name` - A `String` representing the name of the plugins bot.
+/// * `path` - A `PathBuf` representing the library the plugin was loaded from.
+/// * `library` - An `Arc<Library>` keeping the library loaded while any of its bots exist.
+/// * `descriptor` - A `*const PluginBotDescriptor` pointing into the library.
+/// * `seed` - An `Option<PluginSeed>` holding the plugins seed function, if it exports one.
+pub struct Plugin {
+    name: String,
+    path: PathBuf,
+    library: Arc<Library>,
+    descriptor: *const PluginBotDescriptor,
+    seed: Option<PluginSeed>,
+}
+
+// The descriptor is read only and lives as long as the library.
+unsafe impl Send for Plugin {}
+unsafe impl Sync for Plugin {}
+
+impl Plugin {
+    /// Loads a plugin library and checks its ABI version.
+    /// # Arguments
+    /// * `path` - A `&Path` to the shared library.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The library cannot be loaded or does not export the entry point.
+    /// * The plugin was built for a different ABI version.
+    /// * The plugin has no name.
+    /// # Returns
+    /// A `Result<Plugin, String>` which is:
+    /// * `Ok(Plugin)` if the plugin is ready to create bots.
+    /// * `Err(String)` containing an error message if it is not.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        // Loading a library runs its initialisers, plugins are trusted the same as the UI itself.
+        let library = unsafe { Library::new(path) }
+            .map_err(|e| format!("Failed to load plugin {}: {e}", path.display()))?;
+        Plugin::from_library(library, path)
+    }
+
+    /// Helper function for `load`.
+    /// Finds the entry point of a loaded library and checks the descriptor it returns.
+    fn from_library(library: Library, path: &Path) -> Result<Self, String> {
+        let error = |message: String| format!("Failed to load plugin {}: {message}", path.display());
+        let descriptor = unsafe {
+            let entry_point = library
+                .get::<PluginEntryPoint>(PLUGIN_ENTRY_POINT.as_bytes())
+                .map_err(|e| error(e.to_string()))?;
+            entry_point()
+        };
+        let seed = unsafe { library.get::<PluginSeed>(PLUGIN_SEED_SYMBOL.as_bytes()) }.ok().map(|seed| *seed);
+        let name = check_descriptor(unsafe { descriptor.as_ref() }).map_err(error)?;
+        Ok(Plugin {name, path: path.to_path_buf(), library: Arc::new(library), descriptor, seed})
+    }
+
+    /// Gets the name of the plugins bot.
+    /// # Returns
+    /// A `&str` representing the name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Gets the library the plugin was loaded from.
+    /// # Returns
+    /// A `&Path` to the shared library.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Creates a new bot from the plugin.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The plugin fails to create a bot.
+    /// # Returns
+    /// A `Result<PluginBot, String>` which is:
+    /// * `Ok(PluginBot)` containing the new bot.
+    /// * `Err(String)` containing an error message if the plugin returned null.
+    pub fn create_bot(&self) -> Result<PluginBot, String> {
+        let state = unsafe { ((*self.descriptor).create)() };
+        if state.is_null() {
+            return Err(format!("Plugin {} failed to create a bot.", self.name));
+        }
+        Ok(PluginBot {
+            name: self.name.clone(),
+            _library: Arc::clone(&self.library),
+            descriptor: self.descriptor,
+            seed: self.seed,
+            state,
+            failed: false,
+        })
+    }
+}
+
+/// A bot created by a plugin.
+/// # Fields
+/// * `name` - A `String` representing the name of the bot.
+/// * `_library` - An `Arc<Library>` keeping the plugin loaded while the bot exists.
+/// * `descriptor` - A `*const PluginBotDescriptor` containing the plugins functions.
+/// * `seed` - An `Option<PluginSeed>` holding the plugins seed function, if it exports one.
+/// * `state` - A `*mut c_void` pointing to the bots state inside the plugin.
+/// * `failed` - A `bool` that is set once the bot makes an illegal move, until the next game.
+pub struct PluginBot {
+    name: String,
+    _library: Arc<Library>,
+    descriptor: *const PluginBotDescriptor,
+    seed: Option<PluginSeed>,
+    state: *mut c_void,
+    failed: bool,
+}
+
+// Plugins must allow a bot to be moved to another thread between calls.
+unsafe impl Send for PluginBot {}
+
+impl PluginBot {
+    /// Helper function for the bot functions.
+    /// Gets the plugins functions.
+    fn descriptor(&self) -> &PluginBotDescriptor {
+        unsafe { &*self.descriptor }
+    }
+
+    /// Helper function for the bot functions.
+    /// Reports an illegal move and switches to default moves until the next game.
+    fn fail(&mut self, error: String) {
+        eprintln!("{} failed: {error} Playing default moves for the rest of the game.", self.name.trim());
+        self.failed = true;
+    }
+}
+
+impl FantasyRealmsBot for PluginBot {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        if self.failed {
+            return DrawCard::Deck;
+        }
+        let generate_draw = self.descriptor().generate_draw;
+        let draw = with_plugin_view(view, |plugin_view| unsafe { generate_draw(self.state, plugin_view) });
+        if draw == PLUGIN_DECK_DRAW {
+            return DrawCard::Deck;
+        }
+        match usize::try_from(draw).ok().and_then(numbered_card) {
+            Some(card) if view.discard_pile.iter().any(|pile_card| pile_card == card) => DrawCard::Discard (card),
+            _ => {
+                self.fail(format!("Drew card {draw} which is not in the discard pile."));
+                DrawCard::Deck
+            }
+        }
+    }
+
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        if self.failed {
+            return *draw;
+        }
+        let generate_discard = self.descriptor().generate_discard;
+        let draw_number = card_number(*draw);
+        let discard = with_plugin_view(view, |plugin_view| unsafe {
+            generate_discard(self.state, plugin_view, draw_number)
+        });
+        match numbered_card(discard as usize) {
+            Some(card) if card == *draw || view.hand.to_vec().contains(&card) => card,
+            _ => {
+                self.fail(format!("Discarded card {discard} which is not in its hand."));
+                *draw
+            }
+        }
+    }
+
+    fn on_game_start(&mut self, seat: usize, players: usize, hand: &Hand) {
+        self.failed = false;
+        if let Some(on_game_start) = self.descriptor().on_game_start {
+            let hand = card_numbers(hand.to_vec());
+            unsafe { on_game_start(self.state, seat, players, plugin_cards(&hand)) };
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        if let Some(plugin_seed) = self.seed {
+            unsafe { plugin_seed(self.state, seed) };
+        }
+    }
+
+    fn observe_turn(&mut self, turn: &ObservedTurn) {
+        if let Some(observe_turn) = self.descriptor().observe_turn {
+            let plugin_turn = PluginTurn {
+                seat: turn.seat,
+                draw: match turn.draw {
+                    DrawCard::Deck => PLUGIN_DECK_DRAW,
+                    DrawCard::Discard (card) => card_number(card) as i32,
+                },
+                discard: card_number(turn.discard),
+            };
+            unsafe { observe_turn(self.state, &plugin_turn) };
+        }
+    }
+
+    fn on_game_end(&mut self, _: &[Hand], scores: &[i16]) {
+        if let Some(on_game_end) = self.descriptor().on_game_end {
+            unsafe { on_game_end(self.state, scores.as_ptr(), scores.len()) };
+        }
+    }
+}
+
+impl Drop for PluginBot {
+    fn drop(&mut self) {
+        let destroy = self.descriptor().destroy;
+        unsafe { destroy(self.state) };
+    }
+}
+
+/// The plugins loaded at startup.
+static PLUGINS: OnceLock<Vec<Plugin>> = OnceLock::new();
+
+/// Loads every plugin in a directory, only the first call loads anything.
+/// Files are loaded in order of their names, and only files with the
+/// shared library extension of the platform are tried.
+/// # Arguments
+/// * `directory` - A `&Path` to the plugins directory. A missing directory has no plugins.
+/// # Returns
+/// A `Vec<String>` containing an error message for each plugin that failed to load.
+pub fn load_plugins(directory: &Path) -> Vec<String> {
+    let mut errors: Vec<String> = Vec::new();
+    PLUGINS.get_or_init(|| {
+        let mut paths: Vec<PathBuf> = match std::fs::read_dir(directory) {
+            Ok(entries) => entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.extension().is_some_and(|extension| extension == OsStr::new(std::env::consts::DLL_EXTENSION))
+                })
+                .collect(),
+            Err(_) => Vec::new(),
+        };
+        paths.sort();
+        let mut plugins: Vec<Plugin> = Vec::new();
+        for path in paths {
+            match Plugin::load(&path) {
+                Ok(plugin) if plugins.iter().any(|loaded| loaded.name().eq_ignore_ascii_case(plugin.name())) => {
+                    errors.push(format!("Skipped plugin {}: a bot named {} is already loaded.", path.display(), plugin.name()));
+                }
+                Ok(plugin) => plugins.push(plugin),
+                Err(e) => errors.push(e),
+            }
+        }
+        plugins
+    });
+    errors
+}
+
+/// Gets the plugins loaded by `load_plugins`.
+/// # Returns
+/// A `&'static [Plugin]` which is empty if no plugins have been loaded.
+pub fn loaded_plugins() -> &'static [Plugin] {
+    PLUGINS.get().map(|plugins| plugins.as_slice()).unwrap_or(&[])
+}
+
+/// Helper function for `Plugin::load`.
+/// Checks the descriptor returned by a plugins entry point.
+/// # Arguments
+/// * `descriptor` - An `Option<&PluginBotDescriptor>` which is `None` if the entry point returned null.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * The entry point returned null.
+/// * The plugin was built for a different ABI version.
+/// * The plugin has no name.
+/// # Returns
+/// A `Result<String, String>` containing the name of the plugins bot.
+fn check_descriptor(descriptor: Option<&PluginBotDescriptor>) -> Result<String, String> {
+    let descriptor = descriptor.ok_or("The entry point returned null.".to_string())?;
+    if descriptor.abi_version != PLUGIN_ABI_VERSION {
+        return Err(format!(
+            "Built for ABI version {} but version {PLUGIN_ABI_VERSION} is required.",
+            descriptor.abi_version,
+        ));
+    }
+    if descriptor.name.is_null() {
+        return Err("The plugin has no name.".to_string());
+    }
+    let name = unsafe { CStr::from_ptr(descriptor.name) }.to_string_lossy().trim().to_string();
+    if name.is_empty() {
+        return Err("The plugin has no name.".to_string());
+    }
+    Ok(name)
+}
+
+/// Helper function to pass cards to plugins.
+/// Gets the number of each card.
+fn card_numbers<I: IntoIterator<Item = Card>>(cards: I) -> Vec<u8> {
+    cards.into_iter().map(card_number).collect()
+}
+
+/// Helper function to pass cards to plugins.
+/// Points a `PluginCards` at a list of card numbers.
+fn plugin_cards(cards: &[u8]) -> PluginCards {
+    PluginCards {cards: cards.as_ptr(), len: cards.len()}
+}
+
+/// Helper function for `generate_draw` and `generate_discard`.
+/// Builds the plugins view of the game, which is only valid inside `call`.
+fn with_plugin_view<T, F: FnOnce(*const PluginView) -> T>(view: &GameView, call: F) -> T {
+    let hand = card_numbers(view.hand.to_vec());
+    let discard_pile = card_numbers(view.discard_pile);
+    let known_cards: Vec<Vec<u8>> = view.known_cards
+        .iter()
+        .map(|&cards| card_numbers(cards))
+        .collect();
+    let known_plugin_cards: Vec<PluginCards> = known_cards.iter().map(|cards| plugin_cards(cards)).collect();
+    let plugin_view = PluginView {
+        seat: view.seat,
+        players: view.players(),
+        turn_number: view.turn_number,
+        turns_remaining: view.total_turns_remaining(),
+        hand: plugin_cards(&hand),
+        discard_pile: plugin_cards(&discard_pile),
+        known_cards: known_plugin_cards.as_ptr(),
+    };
+    call(&plugin_view)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use fantasy_realms_unofficial_api::card_collection::CardCollection;
+    use super::*;
+
+    /// The number of times `draw_card_30` has been asked for a draw.
+    static DRAWS: AtomicUsize = AtomicUsize::new(0);
+
+    unsafe extern "C" fn create() -> *mut c_void {
+        Box::into_raw(Box::new(0u8)) as *mut c_void
+    }
+
+    unsafe extern "C" fn destroy(bot: *mut c_void) {
+        drop(unsafe { Box::from_raw(bot as *mut u8) });
+    }
+
+    unsafe extern "C" fn draw_card_30(_: *mut c_void, _: *const PluginView) -> i32 {
+        DRAWS.fetch_add(1, Ordering::SeqCst);
+        card_number(Card::from(30)) as i32
+    }
+
+    unsafe extern "C" fn discard_first(_: *mut c_void, view: *const PluginView, _: u8) -> u8 {
+        unsafe { *(*view).hand.cards }
+    }
+
+    /// A descriptor for a bot that always tries to draw card 30 from the discard pile.
+    fn descriptor(abi_version: u32, name: &CStr) -> PluginBotDescriptor {
+        PluginBotDescriptor {
+            abi_version,
+            name: name.as_ptr(),
+            create,
+            destroy,
+            generate_draw: draw_card_30,
+            generate_discard: discard_first,
+            on_game_start: None,
+            observe_turn: None,
+            on_game_end: None,
+        }
+    }
+
+    /// Opens the test program itself as a library, which exports none of the plugin functions.
+    fn this_program() -> Library {
+        #[cfg(unix)]
+        return libloading::os::unix::Library::this().into();
+        #[cfg(windows)]
+        return libloading::os::windows::Library::this().unwrap().into();
+    }
+
+    #[test]
+    fn plugins_for_another_abi_version_or_without_a_name_are_refused() {
+        assert_eq!(check_descriptor(Some(&descriptor(PLUGIN_ABI_VERSION, c"Test"))), Ok("Test".to_string()));
+        let refused = check_descriptor(Some(&descriptor(PLUGIN_ABI_VERSION + 1, c"Test"))).unwrap_err();
+        assert!(refused.contains(&format!("version {PLUGIN_ABI_VERSION} is required")));
+        assert!(check_descriptor(Some(&descriptor(PLUGIN_ABI_VERSION, c" "))).is_err());
+        assert!(check_descriptor(None).is_err());
+        match Plugin::from_library(this_program(), Path::new("this program")) {
+            Err(e) => assert!(e.starts_with("Failed to load plugin this program")),
+            Ok(_) => panic!("A library without the entry point was loaded."),
+        }
+    }
+
+    #[test]
+    fn a_plugin_that_makes_an_illegal_move_is_given_another_chance_each_game() {
+        let descriptor = descriptor(PLUGIN_ABI_VERSION, c"Test");
+        let mut bot = PluginBot {
+            name: "Test".to_string(),
+            _library: Arc::new(this_program()),
+            descriptor: &descriptor,
+            seed: None,
+            state: unsafe { create() },
+            failed: false,
+        };
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let view = GameView::new(hand, 0, vec![CardCollection::new(); 2], CardCollection::new() + Card::from(7), Vec::new());
+
+        assert_eq!(bot.generate_draw(&view), DrawCard::Deck);
+        assert!(bot.failed);
+        assert_eq!(bot.generate_draw(&view), DrawCard::Deck);
+        assert_eq!(bot.generate_discard(&view, &Card::from(20)), Card::from(20));
+        assert_eq!(DRAWS.load(Ordering::SeqCst), 1);
+
+        bot.on_game_start(0, 2, &hand);
+        assert!(!bot.failed);
+        assert_eq!(bot.generate_discard(&view, &Card::from(20)), Card::from(0));
+        assert_eq!(bot.generate_draw(&view), DrawCard::Deck);
+        assert_eq!(DRAWS.load(Ordering::SeqCst), 2);
+    }
+}
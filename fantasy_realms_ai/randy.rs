@@ -3,45 +3,52 @@
 //! It has an equal probability of choosing each card from
 //! each card in the discard pile, or the deck. 
 
+use rand::rngs::StdRng;
 use rand::seq::SliceRandom;
-use rand::thread_rng;
-use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
-use super::FantasyRealmsBot;
+use rand::SeedableRng;
+use fantasy_realms_unofficial_api::{deck::Card, DrawCard};
+use super::{FantasyRealmsBot, GameView};
 
-pub struct Randy;
+pub struct Randy {
+    rng: StdRng,
+}
+
+impl Randy {
+    /// Creates a Randy bot.
+    /// # Returns
+    /// A `Randy` bot with an unseeded generator.
+    pub fn new() -> Self {
+        Randy {rng: StdRng::from_entropy()}
+    }
+}
+
+impl Default for Randy {
+    fn default() -> Self {
+        Randy::new()
+    }
+}
 
 impl FantasyRealmsBot for Randy {
     fn name(&self) -> String {
         "Randy".to_string()
     }
 
-    fn generate_draw(
-        &mut self,
-        _: &Hand, 
-        discard_pile: &CardCollection, 
-        _: &Vec<CardCollection>, 
-        _: u8,
-    ) -> DrawCard {
-        let mut cards: Vec<DrawCard> = discard_pile
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        let mut cards: Vec<DrawCard> = view.discard_pile
             .iter()
-            .map(|card| DrawCard::Discard (card))
+            .map(DrawCard::Discard)
             .collect();
         cards.push(DrawCard::Deck);
-        let mut rng = thread_rng();
-        *cards.choose(&mut rng).unwrap()
+        *cards.choose(&mut self.rng).unwrap()
     }
 
-    fn generate_discard(
-            &mut self,
-            hand: &Hand,
-            _: &CardCollection,
-            _: &Vec<CardCollection>, 
-            _: u8,
-            draw: &Card,
-    ) -> Card {
-        let mut cards: Vec<Card> = hand.to_vec();
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        let mut cards: Vec<Card> = view.hand.to_vec();
         cards.push(*draw);
-        let mut rng = thread_rng();
-        *cards.choose(&mut rng).unwrap()
+        *cards.choose(&mut self.rng).unwrap()
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
     }
 }
\ No newline at end of file
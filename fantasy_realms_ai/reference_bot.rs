@@ -0,0 +1,27 @@
+//! # Reference Bot
+//! An external bot that speaks the protocol described in `fantasy_realms_ai::external`.
+//! It plays as any built in bot, given as its first argument, and as Randy by default.
+//! Run it with `external:/path/to/reference_bot AutomatosV1` to play AutomatosV1 in another process,
+//! or use it as an example when writing a bot in another language.
+
+use std::io;
+use fantasy_realms_ai::{external, new_bot};
+
+fn main() {
+    let bot_type = std::env::args().nth(1).unwrap_or("Randy".to_string());
+    if bot_type.trim().starts_with(external::EXTERNAL_PREFIX) {
+        eprintln!("The reference bot cannot run another external bot.");
+        std::process::exit(1);
+    }
+    let mut bot = match new_bot(bot_type) {
+        Ok(bot) => bot,
+        Err(e) => {
+            eprintln!("{e}");
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = external::serve(&mut *bot, io::stdin().lock(), io::stdout().lock()) {
+        eprintln!("{e}");
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,719 @@
+//! # Registry
+//! Every built in bot is registered here with a description, its aliases,
+//! how strong and how fast it is, and the parameters it accepts.
+//! Bots are created from specs such as `AutomatosV2(depth=2, objective=win)`,
+//! so only the bot asked for is built, and names that do not match any bot
+//! are answered with the closest names instead of a bare error.
+
+use std::fmt;
+use std::str::FromStr;
+use super::FantasyRealmsBot;
+use super::{AutomatosV1, AutomatosV2, AutomatosV3, Ismcts, Objective, Randy};
+use super::automatos_v2::DEFAULT_MAX_DEPTH;
+use super::automatos_v3;
+use super::denial::DEFAULT_DENIAL_WEIGHT;
+use super::ismcts;
+use super::plugin;
+use super::tempo::DEFAULT_TEMPO_WEIGHT;
+
+/// The largest edit distance between a name and a suggestion.
+const MAX_SUGGESTION_DISTANCE: usize = 3;
+/// The most suggestions given for an unknown name.
+const MAX_SUGGESTIONS: usize = 3;
+
+/// How well a bot plays compared to the other bots.
+/// # Variants
+/// * `Weak` - Only useful as a baseline.
+/// * `Medium` - Plays sensibly without searching far ahead.
+/// * `Strong` - Searches ahead and is the hardest to beat.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Strength {
+    Weak,
+    Medium,
+    Strong,
+}
+
+impl fmt::Display for Strength {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Strength::Weak => write!(f, "weak"),
+            Strength::Medium => write!(f, "medium"),
+            Strength::Strong => write!(f, "strong"),
+        }
+    }
+}
+
+/// How long a bot takes to make each decision.
+/// # Variants
+/// * `Instant` - Too fast to notice.
+/// * `Fast` - Well under a second.
+/// * `Slow` - Seconds, usually set by a time budget.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Speed {
+    Instant,
+    Fast,
+    Slow,
+}
+
+impl fmt::Display for Speed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Speed::Instant => write!(f, "instant"),
+            Speed::Fast => write!(f, "fast"),
+            Speed::Slow => write!(f, "slow"),
+        }
+    }
+}
+
+/// The values a parameter accepts.
+/// # Variants
+/// * `Integer` - A whole number between `min` and `max` inclusive.
+/// * `Float` - A number between `min` and `max` inclusive.
+/// * `Choice` - One of a list of words.
+#[derive(Clone, Copy, Debug)]
+pub enum ParameterKind {
+    Integer {min: i64, max: i64},
+    Float {min: f32, max: f32},
+    Choice (&'static [&'static str]),
+}
+
+/// The value of a parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Value {
+    Integer (i64),
+    Float (f32),
+    Choice (&'static str),
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Value::Integer (value) => write!(f, "{value}"),
+            Value::Float (value) => write!(f, "{value}"),
+            Value::Choice (value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// A parameter a bot accepts.
+/// # Fields
+/// * `name` - A `&str` representing the name used in specs.
+/// * `description` - A `&str` describing what the parameter changes.
+/// * `kind` - A `ParameterKind` representing the values accepted.
+/// * `default` - A `Value` used when the parameter is not given.
+#[derive(Clone, Copy, Debug)]
+pub struct Parameter {
+    pub name: &'static str,
+    pub description: &'static str,
+    pub kind: ParameterKind,
+    pub default: Value,
+}
+
+impl Parameter {
+    /// Parses a value given for the parameter.
+    /// # Arguments
+    /// * `value` - A `&str` containing the value.
+    /// # Errors
+    /// This function returns an `Err(String)` if the value is not accepted by the parameter.
+    /// # Returns
+    /// A `Result<Value, String>` containing the parsed value.
+    fn parse(&self, value: &str) -> Result<Value, String> {
+        let value = value.trim();
+        match self.kind {
+            ParameterKind::Integer {min, max} => match value.parse::<i64>() {
+                Ok(number) if (min..=max).contains(&number) => Ok(Value::Integer (number)),
+                _ => Err(format!("{} must be a whole number between {min} and {max}, not {value}.", self.name)),
+            },
+            ParameterKind::Float {min, max} => match value.parse::<f32>() {
+                Ok(number) if (min..=max).contains(&number) => Ok(Value::Float (number)),
+                _ => Err(format!("{} must be a number between {min} and {max}, not {value}.", self.name)),
+            },
+            ParameterKind::Choice (choices) => choices
+                .iter()
+                .find(|choice| choice.eq_ignore_ascii_case(value))
+                .map(|choice| Value::Choice (choice))
+                .ok_or_else(|| format!("{} must be one of {}, not {value}.", self.name, choices.join(", "))),
+        }
+    }
+}
+
+/// The values of every parameter of a bot, passed to its factory.
+/// Parameters that were not given hold their default.
+pub struct Parameters {
+    values: Vec<(&'static str, Value)>,
+}
+
+impl Parameters {
+    /// Helper function for the getters.
+    /// Factories only ask for parameters in their own schema, so a missing parameter is a bug.
+    fn get(&self, name: &str) -> Value {
+        self.values
+            .iter()
+            .find(|(parameter, _)| *parameter == name)
+            .map(|(_, value)| *value)
+            .unwrap_or_else(|| panic!("Parameter {name} is not in the schema."))
+    }
+
+    /// Gets an integer parameter.
+    /// # Arguments
+    /// * `name` - A `&str` representing the name of the parameter.
+    /// # Returns
+    /// An `i64` representing the value.
+    pub fn integer(&self, name: &str) -> i64 {
+        match self.get(name) {
+            Value::Integer (value) => value,
+            value => panic!("Parameter {name} is {value}, not an integer."),
+        }
+    }
+
+    /// Gets a float parameter.
+    /// # Arguments
+    /// * `name` - A `&str` representing the name of the parameter.
+    /// # Returns
+    /// A `f32` representing the value.
+    pub fn float(&self, name: &str) -> f32 {
+        match self.get(name) {
+            Value::Float (value) => value,
+            value => panic!("Parameter {name} is {value}, not a float."),
+        }
+    }
+
+    /// Gets a choice parameter.
+    /// # Arguments
+    /// * `name` - A `&str` representing the name of the parameter.
+    /// # Returns
+    /// A `&str` representing the choice, spelt as in the schema.
+    pub fn choice(&self, name: &str) -> &'static str {
+        match self.get(name) {
+            Value::Choice (value) => value,
+            value => panic!("Parameter {name} is {value}, not a choice."),
+        }
+    }
+}
+
+/// Another name for a bot, which can also set some of its parameters.
+/// # Fields
+/// * `name` - A `&str` representing the alias.
+/// * `arguments` - A `&[(&str, &str)]` containing the parameters set by the alias.
+#[derive(Clone, Copy, Debug)]
+pub struct Alias {
+    pub name: &'static str,
+    pub arguments: &'static [(&'static str, &'static str)],
+}
+
+/// A bot that can be created by name.
+/// # Fields
+/// * `name` - A `&str` representing the name of the bot.
+/// * `aliases` - A `&[Alias]` containing the other names of the bot.
+/// * `description` - A `&str` describing how the bot plays.
+/// * `strength` - A `Strength` representing how well the bot plays.
+/// * `speed` - A `Speed` representing how long the bot takes to play with its default parameters.
+/// * `slow_with` - An optional function that decides if the parameters given make the bot slow,
+///   for bots whose speed depends on them.
+/// * `parameters` - A `&[Parameter]` containing the parameters the bot accepts.
+/// * `factory` - A function that creates the bot from its parameters.
+pub struct BotEntry {
+    pub name: &'static str,
+    pub aliases: &'static [Alias],
+    pub description: &'static str,
+    pub strength: Strength,
+    pub speed: Speed,
+    pub slow_with: Option<fn(&Parameters) -> bool>,
+    pub parameters: &'static [Parameter],
+    pub factory: fn(&Parameters) -> Box<dyn FantasyRealmsBot>,
+}
+
+impl BotEntry {
+    /// Checks if a name refers to the bot.
+    /// # Arguments
+    /// * `name` - A `&str` representing the name, ignoring case.
+    /// # Returns
+    /// An `Option<&[(&str, &str)]>` containing the parameters set by the name,
+    /// or `None` if the name is not the bot or one of its aliases.
+    fn matches(&self, name: &str) -> Option<&'static [(&'static str, &'static str)]> {
+        if self.name.eq_ignore_ascii_case(name) {
+            return Some(&[]);
+        }
+        self.aliases
+            .iter()
+            .find(|alias| alias.name.eq_ignore_ascii_case(name))
+            .map(|alias| alias.arguments)
+    }
+
+    /// Creates the bot, checking every argument against the parameter schema.
+    /// # Arguments
+    /// * `preset` - A `&[(&str, &str)]` containing the parameters set by an alias.
+    /// * `arguments` - A `&[(String, String)]` containing the parameters given in the spec.
+    /// # Errors
+    /// This function returns an `Err(String)` if the arguments are not accepted, see `parse_arguments`.
+    /// # Returns
+    /// A `Result<Box<dyn FantasyRealmsBot>, String>` containing the bot.
+    fn create(
+        &self,
+        preset: &[(&str, &str)],
+        arguments: &[(String, String)],
+    ) -> Result<Box<dyn FantasyRealmsBot>, String> {
+        Ok((self.factory)(&self.parse_arguments(preset, arguments)?))
+    }
+
+    /// Checks every argument against the parameter schema, filling in the defaults.
+    /// # Arguments
+    /// * `preset` - A `&[(&str, &str)]` containing the parameters set by an alias.
+    /// * `arguments` - A `&[(String, String)]` containing the parameters given in the spec.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * An argument is not a parameter of the bot.
+    /// * An argument has a value the parameter does not accept.
+    /// # Returns
+    /// A `Result<Parameters, String>` containing the value of every parameter.
+    fn parse_arguments(&self, preset: &[(&str, &str)], arguments: &[(String, String)]) -> Result<Parameters, String> {
+        let mut values: Vec<(&'static str, Value)> = self.parameters
+            .iter()
+            .map(|parameter| (parameter.name, parameter.default))
+            .collect();
+        let mut given: Vec<(&str, &str)> = preset.to_vec();
+        given.extend(arguments.iter().map(|(name, value)| (name.as_str(), value.as_str())));
+        for (name, value) in given {
+            let index = self.parameters
+                .iter()
+                .position(|parameter| parameter.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| {
+                    if self.parameters.is_empty() {
+                        format!("{} has no parameters.", self.name)
+                    } else {
+                        let names: Vec<&str> = self.parameters.iter().map(|parameter| parameter.name).collect();
+                        format!("{} has no parameter {name}, use one of {}.", self.name, names.join(", "))
+                    }
+                })?;
+            values[index].1 = self.parameters[index].parse(value)?;
+        }
+        Ok(Parameters {values})
+    }
+}
+
+/// A bot name with the parameters to create it with, such as `AutomatosV2(depth=2, objective=win)`.
+/// # Fields
+/// * `name` - A `String` representing the name of the bot.
+/// * `arguments` - A `Vec<(String, String)>` containing each parameter and its value in order.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BotSpec {
+    pub name: String,
+    pub arguments: Vec<(String, String)>,
+}
+
+impl FromStr for BotSpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let spec = spec.trim();
+        let Some((name, rest)) = spec.split_once('(') else {
+            return Ok(BotSpec {name: spec.to_string(), arguments: Vec::new()});
+        };
+        let arguments = rest
+            .trim_end()
+            .strip_suffix(')')
+            .ok_or_else(|| format!("Missing ) at the end of {spec}."))?;
+        let arguments = arguments
+            .split(',')
+            .filter(|argument| !argument.trim().is_empty())
+            .map(|argument| match argument.split_once('=') {
+                Some((parameter, value)) if !parameter.trim().is_empty() => {
+                    Ok((parameter.trim().to_string(), value.trim().to_string()))
+                }
+                _ => Err(format!("Expected parameter=value, not {}.", argument.trim())),
+            })
+            .collect::<Result<Vec<(String, String)>, String>>()?;
+        Ok(BotSpec {name: name.trim().to_string(), arguments})
+    }
+}
+
+impl fmt::Display for BotSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.name)?;
+        if !self.arguments.is_empty() {
+            let arguments: Vec<String> = self.arguments
+                .iter()
+                .map(|(parameter, value)| format!("{parameter}={value}"))
+                .collect();
+            write!(f, "({})", arguments.join(", "))?;
+        }
+        Ok(())
+    }
+}
+
+const OBJECTIVE: Parameter = Parameter {
+    name: "objective",
+    description: "What the bot maximises, its score or its chance of winning",
+    kind: ParameterKind::Choice (&["score", "win"]),
+    default: Value::Choice ("score"),
+};
+const DENIAL: Parameter = Parameter {
+    name: "denial",
+    description: "How much the bot avoids discarding cards that help an opponent",
+    kind: ParameterKind::Float {min: 0.0, max: 2.0},
+    default: Value::Float (DEFAULT_DENIAL_WEIGHT),
+};
+const TEMPO: Parameter = Parameter {
+    name: "tempo",
+    description: "How much the bot hurries the end of the game when ahead and stalls when behind",
+    kind: ParameterKind::Float {min: 0.0, max: 2.0},
+    default: Value::Float (DEFAULT_TEMPO_WEIGHT),
+};
+const DEPTH: Parameter = Parameter {
+    name: "depth",
+    description: "The number of turns searched into the future",
+    kind: ParameterKind::Integer {min: 0, max: 4},
+    default: Value::Integer (DEFAULT_MAX_DEPTH as i64),
+};
+const AUTOMATOS_TIME: Parameter = Parameter {
+    name: "time",
+    description: "The milliseconds spent searching for each decision",
+    kind: ParameterKind::Integer {min: 10, max: 60_000},
+    default: Value::Integer (automatos_v3::DEFAULT_TIME_BUDGET.as_millis() as i64),
+};
+const ISMCTS_TIME: Parameter = Parameter {
+    name: "time",
+    description: "The milliseconds spent searching for each decision",
+    kind: ParameterKind::Integer {min: 10, max: 60_000},
+    default: Value::Integer (ismcts::DEFAULT_TIME_BUDGET.as_millis() as i64),
+};
+const ISMCTS_ITERATIONS: Parameter = Parameter {
+    name: "iterations",
+    description: "The iterations run for each decision instead of a time budget, 0 uses the time budget",
+    kind: ParameterKind::Integer {min: 0, max: 10_000_000},
+    default: Value::Integer (0),
+};
+
+/// Every built in bot.
+static BOTS: [BotEntry; 5] = [
+    BotEntry {
+        name: "Randy",
+        aliases: &[Alias {name: "Random", arguments: &[]}],
+        description: "Plays random moves",
+        strength: Strength::Weak,
+        speed: Speed::Instant,
+        slow_with: None,
+        parameters: &[],
+        factory: |_| Box::new(Randy::new()),
+    },
+    BotEntry {
+        name: "AutomatosV1",
+        aliases: &[
+            Alias {name: "Automatos", arguments: &[]},
+            Alias {name: "AutomatosV1Win", arguments: &[("objective", "win")]},
+            Alias {name: "AutomatosV1Denial", arguments: &[("denial", "0.5")]},
+            Alias {name: "AutomatosV1Tempo", arguments: &[("tempo", "0.5")]},
+        ],
+        description: "Plays the move with the best expected hand at the end of its turn",
+        strength: Strength::Medium,
+        speed: Speed::Fast,
+        slow_with: None,
+        parameters: &[OBJECTIVE, DENIAL, TEMPO],
+        factory: |parameters| {
+            let mut bot = AutomatosV1::new(objective(parameters));
+            bot.denial_weight = parameters.float("denial");
+            bot.tempo_weight = parameters.float("tempo");
+            Box::new(bot)
+        },
+    },
+    BotEntry {
+        name: "AutomatosV2",
+        aliases: &[
+            Alias {name: "AutomatosV2Win", arguments: &[("objective", "win")]},
+            Alias {name: "AutomatosV2Denial", arguments: &[("denial", "0.5")]},
+            Alias {name: "AutomatosV2Tempo", arguments: &[("tempo", "0.5")]},
+        ],
+        description: "AutomatosV1 with a search a fixed number of turns into the future",
+        strength: Strength::Medium,
+        speed: Speed::Fast,
+        // Every extra turn searched multiplies the positions searched, so deep searches take seconds.
+        slow_with: Some(|parameters| parameters.integer("depth") >= 3),
+        parameters: &[OBJECTIVE, DEPTH, DENIAL, TEMPO],
+        factory: |parameters| {
+            let mut bot = AutomatosV2::new(objective(parameters));
+            bot.max_depth = parameters.integer("depth") as u8;
+            bot.denial_weight = parameters.float("denial");
+            bot.tempo_weight = parameters.float("tempo");
+            Box::new(bot)
+        },
+    },
+    BotEntry {
+        name: "AutomatosV3",
+        aliases: &[
+            Alias {name: "AutomatosV3Win", arguments: &[("objective", "win")]},
+            Alias {name: "AutomatosV3Denial", arguments: &[("denial", "0.5")]},
+            Alias {name: "AutomatosV3Tempo", arguments: &[("tempo", "0.5")]},
+        ],
+        description: "The AutomatosV2 search, deepened until its time budget runs out",
+        strength: Strength::Strong,
+        speed: Speed::Slow,
+        slow_with: None,
+        parameters: &[OBJECTIVE, AUTOMATOS_TIME, DENIAL, TEMPO],
+        factory: |parameters| {
+            let mut bot = AutomatosV3::new(objective(parameters));
+            bot.time_budget = std::time::Duration::from_millis(parameters.integer("time") as u64);
+            bot.denial_weight = parameters.float("denial");
+            bot.tempo_weight = parameters.float("tempo");
+            Box::new(bot)
+        },
+    },
+    BotEntry {
+        name: "ISMCTS",
+        aliases: &[Alias {name: "MCTS", arguments: &[]}],
+        description: "Information set Monte Carlo tree search over sampled hidden cards",
+        strength: Strength::Strong,
+        speed: Speed::Slow,
+        slow_with: None,
+        parameters: &[ISMCTS_TIME, ISMCTS_ITERATIONS],
+        factory: |parameters| {
+            let mut bot = Ismcts::new(std::time::Duration::from_millis(parameters.integer("time") as u64));
+            bot.iterations = Some(parameters.integer("iterations") as u32).filter(|&iterations| iterations > 0);
+            Box::new(bot)
+        },
+    },
+];
+
+/// Helper function for the Automatos factories.
+/// Gets the objective parameter.
+fn objective(parameters: &Parameters) -> Objective {
+    match parameters.choice("objective") {
+        "win" => Objective::WinProbability,
+        _ => Objective::Score,
+    }
+}
+
+/// Gets every built in bot.
+/// # Returns
+/// A `&'static [BotEntry]` containing each bot in the order they are listed.
+pub fn registered_bots() -> &'static [BotEntry] {
+    &BOTS
+}
+
+/// Finds a built in bot by its name or one of its aliases.
+/// # Arguments
+/// * `name` - A `&str` representing the name, ignoring case.
+/// # Returns
+/// An `Option<&BotEntry>` which is `None` if no built in bot has the name.
+pub fn find_bot(name: &str) -> Option<&'static BotEntry> {
+    registered_bots().iter().find(|entry| entry.matches(name).is_some())
+}
+
+/// Gets how long a built in bot takes to play with the parameters in a spec.
+/// # Arguments
+/// * `spec` - A `&BotSpec` representing the bot and its parameters.
+/// # Returns
+/// An `Option<Speed>` containing the speed of the bot,
+/// or `None` if it is not a built in bot or the parameters are not accepted.
+pub fn bot_speed(spec: &BotSpec) -> Option<Speed> {
+    let entry = find_bot(&spec.name)?;
+    let preset = entry.matches(&spec.name)?;
+    let parameters = entry.parse_arguments(preset, &spec.arguments).ok()?;
+    match entry.slow_with {
+        Some(slow_with) if slow_with(&parameters) => Some(Speed::Slow),
+        _ => Some(entry.speed),
+    }
+}
+
+/// Creates a built in or plugin bot from a spec.
+/// # Arguments
+/// * `spec` - A `&BotSpec` representing the bot and its parameters.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * There is no bot with the name given, suggesting the closest names.
+/// * The parameters are not accepted by the bot.
+/// * The plugin fails to create the bot.
+/// # Returns
+/// A `Result<Box<dyn FantasyRealmsBot>, String>` containing the bot.
+pub fn create_bot(spec: &BotSpec) -> Result<Box<dyn FantasyRealmsBot>, String> {
+    for entry in registered_bots() {
+        if let Some(preset) = entry.matches(&spec.name) {
+            return entry.create(preset, &spec.arguments);
+        }
+    }
+    if let Some(plugin) = plugin::loaded_plugins()
+        .iter()
+        .find(|plugin| plugin.name().eq_ignore_ascii_case(&spec.name))
+    {
+        if !spec.arguments.is_empty() {
+            return Err(format!("{} has no parameters.", plugin.name()));
+        }
+        return plugin.create_bot().map(|bot| Box::new(bot) as Box<dyn FantasyRealmsBot>);
+    }
+    Err(unknown_bot(&spec.name))
+}
+
+/// Checks that a spec names a built in or plugin bot and that the bot accepts its parameters,
+/// without creating the bot.
+/// # Arguments
+/// * `spec` - A `&BotSpec` representing the bot and its parameters.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * There is no bot with the name given, suggesting the closest names.
+/// * The parameters are not accepted by the bot.
+pub fn validate(spec: &BotSpec) -> Result<(), String> {
+    for entry in registered_bots() {
+        if let Some(preset) = entry.matches(&spec.name) {
+            return entry.parse_arguments(preset, &spec.arguments).map(|_| ());
+        }
+    }
+    match plugin::loaded_plugins().iter().find(|plugin| plugin.name().eq_ignore_ascii_case(&spec.name)) {
+        Some(plugin) if !spec.arguments.is_empty() => Err(format!("{} has no parameters.", plugin.name())),
+        Some(_) => Ok(()),
+        None => Err(unknown_bot(&spec.name)),
+    }
+}
+
+/// Helper function for `create_bot` and `validate`.
+/// Gets the error for a name that is not a bot, suggesting the closest names.
+fn unknown_bot(name: &str) -> String {
+    let suggestions = suggestions(name);
+    if suggestions.is_empty() {
+        format!("Unknown AI type: {name}")
+    } else {
+        format!("Unknown AI type: {name}, did you mean {}?", suggestions.join(" or "))
+    }
+}
+
+/// Finds the names closest to an unknown name.
+/// # Arguments
+/// * `name` - A `&str` representing the unknown name.
+/// # Returns
+/// A `Vec<String>` containing up to `MAX_SUGGESTIONS` names, closest first.
+pub fn suggestions(name: &str) -> Vec<String> {
+    let name = name.to_lowercase();
+    let mut candidates: Vec<(usize, String)> = registered_bots()
+        .iter()
+        .flat_map(|entry| std::iter::once(entry.name).chain(entry.aliases.iter().map(|alias| alias.name)))
+        .map(|candidate| candidate.to_string())
+        .chain(plugin::loaded_plugins().iter().map(|plugin| plugin.name().to_string()))
+        .map(|candidate| (edit_distance(&name, &candidate.to_lowercase()), candidate))
+        .filter(|(distance, _)| *distance <= MAX_SUGGESTION_DISTANCE)
+        .collect();
+    candidates.sort_by_key(|(distance, _)| *distance);
+    candidates
+        .into_iter()
+        .take(MAX_SUGGESTIONS)
+        .map(|(_, candidate)| candidate)
+        .collect()
+}
+
+/// Splits a list of specs separated by commas, ignoring commas inside brackets,
+/// so that `AutomatosV2(depth=2, objective=win), Randy` is two specs.
+/// # Arguments
+/// * `list` - A `&str` containing the specs.
+/// # Returns
+/// A `Vec<String>` containing each spec that is not empty.
+pub fn split_specs(list: &str) -> Vec<String> {
+    let mut specs: Vec<String> = Vec::new();
+    let mut spec = String::new();
+    let mut depth: usize = 0;
+    for character in list.chars() {
+        match character {
+            '(' => depth += 1,
+            ')' => depth = depth.saturating_sub(1),
+            ',' if depth == 0 => {
+                specs.push(std::mem::take(&mut spec));
+                continue;
+            }
+            _ => {}
+        }
+        spec.push(character);
+    }
+    specs.push(spec);
+    specs
+        .into_iter()
+        .map(|spec| spec.trim().to_string())
+        .filter(|spec| !spec.is_empty())
+        .collect()
+}
+
+/// Helper function for `suggestions`.
+/// Gets the number of single character insertions, deletions and substitutions between two words.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn specs_are_read_with_their_arguments() {
+        let spec: BotSpec = " AutomatosV2( depth = 2, objective=win ) ".parse().unwrap();
+        assert_eq!(spec.name, "AutomatosV2");
+        assert_eq!(spec.arguments, vec![
+            ("depth".to_string(), "2".to_string()),
+            ("objective".to_string(), "win".to_string()),
+        ]);
+        assert_eq!(spec.to_string(), "AutomatosV2(depth=2, objective=win)");
+        assert_eq!("Randy".parse::<BotSpec>(), Ok(BotSpec {name: "Randy".to_string(), arguments: Vec::new()}));
+        assert_eq!("ISMCTS()".parse::<BotSpec>().unwrap().arguments, Vec::new());
+        assert!("AutomatosV2(depth=2".parse::<BotSpec>().is_err());
+        assert!("AutomatosV2(2)".parse::<BotSpec>().is_err());
+    }
+
+    #[test]
+    fn lists_are_split_outside_brackets() {
+        assert_eq!(
+            split_specs("AutomatosV2(depth=2, objective=win), Randy,, ISMCTS(time=500) ,"),
+            vec!["AutomatosV2(depth=2, objective=win)", "Randy", "ISMCTS(time=500)"],
+        );
+        assert!(split_specs(" , ").is_empty());
+    }
+
+    #[test]
+    fn misspelt_names_are_answered_with_the_closest_names() {
+        assert_eq!(suggestions("Randi").first().map(String::as_str), Some("Randy"));
+        assert_eq!(suggestions("automatosv4").first().map(String::as_str), Some("AutomatosV1"));
+        assert!(suggestions("Shapeshifter").is_empty());
+        assert!(suggestions("AutomatosV").len() <= MAX_SUGGESTIONS);
+    }
+
+    #[test]
+    fn specs_are_validated_without_creating_bots() {
+        assert_eq!(validate(&"AutomatosV1Win(denial=0.5)".parse().unwrap()), Ok(()));
+        assert!(validate(&"AutomatosV2(depth=9)".parse().unwrap()).is_err());
+        assert!(validate(&"Randy(depth=1)".parse().unwrap()).is_err());
+        let unknown = validate(&"Randi".parse().unwrap()).unwrap_err();
+        assert!(unknown.contains("did you mean Randy"));
+    }
+
+    #[test]
+    fn denial_and_tempo_are_only_on_when_asked_for() {
+        let weight = |name: &str, parameter: &str, arguments: &[(String, String)]| {
+            let entry = find_bot(name).unwrap();
+            let preset = entry.matches(name).unwrap();
+            entry.parse_arguments(preset, arguments).unwrap().float(parameter)
+        };
+        assert_eq!(weight("AutomatosV2", "denial", &[]), 0.0);
+        assert_eq!(weight("AutomatosV2Win", "denial", &[]), 0.0);
+        assert_eq!(weight("AutomatosV2Denial", "denial", &[]), 0.5);
+        assert_eq!(weight("AutomatosV1Denial", "denial", &[("denial".to_string(), "1".to_string())]), 1.0);
+        assert_eq!(weight("AutomatosV3", "denial", &[("denial".to_string(), "0.25".to_string())]), 0.25);
+        assert_eq!(weight("AutomatosV2", "tempo", &[]), 0.0);
+        assert_eq!(weight("AutomatosV2Denial", "tempo", &[]), 0.0);
+        assert_eq!(weight("AutomatosV3Tempo", "tempo", &[]), 0.5);
+        assert_eq!(weight("AutomatosV1", "tempo", &[("tempo".to_string(), "2".to_string())]), 2.0);
+    }
+
+    #[test]
+    fn deep_automatos_v2_searches_are_slow() {
+        let speed = |spec: &str| bot_speed(&spec.parse().unwrap());
+        assert_eq!(speed("AutomatosV2"), Some(Speed::Fast));
+        assert_eq!(speed("AutomatosV2(depth=2)"), Some(Speed::Fast));
+        assert_eq!(speed("AutomatosV2(depth=3)"), Some(Speed::Slow));
+        assert_eq!(speed("AutomatosV2Win(depth=4)"), Some(Speed::Slow));
+        assert_eq!(speed("Randy"), Some(Speed::Instant));
+        assert_eq!(speed("AutomatosV2(depth=9)"), None);
+    }
+}
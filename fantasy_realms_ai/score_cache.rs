@@ -0,0 +1,235 @@
+//! # Score Cache
+//! Remembers the scores of hands so that bots do not score the same hand twice.
+//! Scoring is the slowest part of every bot, and the same hands are scored
+//! many times within one decision and again on later turns.
+//! Entries are keyed on the set of cards in the hand, so the order of the cards does not matter,
+//! and on the discard pile, since it is passed to `Hand::score`.
+//! Memory is bounded by keeping two generations of entries.
+//! When the newer generation is full the older one is dropped,
+//! and entries found in the older generation are moved back into the newer one.
+//! The entries are split between shards by the hash of their key, each with its own lock,
+//! so that bots searching on many threads rarely wait for each other.
+
+use std::collections::HashMap;
+use std::sync::{Mutex, OnceLock};
+use std::sync::atomic::{AtomicU64, Ordering};
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, hand::Hand};
+use super::card_numbers::{card_number, CARD_COUNT};
+
+/// The default number of entries kept in each generation of the cache.
+pub const DEFAULT_CAPACITY: usize = 1 << 19;
+/// The number of shards the entries are split between.
+const SHARDS: usize = 16;
+
+/// A key made from the cards in a hand and the cards in the discard pile.
+type ScoreKey = (u128, u128);
+
+// Every card needs its own bit in a key.
+const _: () = assert!(CARD_COUNT <= u128::BITS as usize);
+
+/// The two generations of cached scores.
+/// # Fields
+/// * `current` - A `HashMap<ScoreKey, i16>` containing recently used scores.
+/// * `previous` - A `HashMap<ScoreKey, i16>` containing scores from the last generation.
+struct Generations {
+    current: HashMap<ScoreKey, i16>,
+    previous: HashMap<ScoreKey, i16>,
+}
+
+/// Counts of how the cache has been used.
+/// # Fields
+/// * `hits` - A `u64` representing the number of scores found in the cache.
+/// * `misses` - A `u64` representing the number of scores that had to be calculated.
+/// * `entries` - A `usize` representing the number of scores currently stored.
+#[derive(Clone, Copy, Debug)]
+pub struct CacheStatistics {
+    pub hits: u64,
+    pub misses: u64,
+    pub entries: usize,
+}
+
+impl CacheStatistics {
+    /// Gets the fraction of lookups that were found in the cache.
+    /// # Returns
+    /// A `f64` between 0 and 1, or 0 if the cache has not been used.
+    pub fn hit_rate(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            return 0.0;
+        }
+        self.hits as f64 / lookups as f64
+    }
+}
+
+/// A bounded, thread safe cache of hand scores.
+/// # Fields
+/// * `shard_capacity` - A `usize` representing the number of entries kept in each generation of a shard.
+/// * `shards` - A `Vec<Mutex<Generations>>` containing the cached scores split by the hash of their key.
+/// * `hits` - An `AtomicU64` counting the scores found in the cache.
+/// * `misses` - An `AtomicU64` counting the scores that had to be calculated.
+pub struct ScoreCache {
+    shard_capacity: usize,
+    shards: Vec<Mutex<Generations>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+}
+
+impl ScoreCache {
+    /// Creates an empty cache.
+    /// # Arguments
+    /// * `capacity` - A `usize` representing the number of entries kept in each generation,
+    ///   shared evenly between the shards.
+    /// # Returns
+    /// A `ScoreCache` holding at most twice its capacity in entries, or two entries per shard if that is more.
+    pub fn new(capacity: usize) -> Self {
+        ScoreCache {
+            shard_capacity: (capacity / SHARDS).max(1),
+            shards: (0..SHARDS)
+                .map(|_| Mutex::new(Generations {
+                    current: HashMap::new(),
+                    previous: HashMap::new(),
+                }))
+                .collect(),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+        }
+    }
+
+    /// Gets the cache shared by every bot.
+    /// # Returns
+    /// A `&'static ScoreCache` with the default capacity.
+    pub fn global() -> &'static ScoreCache {
+        static GLOBAL: OnceLock<ScoreCache> = OnceLock::new();
+        GLOBAL.get_or_init(|| ScoreCache::new(DEFAULT_CAPACITY))
+    }
+
+    /// Scores a hand, using the cached score if there is one.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the hand to score.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// # Returns
+    /// An `i16` equal to `hand.score(discard_pile)`.
+    pub fn score(&self, hand: &Hand, discard_pile: &CardCollection) -> i16 {
+        let key: ScoreKey = (collection_key(CardCollection::from(hand)), collection_key(*discard_pile));
+        if let Some(score) = self.lookup(key) {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+            return score;
+        }
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        let score = hand.score(discard_pile);
+        self.insert(key, score);
+        score
+    }
+
+    /// Gets how the cache has been used.
+    /// # Returns
+    /// A `CacheStatistics` containing the hits, misses and number of entries.
+    pub fn statistics(&self) -> CacheStatistics {
+        let entries = self.shards
+            .iter()
+            .map(|shard| {
+                let generations = shard.lock().expect("Score cache lock poisoned.");
+                generations.current.len() + generations.previous.len()
+            })
+            .sum();
+        CacheStatistics {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            entries,
+        }
+    }
+
+    /// Removes every entry and resets the statistics.
+    pub fn clear(&self) {
+        for shard in &self.shards {
+            let mut generations = shard.lock().expect("Score cache lock poisoned.");
+            generations.current.clear();
+            generations.previous.clear();
+        }
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+    }
+
+    /// Helper function for `score`.
+    /// Finds a cached score and moves it into the current generation.
+    fn lookup(&self, key: ScoreKey) -> Option<i16> {
+        let mut generations = self.shard(key).lock().expect("Score cache lock poisoned.");
+        if let Some(&score) = generations.current.get(&key) {
+            return Some(score);
+        }
+        let score = generations.previous.remove(&key)?;
+        self.store(&mut generations, key, score);
+        Some(score)
+    }
+
+    /// Helper function for `score`.
+    /// Adds a newly calculated score to the cache.
+    fn insert(&self, key: ScoreKey, score: i16) {
+        let mut generations = self.shard(key).lock().expect("Score cache lock poisoned.");
+        self.store(&mut generations, key, score);
+    }
+
+    /// Helper function for `lookup` and `insert`.
+    /// Gets the shard a key is stored in from a multiplicative hash of the key.
+    fn shard(&self, key: ScoreKey) -> &Mutex<Generations> {
+        let hash = (key.0 ^ key.1.rotate_left(64)).wrapping_mul(0x9E37_79B9_7F4A_7C15_F39C_C060_5CED_C835);
+        &self.shards[(hash >> (u128::BITS - SHARDS.trailing_zeros())) as usize]
+    }
+
+    /// Helper function for `lookup` and `insert`.
+    /// Stores a score in the current generation, starting a new generation when it is full.
+    fn store(&self, generations: &mut Generations, key: ScoreKey, score: i16) {
+        if generations.current.len() >= self.shard_capacity {
+            generations.previous = std::mem::take(&mut generations.current);
+        }
+        generations.current.insert(key, score);
+    }
+}
+
+/// Scores a hand using the cache shared by every bot.
+/// # Arguments
+/// * `hand` - A `&Hand` representing the hand to score.
+/// * `discard_pile` - A `&CardCollection` representing the discard pile.
+/// # Returns
+/// An `i16` equal to `hand.score(discard_pile)`.
+pub fn cached_score(hand: &Hand, discard_pile: &CardCollection) -> i16 {
+    ScoreCache::global().score(hand, discard_pile)
+}
+
+/// Helper function to build cache keys.
+/// Gets a bit set with one bit for each card in a collection, by card number.
+fn collection_key(cards: CardCollection) -> u128 {
+    cards
+        .iter()
+        .fold(0, |key, card| key | 1 << card_number(card))
+}
+
+#[cfg(test)]
+mod tests {
+    use fantasy_realms_unofficial_api::deck::Card;
+    use super::*;
+
+    #[test]
+    fn old_entries_are_evicted_and_scored_again() {
+        let cache = ScoreCache::new(SHARDS);
+        let discard_pile = CardCollection::new();
+        let hands: Vec<Hand> = (0..40u8)
+            .map(|first| Hand::new(std::array::from_fn(|i| Card::from((first + i as u8) % CARD_COUNT as u8))))
+            .collect();
+        for hand in &hands {
+            assert_eq!(cache.score(hand, &discard_pile), hand.score(&discard_pile));
+        }
+        assert!(cache.statistics().entries <= 2 * SHARDS);
+        assert_eq!(cache.statistics().misses, 40);
+        // The last hand is always in the newest generation of its shard.
+        cache.score(&hands[39], &discard_pile);
+        assert_eq!(cache.statistics().hits, 1);
+        // Evicted hands are scored again correctly.
+        for hand in &hands {
+            assert_eq!(cache.score(hand, &discard_pile), hand.score(&discard_pile));
+        }
+        assert!(cache.statistics().misses > 40);
+        cache.clear();
+        assert_eq!(cache.statistics().entries, 0);
+    }
+}
@@ -0,0 +1,181 @@
+//! # Simulation
+//! A lightweight copy of the game state that bots can play out to the end.
+//! Hidden cards are filled in by sampling the unknown cards,
+//! so each `Simulation` is one possible version of the real game.
+
+use rand::Rng;
+use rand::seq::SliceRandom;
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, deck::Card, hand::Hand, DrawCard};
+use super::belief::Belief;
+use super::score_cache::cached_score;
+
+/// The size of the discard pile that ends the game.
+pub(crate) const END_DISCARD_PILE_SIZE: usize = 10;
+
+/// A turn played in a simulation.
+/// # Fields
+/// * `draw` - A `DrawCard` representing where the card was drawn from.
+/// * `card` - A `Card` representing the card drawn.
+/// * `discard` - A `Card` representing the card discarded.
+#[derive(Clone, Copy, PartialEq)]
+pub(crate) struct SimulatedTurn {
+    pub(crate) draw: DrawCard,
+    pub(crate) card: Card,
+    pub(crate) discard: Card,
+}
+
+/// A sampled game state.
+/// # Fields
+/// * `hands` - A `Vec<Hand>` in turn order, index 0 is the bot running the simulation.
+/// * `discard_pile` - A `CardCollection` representing the discard pile.
+/// * `deck` - A `Vec<Card>` representing the deck, the last card is drawn first.
+/// * `current_turn` - A `usize` representing the index of the player whos turn it is.
+#[derive(Clone)]
+pub(crate) struct Simulation {
+    pub(crate) hands: Vec<Hand>,
+    pub(crate) discard_pile: CardCollection,
+    pub(crate) deck: Vec<Card>,
+    pub(crate) current_turn: usize,
+}
+
+impl Simulation {
+    /// Samples a game state consistent with what the bot knows.
+    /// Hidden cards are dealt to fill each opponents hand from the belief, the rest are shuffled into the deck.
+    /// # Arguments
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be,
+    ///   with any card the bot has drawn already revealed.
+    /// * `drawn` - An `Option<Card>` representing a card the bot has already drawn from the deck.
+    /// * `rng` - A `&mut R` random number generator.
+    /// # Returns
+    /// A `Simulation` where it is the bots turn.
+    pub(crate) fn sample<R: Rng>(
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        drawn: Option<Card>,
+        rng: &mut R,
+    ) -> Self {
+        let mut dealt: Vec<Card> = Vec::new();
+        let mut hands: Vec<Hand> = vec![*hand];
+        for (known, hidden) in known_opponent_cards.iter().zip(belief.sample_hidden_cards(rng)) {
+            let mut cards: Vec<Card> = known.iter().take(7).collect();
+            dealt.extend(hidden.iter().copied());
+            cards.extend(hidden);
+            hands.push(Hand::new(cards.try_into().expect("Not enough cards to fill an opponents hand.")));
+        }
+        let mut deck: Vec<Card> = belief
+            .unknown_cards()
+            .iter()
+            .copied()
+            .filter(|card| !dealt.contains(card) && Some(*card) != drawn)
+            .collect();
+        deck.shuffle(rng);
+        if let Some(card) = drawn {
+            deck.push(card);
+        }
+        Simulation {
+            hands,
+            discard_pile: *discard_pile,
+            deck,
+            current_turn: 0,
+        }
+    }
+
+    /// Checks if the simulated game has ended.
+    /// # Returns
+    /// A `bool` that is true when the discard pile is full or the deck is empty.
+    pub(crate) fn over(&self) -> bool {
+        self.discard_pile.len() as usize >= END_DISCARD_PILE_SIZE || self.deck.is_empty()
+    }
+
+    /// Gets every turn the current player can play.
+    /// # Returns
+    /// A `Vec<SimulatedTurn>` containing each draw from the discard pile,
+    /// and each discard after drawing the top card of the deck.
+    pub(crate) fn legal_turns(&self) -> Vec<SimulatedTurn> {
+        let hand = &self.hands[self.current_turn];
+        let mut turns: Vec<SimulatedTurn> = Vec::new();
+        for card in self.discard_pile {
+            for i in 0..7 {
+                turns.push(SimulatedTurn {draw: DrawCard::Discard (card), card, discard: hand[i]});
+            }
+        }
+        if let Some(&card) = self.deck.last() {
+            turns.push(SimulatedTurn {draw: DrawCard::Deck, card, discard: card});
+            for i in 0..7 {
+                turns.push(SimulatedTurn {draw: DrawCard::Deck, card, discard: hand[i]});
+            }
+        }
+        turns
+    }
+
+    /// Plays a turn for the current player and passes the turn on.
+    /// # Arguments
+    /// * `turn` - A `SimulatedTurn` representing the turn to play.
+    pub(crate) fn play_turn(&mut self, turn: SimulatedTurn) {
+        match turn.draw {
+            DrawCard::Deck => {
+                self.deck.pop();
+            }
+            DrawCard::Discard (card) => {
+                self.discard_pile -= card;
+            }
+        }
+        let hand = &mut self.hands[self.current_turn];
+        if let Some(i) = (0..7).find(|&i| hand[i] == turn.discard) {
+            hand[i] = turn.card;
+        }
+        self.discard_pile += turn.discard;
+        self.current_turn = (self.current_turn + 1) % self.hands.len();
+    }
+
+    /// Generates a cheap turn for the current player.
+    /// The top card of the deck is drawn and the card that leaves the best hand is discarded.
+    /// # Returns
+    /// A `SimulatedTurn` drawing from the deck.
+    pub(crate) fn greedy_deck_turn(&self) -> SimulatedTurn {
+        let hand = &self.hands[self.current_turn];
+        let card = *self.deck.last().expect("Cannot draw from an empty deck.");
+        let mut turn = SimulatedTurn {draw: DrawCard::Deck, card, discard: card};
+        let mut max_score: i16 = cached_score(hand, &(self.discard_pile + card));
+        for i in 0..7 {
+            let mut test_hand = *hand;
+            test_hand[i] = card;
+            let score: i16 = cached_score(&test_hand, &(self.discard_pile + hand[i]));
+            if score > max_score {
+                max_score = score;
+                turn = SimulatedTurn {draw: DrawCard::Deck, card, discard: hand[i]};
+            }
+        }
+        turn
+    }
+
+    /// Plays greedy turns until it is the bots turn again or the game ends.
+    pub(crate) fn play_opponents(&mut self) {
+        while !self.over() && self.current_turn != 0 {
+            let turn = self.greedy_deck_turn();
+            self.play_turn(turn);
+        }
+    }
+
+    /// Plays greedy turns for every player until the game ends.
+    pub(crate) fn play_out(&mut self) {
+        while !self.over() {
+            let turn = self.greedy_deck_turn();
+            self.play_turn(turn);
+        }
+    }
+
+    /// Gets the score of a player.
+    /// # Arguments
+    /// * `player` - A `usize` representing the index of the player.
+    /// # Returns
+    /// An `i16` representing the players score with the current discard pile.
+    pub(crate) fn score(&self, player: usize) -> i16 {
+        cached_score(&self.hands[player], &self.discard_pile)
+    }
+}
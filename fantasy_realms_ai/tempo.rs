@@ -0,0 +1,184 @@
+//! # Tempo
+//! Predicts when the game will end and how the bots draw changes it.
+//! The game ends when the discard pile reaches 10 cards.
+//! Drawing from the deck grows the discard pile by one card,
+//! while drawing from the discard pile leaves it the same size.
+//! If every player draws from the deck the game ends after exactly
+//! `10 - discard_pile.len()` more turns, so the turn order decides who plays last.
+//! Whether a draw from the deck takes a turn away from a player depends on where they sit,
+//! so the bot compares the turns every player has left after a draw from the deck
+//! with the turns they have left after a draw from the discard pile.
+//! A bot that is ahead hurries the game by drawing from the deck when that takes a turn away
+//! from the best opponent, and a bot that is behind stalls by drawing from the discard pile
+//! when a draw from the deck would take away one of its own turns.
+
+use rand::Rng;
+use fantasy_realms_unofficial_api::{card_collection::CardCollection, hand::Hand};
+use super::belief::Belief;
+use super::score_cache::cached_score;
+use super::simulation::{Simulation, END_DISCARD_PILE_SIZE};
+
+/// The default weight of the bots lead when deciding whether to hurry or stall.
+/// Tempo is off by default, and is turned on with the `tempo` parameter or the `Tempo` aliases, which use 0.5.
+pub const DEFAULT_TEMPO_WEIGHT: f32 = 0.0;
+/// The largest lead in points that is taken into account.
+const MAX_LEAD: f32 = 100.0;
+/// The number of samples of the opponents hidden cards.
+const TEMPO_SAMPLES: usize = 3;
+
+/// Gets the number of turns left in the game if every player draws from the deck.
+/// # Arguments
+/// * `discard_pile_size` - A `usize` representing the number of cards in the discard pile.
+/// # Returns
+/// A `usize` representing the number of turns, including the current one.
+pub fn turns_remaining(discard_pile_size: usize) -> usize {
+    END_DISCARD_PILE_SIZE.saturating_sub(discard_pile_size)
+}
+
+/// Gets the number of turns a player has left if every player draws from the deck.
+/// # Arguments
+/// * `discard_pile_size` - A `usize` representing the number of cards in the discard pile.
+/// * `players` - A `usize` representing the number of players in the game.
+/// * `offset` - A `usize` representing how many turns after the current player the player plays,
+///   `(seat - current_seat) mod players`.
+/// # Returns
+/// A `usize` representing the number of turns, including the current one for the current player.
+pub fn player_turns_remaining(discard_pile_size: usize, players: usize, offset: usize) -> usize {
+    let turns = turns_remaining(discard_pile_size);
+    if offset >= turns {
+        return 0;
+    }
+    (turns - offset).div_ceil(players.max(1))
+}
+
+/// Gets the number of turns the current player is sure to have after this one.
+/// Drawing from the discard pile can only add turns, so this is a lower bound.
+/// # Arguments
+/// * `discard_pile_size` - A `usize` representing the number of cards in the discard pile.
+/// * `players` - A `usize` representing the number of players in the game.
+/// # Returns
+/// A `u8` representing the minimum number of turns remaining after the current one.
+pub fn minimum_turns_remaining(discard_pile_size: usize, players: usize) -> u8 {
+    player_turns_remaining(discard_pile_size, players, 0).saturating_sub(1) as u8
+}
+
+/// Gets the number of turns each player has left after the current turn, for each kind of draw.
+/// The current player is at offset 0, and every other player is assumed to draw from the deck.
+/// # Arguments
+/// * `discard_pile_size` - A `usize` representing the number of cards in the discard pile.
+/// * `players` - A `usize` representing the number of players in the game.
+/// # Returns
+/// A `Vec<(usize, usize)>` containing, by offset from the current player, the turns left
+/// after the current player draws from the deck and after they draw from the discard pile.
+pub fn turns_after_draw(discard_pile_size: usize, players: usize) -> Vec<(usize, usize)> {
+    (0..players)
+        .map(|offset| {
+            // After the current turn the next player is at offset 0 and the current player is last.
+            let next_offset = (offset + players - 1) % players.max(1);
+            (
+                player_turns_remaining(discard_pile_size + 1, players, next_offset),
+                player_turns_remaining(discard_pile_size, players, next_offset),
+            )
+        })
+        .collect()
+}
+
+/// The bonus for drawing from the deck based on the state of the game.
+/// # Fields
+/// * `deck_bonus` - A `f32` that is positive when the bot should hurry and negative when it should stall.
+pub struct Tempo {
+    deck_bonus: f32,
+}
+
+impl Tempo {
+    /// Estimates the bots lead and the value of changing the length of the game.
+    /// Each draw from the deck removes one of the turns left in the game, and the turn order
+    /// decides whose turn it is. When the bot is ahead and the best opponent loses a turn,
+    /// the bonus is the lead shared between that opponents turns. When the bot is behind and
+    /// loses one of its own turns, the bonus is the deficit shared between its turns.
+    /// No samples are taken when the weight is zero.
+    /// # Arguments
+    /// * `weight` - A `f32` representing how much the bots lead is worth when choosing where to draw from.
+    /// * `hand` - A `&Hand` representing the bots current hand.
+    /// * `discard_pile` - A `&CardCollection` representing the discard pile.
+    /// * `known_opponent_cards` - A `&[CardCollection]` representing the known cards in opponents hands
+    ///   in turn order, starting with the next player.
+    /// * `belief` - A `&Belief` representing where the unknown cards are likely to be.
+    /// * `rng` - A `&mut R` used to sample the opponents hidden cards.
+    /// # Returns
+    /// A `Tempo` holding the bonus for drawing from the deck.
+    pub fn new<R: Rng>(
+        weight: f32,
+        hand: &Hand,
+        discard_pile: &CardCollection,
+        known_opponent_cards: &[CardCollection],
+        belief: &Belief,
+        rng: &mut R,
+    ) -> Self {
+        let turns = turns_remaining(discard_pile.len() as usize);
+        if weight <= 0.0 || known_opponent_cards.is_empty() || turns == 0 {
+            return Tempo {deck_bonus: 0.0};
+        }
+        let samples: Vec<Simulation> = (0..TEMPO_SAMPLES)
+            .map(|_| Simulation::sample(hand, discard_pile, known_opponent_cards, belief, None, rng))
+            .collect();
+        let (leader, leader_score) = leader(&samples);
+        let lead = (cached_score(hand, discard_pile) as f32 - leader_score).clamp(-MAX_LEAD, MAX_LEAD);
+        let turns_left = turns_after_draw(discard_pile.len() as usize, known_opponent_cards.len() + 1);
+        let (bot_deck, bot_discard) = turns_left[0];
+        let (leader_deck, leader_discard) = turns_left[leader];
+        let deck_bonus = if lead >= 0.0 {
+            lead * (leader_discard - leader_deck) as f32 / leader_discard.max(1) as f32
+        } else {
+            lead * (bot_discard - bot_deck) as f32 / bot_discard.max(1) as f32
+        };
+        Tempo {deck_bonus: weight * deck_bonus}
+    }
+
+    /// Gets the bonus for drawing from the deck.
+    /// # Returns
+    /// A `f32` in points that is positive when the bot is ahead and negative when it is behind.
+    pub fn deck_bonus(&self) -> f32 {
+        self.deck_bonus
+    }
+}
+
+/// Helper function for `Tempo::new`.
+/// Finds the opponent with the best expected score.
+/// # Arguments
+/// * `samples` - A `&[Simulation]` containing sampled opponent hands.
+/// # Returns
+/// A `(usize, f32)` containing the offset of the opponent from the bot and their average score over the samples.
+fn leader(samples: &[Simulation]) -> (usize, f32) {
+    (1..samples[0].hands.len())
+        .map(|opponent| {
+            let total: f32 = samples.iter().map(|sample| sample.score(opponent) as f32).sum();
+            (opponent, total / samples.len() as f32)
+        })
+        .max_by(|(_, a), (_, b)| a.total_cmp(b))
+        .unwrap_or((0, 0.0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn every_turn_left_belongs_to_one_player() {
+        for players in 3..=6 {
+            for pile in 0..END_DISCARD_PILE_SIZE {
+                let total: usize = (0..players).map(|offset| player_turns_remaining(pile, players, offset)).sum();
+                assert_eq!(total, turns_remaining(pile));
+            }
+        }
+    }
+
+    #[test]
+    fn a_draw_from_the_deck_takes_the_last_turn_away() {
+        // Three players with two turns left: the bot and the next player play them.
+        // Drawing from the deck ends the game after the next player, drawing from the pile gives the player after them a turn.
+        assert_eq!(turns_after_draw(8, 3), vec![(0, 0), (1, 1), (0, 1)]);
+        // With one turn left a draw from the deck ends the game at once.
+        assert_eq!(turns_after_draw(9, 3), vec![(0, 0), (0, 1), (0, 0)]);
+    }
+}
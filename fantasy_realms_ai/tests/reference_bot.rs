@@ -0,0 +1,80 @@
+//! Plays whole games against the reference bot running as an external bot.
+
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::SeedableRng;
+use fantasy_realms_unofficial_api::{DrawCard, Game, Player};
+use fantasy_realms_unofficial_api::card_collection::CardCollection;
+use fantasy_realms_unofficial_api::deck::Card;
+use fantasy_realms_unofficial_api::hand::{Hand, Turn};
+use fantasy_realms_ai::{new_bot, FantasyRealmsBot, GameView, ObservedTurn};
+
+/// Creates the reference bot playing as a built in bot.
+fn reference_bot(bot_type: &str) -> Box<dyn FantasyRealmsBot> {
+    let command = format!("external:\"{}\" {bot_type}", env!("CARGO_BIN_EXE_reference_bot"));
+    new_bot(command).unwrap()
+}
+
+/// Plays a game between bots from a shuffled deck.
+/// Kept in the test, since the game loop of the interface crate depends on this crate.
+/// # Returns
+/// A `(Game, Vec<(ObservedTurn, Card)>)` containing the finished game and every turn with the card drawn.
+fn play_game(bots: &mut [Box<dyn FantasyRealmsBot>], seed: u64) -> (Game, Vec<(ObservedTurn, Card)>) {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut deck: Vec<Card> = (!CardCollection::new()).iter().collect();
+    deck.shuffle(&mut rng);
+    let players: Vec<Player> = bots
+        .iter()
+        .map(|bot| {
+            let cards: Vec<Card> = deck.drain(..7).collect();
+            Player::new(bot.name(), Hand::new(cards.try_into().unwrap()))
+        })
+        .collect();
+    let mut deck = deck.into_iter();
+    let mut game = Game::new(players).unwrap();
+    let player_count = bots.len();
+    for (seat, bot) in bots.iter_mut().enumerate() {
+        bot.seed(seed + seat as u64);
+        bot.on_game_start(seat, player_count, &game.players[seat].hand);
+    }
+    let mut turns: Vec<(ObservedTurn, Card)> = Vec::new();
+    while !game.over {
+        let seat = game.current_turn;
+        let view = GameView::new(
+            game.players[seat].hand,
+            seat,
+            game.players.iter().map(|player| player.cards_known_to_opponents).collect(),
+            game.discard_pile,
+            turns.iter().map(|(turn, _)| *turn).collect(),
+        );
+        let draw = bots[seat].generate_draw(&view);
+        let card = match draw {
+            DrawCard::Discard(card) => card,
+            _ => deck.next().unwrap(),
+        };
+        let discard = bots[seat].generate_discard(&view, &card);
+        game.play_turn(Turn::new(card, discard)).unwrap();
+        let turn = ObservedTurn {seat, draw, discard};
+        for bot in bots.iter_mut() {
+            bot.observe_turn(&turn);
+        }
+        turns.push((turn, card));
+    }
+    (game, turns)
+}
+
+#[test]
+fn reference_bot_plays_a_whole_game() {
+    let mut bots = vec![
+        reference_bot("Randy"),
+        new_bot("Randy".to_string()).unwrap(),
+        reference_bot("AutomatosV1"),
+    ];
+    assert_eq!(bots[0].name().trim(), "Randy");
+    let (game, turns) = play_game(&mut bots, 42);
+    assert_eq!(game.discard_pile.len(), 10);
+    assert_eq!(game.players.len(), 3);
+    // A failed external bot discards every card it draws, Randy almost never does so every turn.
+    let external_turns: Vec<_> = turns.iter().filter(|(turn, _)| turn.seat == 0).collect();
+    assert!(external_turns.iter().any(|(turn, card)| *card != turn.discard));
+}
@@ -0,0 +1,366 @@
+//! # Wasm
+//! Runs untrusted bots compiled to WebAssembly in a sandbox.
+//! A bot can only use the memory and fuel it is given, and cannot reach the file system,
+//! the network or the rest of the program, so bots from competitions can be played safely.
+//! Fuel is used up by every instruction and is refilled before each decision,
+//! which limits the time a bot can think without depending on the speed of the machine.
+//!
+//! A bot module exports its `memory` and these functions:
+//! * `fr_abi_version() -> i32` returning `WASM_ABI_VERSION`.
+//! * `fr_alloc(len: i32) -> i32` returning a pointer the host can write `len` bytes to.
+//! * `fr_generate_draw(view: i32, len: i32) -> i32` returning the card to draw
+//!   from the discard pile, or -1 to draw from the deck.
+//! * `fr_generate_discard(view: i32, len: i32, draw: i32) -> i32` returning the card to discard.
+//!
+//! A module can also export `fr_seed(seed: i64)`, which is called with the bits of the seed of a seeded game
+//! once the instance for the game is created, before any decision.
+//! A bot that makes random choices should make all of them from a generator seeded with it,
+//! so that the game can be replayed. A bot that makes no random choices does not export it.
+//!
+//! The view is a list of bytes: the bots seat, the number of players, the turns remaining for all players,
+//! then the hand, the discard pile and the known cards of each seat in order,
+//! each written as the number of cards followed by the cards.
+//! Cards are numbered by their position in the full deck, the same as for plugins.
+//! Growing the memory past its limit traps, so it is treated the same as running out of memory.
+//! If a bot traps, runs out of fuel or memory, or makes an illegal move it plays
+//! a default move, drawing from the deck and discarding the card drawn,
+//! until the next game starts with a fresh instance.
+
+use std::path::Path;
+use wasmtime::{Config, Engine, Instance, Linker, Memory, Module, Store, StoreLimits, StoreLimitsBuilder, TypedFunc};
+use fantasy_realms_unofficial_api::{deck::Card, hand::Hand, DrawCard};
+use super::{FantasyRealmsBot, GameView};
+use super::card_numbers::{card_number, numbered_card};
+
+/// The version of the ABI the host expects bots to be built for.
+pub const WASM_ABI_VERSION: i32 = 1;
+/// The prefix of a bot name that runs a WebAssembly bot.
+pub const WASM_PREFIX: &str = "wasm:";
+/// The default fuel given to a bot for each decision.
+pub const DEFAULT_FUEL: u64 = 200_000_000;
+/// The default largest memory a bot can use in bytes.
+pub const DEFAULT_MEMORY_LIMIT: usize = 64 << 20;
+
+/// A running instance of a bot module.
+/// # Fields
+/// * `store` - A `Store<StoreLimits>` holding the instances memory and fuel.
+/// * `memory` - A `Memory` exported by the bot.
+/// * `alloc` - The bots `fr_alloc` function.
+/// * `generate_draw` - The bots `fr_generate_draw` function.
+/// * `generate_discard` - The bots `fr_generate_discard` function.
+/// * `seed` - The bots `fr_seed` function, if it exports one.
+struct WasmInstance {
+    store: Store<StoreLimits>,
+    memory: Memory,
+    alloc: TypedFunc<i32, i32>,
+    generate_draw: TypedFunc<(i32, i32), i32>,
+    generate_discard: TypedFunc<(i32, i32, i32), i32>,
+    seed: Option<TypedFunc<i64, ()>>,
+}
+
+/// A bot running in a WebAssembly sandbox.
+/// # Fields
+/// * `name` - A `String` representing the name of the bot, taken from its file name.
+/// * `engine` - An `Engine` that compiled the module.
+/// * `module` - A `Module` containing the compiled bot.
+/// * `fuel` - A `u64` representing the fuel given for each decision.
+/// * `memory_limit` - A `usize` representing the largest memory the bot can use in bytes.
+/// * `seed` - An `Option<u64>` holding the seed for the next game until its instance is created.
+/// * `instance` - An `Option<WasmInstance>` which is `None` once the bot has failed.
+pub struct WasmBot {
+    name: String,
+    engine: Engine,
+    module: Module,
+    fuel: u64,
+    memory_limit: usize,
+    seed: Option<u64>,
+    instance: Option<WasmInstance>,
+}
+
+impl WasmBot {
+    /// Compiles a bot module and checks that it follows the ABI.
+    /// # Arguments
+    /// * `path` - A `&Path` to the `.wasm` file.
+    /// * `fuel` - A `u64` representing the fuel given for each decision.
+    /// * `memory_limit` - A `usize` representing the largest memory the bot can use in bytes.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The module cannot be read or compiled.
+    /// * The module does not export the ABI functions or was built for a different version.
+    /// # Returns
+    /// A `Result<WasmBot, String>` which is:
+    /// * `Ok(WasmBot)` if the bot is ready to play.
+    /// * `Err(String)` containing an error message if it is not.
+    pub fn load(path: &Path, fuel: u64, memory_limit: usize) -> Result<Self, String> {
+        let error = |message: String| format!("Failed to load WebAssembly bot {}: {message}", path.display());
+        let engine = fuel_engine().map_err(error)?;
+        let module = Module::from_file(&engine, path).map_err(|e| error(e.to_string()))?;
+        let name = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or("WasmBot".to_string());
+        WasmBot::from_module(name, engine, module, fuel, memory_limit).map_err(error)
+    }
+
+    /// Helper function for `load`.
+    /// Creates a bot from a compiled module and checks that it follows the ABI.
+    fn from_module(name: String, engine: Engine, module: Module, fuel: u64, memory_limit: usize) -> Result<Self, String> {
+        let mut bot = WasmBot {name, engine, module, fuel, memory_limit, seed: None, instance: None};
+        bot.instance = Some(bot.instantiate()?);
+        Ok(bot)
+    }
+
+    /// Helper function for `load` and `on_game_start`.
+    /// Creates a fresh instance of the module with its limits.
+    fn instantiate(&self) -> Result<WasmInstance, String> {
+        let limits = StoreLimitsBuilder::new()
+            .memory_size(self.memory_limit)
+            .instances(1)
+            .trap_on_grow_failure(true)
+            .build();
+        let mut store = Store::new(&self.engine, limits);
+        store.limiter(|limits| limits);
+        store.set_fuel(self.fuel).map_err(|e| e.to_string())?;
+        let linker: Linker<StoreLimits> = Linker::new(&self.engine);
+        let instance: Instance = linker.instantiate(&mut store, &self.module).map_err(|e| e.to_string())?;
+        let abi_version = instance
+            .get_typed_func::<(), i32>(&mut store, "fr_abi_version")
+            .and_then(|abi_version| abi_version.call(&mut store, ()))
+            .map_err(|e| e.to_string())?;
+        if abi_version != WASM_ABI_VERSION {
+            return Err(format!("Built for ABI version {abi_version} but version {WASM_ABI_VERSION} is required."));
+        }
+        let memory = instance.get_memory(&mut store, "memory").ok_or("No memory exported.".to_string())?;
+        let seed = match instance.get_func(&mut store, "fr_seed") {
+            Some(seed) => Some(seed.typed::<i64, ()>(&store).map_err(|e| e.to_string())?),
+            None => None,
+        };
+        Ok(WasmInstance {
+            alloc: instance.get_typed_func(&mut store, "fr_alloc").map_err(|e| e.to_string())?,
+            generate_draw: instance.get_typed_func(&mut store, "fr_generate_draw").map_err(|e| e.to_string())?,
+            generate_discard: instance.get_typed_func(&mut store, "fr_generate_discard").map_err(|e| e.to_string())?,
+            seed,
+            store,
+            memory,
+        })
+    }
+
+    /// Helper function for the bot functions.
+    /// Refills the fuel and writes the view into the bots memory.
+    /// # Returns
+    /// A `Result<(i32, i32), String>` containing the pointer and length of the view.
+    fn write_view(&mut self, view: &GameView) -> Result<(i32, i32), String> {
+        let fuel = self.fuel;
+        let instance = self.instance.as_mut().ok_or("The bot has already failed.".to_string())?;
+        instance.store.set_fuel(fuel).map_err(|e| e.to_string())?;
+        let bytes = encode_view(view);
+        let len = bytes.len() as i32;
+        let pointer = instance.alloc.call(&mut instance.store, len).map_err(|e| e.to_string())?;
+        instance.memory
+            .write(&mut instance.store, pointer as u32 as usize, &bytes)
+            .map_err(|e| e.to_string())?;
+        Ok((pointer, len))
+    }
+
+    /// Helper function for the bot functions.
+    /// Reports a failure and drops the instance so that default moves are played.
+    fn fail(&mut self, error: String) {
+        eprintln!("{} failed: {error} Playing default moves until the next game.", self.name.trim());
+        self.instance = None;
+    }
+}
+
+impl FantasyRealmsBot for WasmBot {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn generate_draw(&mut self, view: &GameView) -> DrawCard {
+        if self.instance.is_none() {
+            return DrawCard::Deck;
+        }
+        let draw = self.write_view(view).and_then(|(pointer, len)| {
+            let instance = self.instance.as_mut().ok_or("The bot has already failed.".to_string())?;
+            instance.generate_draw
+                .call(&mut instance.store, (pointer, len))
+                .map_err(|e| e.to_string())
+        });
+        let draw = draw.and_then(|draw| {
+            if draw == -1 {
+                return Ok(DrawCard::Deck);
+            }
+            match usize::try_from(draw).ok().and_then(numbered_card) {
+                Some(card) if view.discard_pile.iter().any(|pile_card| pile_card == card) => Ok(DrawCard::Discard (card)),
+                _ => Err(format!("Drew card {draw} which is not in the discard pile.")),
+            }
+        });
+        draw.unwrap_or_else(|e| {
+            self.fail(e);
+            DrawCard::Deck
+        })
+    }
+
+    fn generate_discard(&mut self, view: &GameView, draw: &Card) -> Card {
+        if self.instance.is_none() {
+            return *draw;
+        }
+        let draw_number = card_number(*draw) as i32;
+        let discard = self.write_view(view).and_then(|(pointer, len)| {
+            let instance = self.instance.as_mut().ok_or("The bot has already failed.".to_string())?;
+            instance.generate_discard
+                .call(&mut instance.store, (pointer, len, draw_number))
+                .map_err(|e| e.to_string())
+        });
+        let discard = discard.and_then(|discard| {
+            match usize::try_from(discard).ok().and_then(numbered_card) {
+                Some(card) if card == *draw || view.hand.to_vec().contains(&card) => Ok(card),
+                _ => Err(format!("Discarded card {discard} which is not in its hand.")),
+            }
+        });
+        discard.unwrap_or_else(|e| {
+            self.fail(e);
+            *draw
+        })
+    }
+
+    fn on_game_start(&mut self, _: usize, _: usize, _: &Hand) {
+        let seed = self.seed.take();
+        let instance = self.instantiate().and_then(|mut instance| {
+            if let (Some(fr_seed), Some(seed)) = (instance.seed.as_ref(), seed) {
+                fr_seed.call(&mut instance.store, seed as i64).map_err(|e| e.to_string())?;
+            }
+            Ok(instance)
+        });
+        match instance {
+            Ok(instance) => self.instance = Some(instance),
+            Err(e) => self.fail(e),
+        }
+    }
+
+    fn seed(&mut self, seed: u64) {
+        self.seed = Some(seed);
+    }
+}
+
+/// Helper function for `WasmBot::load`.
+/// Creates an engine that counts the fuel used by every instruction.
+fn fuel_engine() -> Result<Engine, String> {
+    let mut config = Config::new();
+    config.consume_fuel(true);
+    Engine::new(&config).map_err(|e| e.to_string())
+}
+
+/// Helper function for `write_view`.
+/// Writes a view as the list of bytes described by the ABI.
+fn encode_view(view: &GameView) -> Vec<u8> {
+    let mut bytes: Vec<u8> = vec![view.seat as u8, view.players() as u8, view.total_turns_remaining() as u8];
+    let mut push_cards = |cards: Vec<Card>| {
+        bytes.push(cards.len() as u8);
+        bytes.extend(cards.into_iter().map(card_number));
+    };
+    push_cards(view.hand.to_vec());
+    push_cards(view.discard_pile.iter().collect());
+    for cards in &view.known_cards {
+        push_cards(cards.iter().collect());
+    }
+    bytes
+}
+
+/// Loads a WebAssembly bot with the default limits.
+/// # Arguments
+/// * `path` - A `&str` containing the path to the `.wasm` file.
+/// # Errors
+/// This function returns an `Err(String)` if the bot cannot be loaded, see `WasmBot::load`.
+/// # Returns
+/// A `Result<WasmBot, String>` containing the bot.
+pub fn load_wasm_bot(path: &str) -> Result<WasmBot, String> {
+    WasmBot::load(Path::new(path.trim()), DEFAULT_FUEL, DEFAULT_MEMORY_LIMIT)
+}
+
+/// Checks that there is a file at the path of a WebAssembly bot, without compiling it.
+/// # Arguments
+/// * `path` - A `&str` containing the path to the `.wasm` file.
+/// # Errors
+/// This function returns an `Err(String)` if there is no file at the path.
+pub fn validate_path(path: &str) -> Result<(), String> {
+    if Path::new(path.trim()).is_file() {
+        Ok(())
+    } else {
+        Err(format!("No WebAssembly bot found at {}.", path.trim()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use fantasy_realms_unofficial_api::card_collection::CardCollection;
+    use super::*;
+
+    /// Builds a bot from a module that follows the ABI, with the bodies of its two decisions given.
+    fn bot(draw: &str, discard: &str, fuel: u64, memory_limit: usize) -> WasmBot {
+        let wat = format!(r#"
+            (module
+                (memory (export "memory") 1)
+                (func (export "fr_abi_version") (result i32) (i32.const 1))
+                (func (export "fr_alloc") (param i32) (result i32) (i32.const 0))
+                (func (export "fr_generate_draw") (param i32 i32) (result i32) {draw})
+                (func (export "fr_generate_discard") (param i32 i32 i32) (result i32) {discard}))
+        "#);
+        let engine = fuel_engine().unwrap();
+        let module = Module::new(&engine, wat).unwrap();
+        WasmBot::from_module("Test".to_string(), engine, module, fuel, memory_limit).unwrap()
+    }
+
+    /// Seat 0 of two holding the first seven cards, with the next two cards in the discard pile.
+    fn view() -> GameView {
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let discard_pile = CardCollection::new() + Card::from(7) + Card::from(8);
+        GameView::new(hand, 0, vec![CardCollection::new(); 2], discard_pile, Vec::new())
+    }
+
+    /// Checks that a failed bot plays the default moves, and is given a fresh instance by the next game.
+    fn assert_falls_back(mut bot: WasmBot) {
+        let view = view();
+        assert_eq!(bot.generate_draw(&view), DrawCard::Deck);
+        assert!(bot.instance.is_none());
+        let draw = Card::from(20);
+        assert_eq!(bot.generate_discard(&view, &draw), draw);
+        bot.on_game_start(0, 2, &view.hand);
+        assert!(bot.instance.is_some());
+    }
+
+    #[test]
+    fn a_legal_bot_is_played() {
+        let number = |card: u8| card_number(Card::from(card));
+        let mut bot = bot(&format!("(i32.const {})", number(8)), &format!("(i32.const {})", number(3)), DEFAULT_FUEL, DEFAULT_MEMORY_LIMIT);
+        assert_eq!(bot.generate_draw(&view()), DrawCard::Discard (Card::from(8)));
+        assert_eq!(bot.generate_discard(&view(), &Card::from(8)), Card::from(3));
+        assert!(bot.instance.is_some());
+    }
+
+    #[test]
+    fn a_bot_that_traps_plays_default_moves() {
+        assert_falls_back(bot("(unreachable)", "(unreachable)", DEFAULT_FUEL, DEFAULT_MEMORY_LIMIT));
+    }
+
+    #[test]
+    fn a_bot_that_runs_out_of_fuel_plays_default_moves() {
+        assert_falls_back(bot("(loop (br 0)) (i32.const -1)", "(i32.const 0)", 10_000, DEFAULT_MEMORY_LIMIT));
+    }
+
+    #[test]
+    fn a_bot_that_grows_past_its_memory_limit_plays_default_moves() {
+        assert_falls_back(bot("(drop (memory.grow (i32.const 4))) (i32.const -1)", "(i32.const 0)", DEFAULT_FUEL, 2 << 16));
+    }
+
+    #[test]
+    fn a_bot_that_makes_an_illegal_move_plays_default_moves() {
+        let not_in_pile = card_number(Card::from(30));
+        assert_falls_back(bot(&format!("(i32.const {not_in_pile})"), "(i32.const 0)", DEFAULT_FUEL, DEFAULT_MEMORY_LIMIT));
+
+        let not_in_hand = card_number(Card::from(30));
+        let mut bot = bot("(i32.const -1)", &format!("(i32.const {not_in_hand})"), DEFAULT_FUEL, DEFAULT_MEMORY_LIMIT);
+        assert_eq!(bot.generate_draw(&view()), DrawCard::Deck);
+        assert_eq!(bot.generate_discard(&view(), &Card::from(20)), Card::from(20));
+        assert!(bot.instance.is_none());
+    }
+}
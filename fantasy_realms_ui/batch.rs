@@ -0,0 +1,375 @@
+//! # Batch
+//! Runs many simulated games at once without printing them, for large experiments.
+//! Games are handed out to a pool of worker threads as each worker finishes its last game.
+//! Every game has its own seed chosen up front from the seed of the batch,
+//! so for bots that replay exactly from a seed the results are the same however many threads are used.
+//! Bots limited by time, AutomatosV3 and ISMCTS without an iteration budget, search less when the
+//! machine is busy, so their results change from run to run and with the number of threads.
+//! The bots are rotated one seat every game so that each bot plays from every seat.
+//! With more than one worker the bots evaluate their moves on their own worker's thread,
+//! so a batch never runs more threads than it was given.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::thread;
+use std::time::{Duration, Instant};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use fantasy_realms_unofficial_api::hand::Hand;
+use fantasy_realms_ai::{new_bot, run_sequentially, validate_bot, FantasyRealmsBot};
+use super::simulated_game::play_simulated_game;
+use super::tournament::{MAX_PLAYERS, MIN_PLAYERS};
+
+/// The result of one bot in a game.
+/// # Fields
+/// * `seat` - A `usize` representing the seat the bot played from.
+/// * `bot` - A `String` representing the spec the bot was created from.
+/// * `hand` - A `Hand` representing the bots final hand.
+/// * `score` - An `i16` representing the bots final score.
+/// * `placement` - A `usize` representing the place the bot finished in, where 1 is first.
+#[derive(Clone)]
+pub struct SeatResult {
+    pub seat: usize,
+    pub bot: String,
+    pub hand: Hand,
+    pub score: i16,
+    pub placement: usize,
+}
+
+/// The result of a game in a batch.
+/// # Fields
+/// * `game` - A `usize` representing the number of the game in the batch.
+/// * `seed` - A `u64` representing the seed the game was dealt from.
+/// * `seats` - A `Vec<SeatResult>` containing the result of each seat.
+/// * `turns` - A `usize` representing the number of turns played.
+#[derive(Clone)]
+pub struct BatchGameResult {
+    pub game: usize,
+    pub seed: u64,
+    pub seats: Vec<SeatResult>,
+    pub turns: usize,
+}
+
+/// The totals of one bot over a batch.
+/// # Fields
+/// * `bot` - A `String` representing the spec of the bot. Copies of the same spec are combined.
+/// * `games` - A `usize` representing the number of seats the bot played.
+/// * `wins` - A `f64` representing the number of games finished first, ties share a win.
+/// * `total_score` - An `i64` representing the sum of the bots final scores.
+/// * `total_placement` - A `usize` representing the sum of the places the bot finished in.
+pub struct BotSummary {
+    pub bot: String,
+    pub games: usize,
+    pub wins: f64,
+    pub total_score: i64,
+    pub total_placement: usize,
+}
+
+/// The results of a batch.
+/// # Fields
+/// * `results` - A `Vec<BatchGameResult>` containing every game in order.
+/// * `elapsed` - A `Duration` representing how long the batch took.
+/// * `threads` - A `usize` representing the number of worker threads used.
+pub struct BatchReport {
+    pub results: Vec<BatchGameResult>,
+    pub elapsed: Duration,
+    pub threads: usize,
+}
+
+impl BatchReport {
+    /// Adds up the results of every bot.
+    /// # Returns
+    /// A `Vec<BotSummary>` containing each bot in the order it first appears.
+    pub fn summaries(&self) -> Vec<BotSummary> {
+        let mut summaries: Vec<BotSummary> = Vec::new();
+        for result in &self.results {
+            let winners = result.seats.iter().filter(|seat| seat.placement == 1).count();
+            for seat in &result.seats {
+                let index = match summaries.iter().position(|summary| summary.bot == seat.bot) {
+                    Some(index) => index,
+                    None => {
+                        summaries.push(BotSummary {
+                            bot: seat.bot.clone(),
+                            games: 0,
+                            wins: 0.0,
+                            total_score: 0,
+                            total_placement: 0,
+                        });
+                        summaries.len() - 1
+                    }
+                };
+                let summary = &mut summaries[index];
+                summary.games += 1;
+                summary.total_score += seat.score as i64;
+                summary.total_placement += seat.placement;
+                if seat.placement == 1 {
+                    summary.wins += 1.0 / winners as f64;
+                }
+            }
+        }
+        summaries
+    }
+
+    /// Gets the total number of turns played.
+    /// # Returns
+    /// A `usize` representing the turns in every game.
+    pub fn turns(&self) -> usize {
+        self.results.iter().map(|result| result.turns).sum()
+    }
+
+    /// Writes the batch as JSON, with the totals of every bot and the result of every seat in every game.
+    /// # Arguments
+    /// * `seed` - A `u64` representing the seed the batch was played from.
+    /// # Returns
+    /// A `String` containing a JSON object.
+    pub fn to_json(&self, seed: u64) -> String {
+        let bots: Vec<String> = self.summaries()
+            .iter()
+            .map(|summary| {
+                let games = summary.games.max(1) as f64;
+                format!(
+                    "{{\"bot\": {}, \"games\": {}, \"wins\": {}, \"average_score\": {}, \"average_placement\": {}}}",
+                    json_string(&summary.bot),
+                    summary.games,
+                    summary.wins,
+                    summary.total_score as f64 / games,
+                    summary.total_placement as f64 / games,
+                )
+            })
+            .collect();
+        let games: Vec<String> = self.results
+            .iter()
+            .map(|result| {
+                let seats: Vec<String> = result.seats
+                    .iter()
+                    .map(|seat| {
+                        let hand: Vec<String> = seat.hand.to_vec().iter().map(|card| json_string(&card.to_string())).collect();
+                        format!(
+                            "{{\"seat\": {}, \"bot\": {}, \"score\": {}, \"placement\": {}, \"hand\": [{}]}}",
+                            seat.seat,
+                            json_string(&seat.bot),
+                            seat.score,
+                            seat.placement,
+                            hand.join(", "),
+                        )
+                    })
+                    .collect();
+                format!(
+                    "    {{\"game\": {}, \"seed\": {}, \"turns\": {}, \"seats\": [{}]}}",
+                    result.game,
+                    result.seed,
+                    result.turns,
+                    seats.join(", "),
+                )
+            })
+            .collect();
+        format!(
+            "{{\n  \"seed\": {seed},\n  \"games\": {},\n  \"threads\": {},\n  \"seconds\": {},\n  \"bots\": [\n    {}\n  ],\n  \"results\": [\n{}\n  ]\n}}\n",
+            self.results.len(),
+            self.threads,
+            self.elapsed.as_secs_f64(),
+            bots.join(",\n    "),
+            games.join(",\n"),
+        )
+    }
+
+    /// Displays the totals of every bot and the throughput of the batch.
+    pub fn display(&self) {
+        let summaries = self.summaries();
+        let width = summaries.iter().map(|summary| summary.bot.len()).max().unwrap_or(0).max(3);
+        println!("{:<width$} {:>6} {:>6} {:>9} {:>9}", "Bot", "Games", "Wins", "Avg score", "Avg place");
+        for summary in summaries {
+            let games = summary.games.max(1) as f64;
+            println!(
+                "{:<width$} {:>6} {:>5.1}% {:>9.1} {:>9.2}",
+                summary.bot,
+                summary.games,
+                100.0 * summary.wins / games,
+                summary.total_score as f64 / games,
+                summary.total_placement as f64 / games,
+            );
+        }
+        let seconds = self.elapsed.as_secs_f64().max(f64::EPSILON);
+        println!(
+            "Played {} games ({} turns) in {:.1} seconds on {} threads, {:.2} games per second, {:.1} turns per second.",
+            self.results.len(),
+            self.turns(),
+            seconds,
+            self.threads,
+            self.results.len() as f64 / seconds,
+            self.turns() as f64 / seconds,
+        );
+    }
+}
+
+/// Helper function for `BatchReport::to_json`.
+/// Writes a string as a JSON string, escaping quotes, backslashes and control characters.
+fn json_string(text: &str) -> String {
+    let mut json = String::from("\"");
+    for character in text.chars() {
+        match character {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            character if character.is_control() => json.push_str(&format!("\\u{:04x}", character as u32)),
+            character => json.push(character),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Plays a batch of games on a pool of threads without printing any turns.
+/// # Arguments
+/// * `specs` - A `&[String]` containing the spec of the bot in each seat of the first game.
+/// * `games` - A `usize` representing the number of games to play.
+/// * `seed` - A `u64` representing the seed of the batch, the seed of every game is chosen from it.
+/// * `threads` - A `usize` representing the number of worker threads, 0 uses one for each core.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * There are not between 3 and 6 bots.
+/// * Any of the specs is not a bot.
+/// * Any game fails, the error of the first game to fail is returned.
+/// # Returns
+/// A `Result<BatchReport, String>` containing the result of every game.
+pub fn run_batch(specs: &[String], games: usize, seed: u64, threads: usize) -> Result<BatchReport, String> {
+    if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&specs.len()) {
+        return Err(format!("A batch needs between {MIN_PLAYERS} and {MAX_PLAYERS} bots."));
+    }
+    for spec in specs {
+        validate_bot(spec)?;
+    }
+    let players = specs.len();
+    let tables: Vec<Vec<String>> = (0..games)
+        .map(|game| (0..players).map(|seat| specs[(seat + game) % players].clone()).collect())
+        .collect();
+    play_tables(&tables, seed, threads)
+}
+
+/// Plays a batch of games with the bots in each game seated as given, on a pool of threads without printing any turns.
+/// # Arguments
+/// * `tables` - A `&[Vec<String>]` containing, for each game, the spec of the bot in each seat.
+/// * `seed` - A `u64` representing the seed of the batch, the seed of every game is chosen from it.
+/// * `threads` - A `usize` representing the number of worker threads, 0 uses one for each core.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * Any table does not have between 3 and 6 bots.
+/// * Any of the specs is not a bot.
+/// * Any game fails, the error of the first game to fail is returned.
+/// # Returns
+/// A `Result<BatchReport, String>` containing the result of every game.
+pub fn run_tables(tables: &[Vec<String>], seed: u64, threads: usize) -> Result<BatchReport, String> {
+    for table in tables {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&table.len()) {
+            return Err(format!("A batch needs between {MIN_PLAYERS} and {MAX_PLAYERS} bots."));
+        }
+        for spec in table {
+            validate_bot(spec)?;
+        }
+    }
+    play_tables(tables, seed, threads)
+}
+
+/// Helper function for `run_batch` and `run_tables`.
+/// Plays every table on a pool of worker threads, once the specs have been checked.
+fn play_tables(tables: &[Vec<String>], seed: u64, threads: usize) -> Result<BatchReport, String> {
+    let games = tables.len();
+    let threads = match threads {
+        0 => thread::available_parallelism().map(|threads| threads.get()).unwrap_or(1),
+        threads => threads,
+    }.min(games.max(1));
+    let mut rng = StdRng::seed_from_u64(seed);
+    let seeds: Vec<u64> = (0..games).map(|_| rng.gen()).collect();
+    let next = AtomicUsize::new(0);
+    let start = Instant::now();
+    let mut results: Vec<(usize, Result<BatchGameResult, String>)> = thread::scope(|scope| {
+        let handles: Vec<_> = (0..threads)
+            .map(|_| scope.spawn(|| {
+                let mut played: Vec<(usize, Result<BatchGameResult, String>)> = Vec::new();
+                let mut play = || loop {
+                    let game = next.fetch_add(1, Ordering::Relaxed);
+                    if game >= games {
+                        return;
+                    }
+                    played.push((game, play_batch_game(&tables[game], game, seeds[game])));
+                };
+                // The workers already use every thread asked for, so the bots do not start their own.
+                if threads > 1 {
+                    run_sequentially(play);
+                } else {
+                    play();
+                }
+                played
+            }))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("Batch thread panicked."))
+            .collect()
+    });
+    let elapsed = start.elapsed();
+    results.sort_by_key(|(game, _)| *game);
+    let results = results
+        .into_iter()
+        .map(|(_, result)| result)
+        .collect::<Result<Vec<BatchGameResult>, String>>()?;
+    Ok(BatchReport {results, elapsed, threads})
+}
+
+/// Helper function for `play_tables`.
+/// Plays one game with the bots seated as given.
+fn play_batch_game(seat_specs: &[String], game: usize, seed: u64) -> Result<BatchGameResult, String> {
+    let mut bots: Vec<Box<dyn FantasyRealmsBot>> = seat_specs
+        .iter()
+        .map(|spec| new_bot(spec.to_string()))
+        .collect::<Result<Vec<Box<dyn FantasyRealmsBot>>, String>>()?;
+    let result = play_simulated_game(&mut bots, seed, false)?;
+    let placements = result.placements();
+    let seats = seat_specs
+        .iter()
+        .enumerate()
+        .map(|(seat, spec)| SeatResult {
+            seat,
+            bot: spec.clone(),
+            hand: result.hands[seat],
+            score: result.scores[seat],
+            placement: placements[seat],
+        })
+        .collect();
+    Ok(BatchGameResult {game, seed, seats, turns: result.turns.len()})
+}
+
+/// Runs a batch and displays the results.
+/// # Arguments
+/// * `specs` - A `Vec<String>` containing the spec of each bot.
+/// * `games` - A `usize` representing the number of games to play.
+/// * `seed` - A `u64` representing the seed of the batch.
+/// # Errors
+/// This function returns an `Err(String)` if a bot cannot be created or a game fails.
+pub fn run_quiet_batch(specs: Vec<String>, games: usize, seed: u64) -> Result<(), String> {
+    let report = run_batch(&specs, games, seed, 0)?;
+    report.display();
+    println!("Seed {seed}, enter it when starting a new batch to replay these games.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_seeded_batch_is_the_same_on_any_number_of_threads() {
+        let specs = vec!["Randy".to_string(); 3];
+        let outcome = |report: BatchReport| -> Vec<String> {
+            report.results
+                .iter()
+                .flat_map(|result| result.seats.iter().map(move |seat| {
+                    format!("{} {} {} {} {} {}", result.game, result.seed, result.turns, seat.bot, seat.score, seat.placement)
+                }))
+                .collect()
+        };
+        let one = run_batch(&specs, 8, 11, 1).unwrap();
+        let four = run_batch(&specs, 8, 11, 4).unwrap();
+        assert_eq!((one.threads, four.threads), (1, 4));
+        assert_eq!(outcome(one), outcome(four));
+    }
+}
@@ -0,0 +1,389 @@
+//! # Command Line
+//! Subcommands and flags for running the UI without answering prompts, so scripts and CI jobs can drive it.
+//! Running the UI with no arguments starts the interactive terminal interface as before.
+//!
+//! ```text
+//! fantasy_realms_ui simulate --players AutomatosV1,AutomatosV1,Randy --games 500 --seed 42 --output results.json
+//! fantasy_realms_ui tournament --players Randy,AutomatosV1,AutomatosV2 --rounds 10 --seed 7
+//! fantasy_realms_ui duplicate --players Randy,AutomatosV1,AutomatosV2 --deals 50
+//! fantasy_realms_ui head-to-head --a AutomatosV2 --b AutomatosV1 --field Randy --players 4 --margin 55% --seed 3
+//! fantasy_realms_ui analyze records/physical-1700000000000.txt --bot AutomatosV2(depth=2)
+//! fantasy_realms_ui replay records/simulated-1700000000000.txt
+//! fantasy_realms_ui play
+//! ```
+
+use std::fs;
+use std::path::PathBuf;
+use fantasy_realms_ai::registry::split_specs;
+use super::batch::run_batch;
+use super::deal::random_seed;
+use super::duplicate::run_duplicate;
+use super::head_to_head::{parse_margin, run_head_to_head, Margin};
+use super::record::run_replay;
+use super::replay_viewer::run_analysis;
+use super::tournament::{run_tournament, MIN_PLAYERS};
+
+/// The bot used by `analyze` when no bot is given.
+pub const DEFAULT_ANALYSIS_BOT: &str = "AutomatosV1";
+/// The opponents used by `head-to-head` when no field is given.
+pub const DEFAULT_FIELD_BOT: &str = "AutomatosV1";
+
+/// The flags that are given without a value.
+const SWITCHES: [&str; 1] = ["resume"];
+
+/// The usage printed by `help` and after a mistake in the arguments.
+pub const USAGE: &str = "\
+Usage: fantasy_realms_ui [COMMAND] [OPTIONS]
+
+With no command the interactive terminal interface is started.
+
+Commands:
+  play                          Start a game, answering prompts for the players and turns.
+      --resume                  Resume the unfinished physical game instead of asking.
+  simulate                      Play many simulated games without printing them.
+      -p, --players <BOTS>      The bot in each seat separated by commas, 3 to 6 bots.
+      -n, --games <N>           The number of games, default 1.
+      -s, --seed <SEED>         The seed of the games, random if not given.
+      -t, --threads <N>         The number of threads, default one for each core.
+      -o, --output <FILE>       Also write every result to a JSON file.
+  tournament                    Play a round robin and rate the bots.
+      -p, --players <BOTS>      The bots separated by commas, at least 2.
+      -r, --rounds <N>          The number of rounds of 18 games, default 1.
+      -s, --seed <SEED>         The seed of the tournament, random if not given.
+  duplicate                     Compare bots over duplicate deals.
+      -p, --players <BOTS>      The bot in each seat separated by commas, 3 to 6 bots.
+      -d, --deals <N>           The number of deals, default 10.
+      -s, --seed <SEED>         The seed of the deals, random if not given.
+  head-to-head                  Test whether bot A is stronger than bot B.
+      --a <BOT>                 Bot A, the bot that might be stronger.
+      --b <BOT>                 Bot B, the bot to compare it to.
+      -f, --field <BOT>         The bot both bots play against, default AutomatosV1.
+      -p, --players <N>         The number of players at the table, 3 to 6, default 3.
+      -m, --margin <MARGIN>     The margin A must beat B by, in points such as 5 or a win rate such as 55%.
+      -s, --seed <SEED>         The seed of the deals, random if not given.
+  analyze <RECORD>              Compare every turn of a recorded game with what a bot would play.
+      -b, --bot <BOT>           The bot to compare with, default AutomatosV1.
+  replay <RECORD>               Play a recorded game again and check it ends the same way.
+  help                          Print this message.";
+
+/// A command given on the command line.
+/// # Variants
+/// * `Interactive` - No command, the interactive terminal interface is started.
+/// * `Play` - A game set up by answering prompts, or the unfinished physical game if `resume` is set.
+/// * `Simulate` - A batch of simulated games, with the file the results are written to.
+/// * `Tournament` - A round robin between bots.
+/// * `Duplicate` - A match between bots over duplicate deals.
+/// * `HeadToHead` - A test of whether bot A is stronger than bot B.
+/// * `Analyze` - A recorded game compared with a bot.
+/// * `Replay` - A recorded game played again to check it.
+/// * `Help` - The usage is printed.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Interactive,
+    Play {resume: bool},
+    Simulate {players: Vec<String>, games: usize, seed: u64, threads: usize, output: Option<PathBuf>},
+    Tournament {players: Vec<String>, rounds: usize, seed: u64},
+    Duplicate {players: Vec<String>, deals: usize, seed: u64},
+    HeadToHead {a: String, b: String, field: String, players: usize, margin: Margin, seed: u64},
+    Analyze {record: PathBuf, bot: String},
+    Replay {record: PathBuf},
+    Help,
+}
+
+impl Command {
+    /// Reads a command from the arguments the program was started with.
+    /// # Arguments
+    /// * `args` - A `&[String]` containing the arguments, without the name of the program.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The command is not known.
+    /// * A flag is not known for the command, or is missing its value.
+    /// * A number or seed is not valid.
+    /// * A required flag or record is missing.
+    /// # Returns
+    /// A `Result<Command, String>` containing the command.
+    pub fn parse(args: &[String]) -> Result<Self, String> {
+        let (command, rest) = match args.split_first() {
+            Some((command, rest)) => (command.as_str(), rest),
+            None => return Ok(Command::Interactive),
+        };
+        let flags = Flags::parse(rest)?;
+        let command = match command {
+            "play" => {
+                flags.allow(&["resume"])?;
+                Command::Play {resume: flags.switch("resume")?}
+            }
+            "simulate" => {
+                flags.allow(&["players", "games", "seed", "threads", "output"])?;
+                Command::Simulate {
+                    players: flags.players()?,
+                    games: flags.number("games", 1)?,
+                    seed: flags.seed()?,
+                    threads: flags.number("threads", 0)?,
+                    output: flags.value("output").map(PathBuf::from),
+                }
+            }
+            "tournament" => {
+                flags.allow(&["players", "rounds", "seed"])?;
+                Command::Tournament {players: flags.players()?, rounds: flags.number("rounds", 1)?, seed: flags.seed()?}
+            }
+            "duplicate" => {
+                flags.allow(&["players", "deals", "seed"])?;
+                Command::Duplicate {players: flags.players()?, deals: flags.number("deals", 10)?, seed: flags.seed()?}
+            }
+            "head-to-head" => {
+                flags.allow(&["a", "b", "field", "players", "margin", "seed"])?;
+                Command::HeadToHead {
+                    a: flags.required("a")?.to_string(),
+                    b: flags.required("b")?.to_string(),
+                    field: flags.value("field").unwrap_or(DEFAULT_FIELD_BOT).to_string(),
+                    players: flags.number("players", MIN_PLAYERS)?,
+                    margin: flags.margin()?,
+                    seed: flags.seed()?,
+                }
+            }
+            "analyze" | "analyse" => {
+                flags.allow(&["bot"])?;
+                Command::Analyze {
+                    record: flags.record()?,
+                    bot: flags.value("bot").unwrap_or(DEFAULT_ANALYSIS_BOT).to_string(),
+                }
+            }
+            "replay" => {
+                flags.allow(&[])?;
+                Command::Replay {record: flags.record()?}
+            }
+            "help" | "-h" | "--help" => Command::Help,
+            command => return Err(format!("Unknown command: {command}")),
+        };
+        if !matches!(command, Command::Analyze {..} | Command::Replay {..} | Command::Help) && !flags.positional.is_empty() {
+            return Err(format!("Unexpected argument: {}", flags.positional[0]));
+        }
+        Ok(command)
+    }
+
+    /// Runs a command that does not need any prompts.
+    /// `Interactive` and `Play` are run by the binary, which owns the prompts, and do nothing here.
+    /// # Errors
+    /// This function returns an `Err(String)` if a bot cannot be created, a game fails,
+    /// or a record or output file cannot be read or written.
+    pub fn run(self) -> Result<(), String> {
+        match self {
+            Command::Interactive | Command::Play {..} => Ok(()),
+            Command::Simulate {players, games, seed, threads, output} => {
+                let report = run_batch(&players, games, seed, threads)?;
+                report.display();
+                println!("Seed {seed}.");
+                if let Some(output) = output {
+                    fs::write(&output, report.to_json(seed))
+                        .map_err(|e| format!("Failed to write {}: {e}", output.display()))?;
+                    println!("Wrote the results to {}.", output.display());
+                }
+                Ok(())
+            }
+            Command::Tournament {players, rounds, seed} => run_tournament(players, rounds, seed),
+            Command::Duplicate {players, deals, seed} => run_duplicate(players, deals, seed),
+            Command::HeadToHead {a, b, field, players, margin, seed} => {
+                run_head_to_head(a, b, field, players, margin, seed)
+            }
+            Command::Analyze {record, bot} => run_analysis(&record, &bot),
+            Command::Replay {record} => run_replay(&record),
+            Command::Help => {
+                println!("{USAGE}");
+                Ok(())
+            }
+        }
+    }
+}
+
+/// The flags and other arguments given after a command.
+/// # Fields
+/// * `named` - A `Vec<(String, String)>` containing the long name and value of each flag.
+/// * `positional` - A `Vec<String>` containing the arguments that are not flags.
+struct Flags {
+    named: Vec<(String, String)>,
+    positional: Vec<String>,
+}
+
+impl Flags {
+    /// Helper function for `Command::parse`.
+    /// Splits the arguments into flags and other arguments.
+    /// Flags are written `--name value`, `--name=value` or `-n value`, and switches are written `--name` alone.
+    fn parse(args: &[String]) -> Result<Self, String> {
+        let mut named: Vec<(String, String)> = Vec::new();
+        let mut positional: Vec<String> = Vec::new();
+        let mut args = args.iter();
+        while let Some(arg) = args.next() {
+            let name = match (arg.strip_prefix("--"), arg.strip_prefix('-')) {
+                (Some(name), _) => name,
+                (None, Some(short)) if short.len() == 1 => long_name(short)?,
+                _ => {
+                    positional.push(arg.clone());
+                    continue;
+                }
+            };
+            let (name, value) = match name.split_once('=') {
+                Some((name, value)) => (name.to_string(), value.to_string()),
+                None if SWITCHES.contains(&name) => (name.to_string(), "true".to_string()),
+                None => {
+                    let value = args.next().ok_or(format!("--{name} needs a value."))?;
+                    (name.to_string(), value.clone())
+                }
+            };
+            named.push((name, value));
+        }
+        Ok(Flags {named, positional})
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Checks that every flag given is one the command takes.
+    fn allow(&self, names: &[&str]) -> Result<(), String> {
+        match self.named.iter().find(|(name, _)| !names.contains(&name.as_str())) {
+            Some((name, _)) => Err(format!("Unknown flag: --{name}")),
+            None => Ok(()),
+        }
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets the value of a flag, the last one if it was given more than once.
+    fn value(&self, name: &str) -> Option<&str> {
+        self.named.iter().rev().find(|(flag, _)| flag == name).map(|(_, value)| value.as_str())
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets whether a switch was given.
+    fn switch(&self, name: &str) -> Result<bool, String> {
+        match self.value(name) {
+            None => Ok(false),
+            Some(value) => value.parse::<bool>().map_err(|_| format!("--{name} must be true or false: {value}")),
+        }
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets the value of a flag that has to be given.
+    fn required(&self, name: &str) -> Result<&str, String> {
+        self.value(name).ok_or(format!("--{name} is required."))
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets the bots given with `--players`.
+    fn players(&self) -> Result<Vec<String>, String> {
+        self.value("players")
+            .map(split_specs)
+            .filter(|players| !players.is_empty())
+            .ok_or("--players is required.".to_string())
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets a positive number, or the default if the flag was not given.
+    fn number(&self, name: &str, default: usize) -> Result<usize, String> {
+        match self.value(name) {
+            Some(value) => match value.parse::<usize>() {
+                Ok(number) if number > 0 => Ok(number),
+                _ => Err(format!("--{name} must be a whole number above 0: {value}")),
+            },
+            None => Ok(default),
+        }
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets the margin given with `--margin`.
+    fn margin(&self) -> Result<Margin, String> {
+        let value = self.required("margin")?;
+        parse_margin(value).ok_or(format!("--margin must be a number of points such as 5 or a win rate such as 55%: {value}"))
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets the seed, or a random seed if the flag was not given.
+    fn seed(&self) -> Result<u64, String> {
+        match self.value("seed") {
+            Some(value) => value.parse::<u64>().map_err(|_| format!("--seed must be a whole number: {value}")),
+            None => Ok(random_seed()),
+        }
+    }
+
+    /// Helper function for `Command::parse`.
+    /// Gets the path of the record given after the command.
+    fn record(&self) -> Result<PathBuf, String> {
+        match self.positional.as_slice() {
+            [record] => Ok(PathBuf::from(record)),
+            [] => Err("The path of a game record is required.".to_string()),
+            [_, extra, ..] => Err(format!("Unexpected argument: {extra}")),
+        }
+    }
+}
+
+/// Helper function for `Flags::parse`.
+/// Gets the long name of a short flag.
+fn long_name(short: &str) -> Result<&'static str, String> {
+    match short {
+        "p" => Ok("players"),
+        "n" => Ok("games"),
+        "s" => Ok("seed"),
+        "t" => Ok("threads"),
+        "o" => Ok("output"),
+        "r" => Ok("rounds"),
+        "d" => Ok("deals"),
+        "b" => Ok("bot"),
+        "f" => Ok("field"),
+        "m" => Ok("margin"),
+        short => Err(format!("Unknown flag: -{short}")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper function for the tests.
+    /// Parses a command line written as one string.
+    fn parse(line: &str) -> Result<Command, String> {
+        Command::parse(&line.split_whitespace().map(String::from).collect::<Vec<String>>())
+    }
+
+    #[test]
+    fn commands_are_read_with_their_flags() {
+        assert_eq!(parse(""), Ok(Command::Interactive));
+        assert_eq!(parse("play"), Ok(Command::Play {resume: false}));
+        assert_eq!(parse("play --resume"), Ok(Command::Play {resume: true}));
+        assert_eq!(
+            parse("simulate -p AutomatosV1,Randy,Randy --games=20 -s 42 -o out.json"),
+            Ok(Command::Simulate {
+                players: vec!["AutomatosV1".to_string(), "Randy".to_string(), "Randy".to_string()],
+                games: 20,
+                seed: 42,
+                threads: 0,
+                output: Some(PathBuf::from("out.json")),
+            }),
+        );
+        assert_eq!(
+            parse("head-to-head --a AutomatosV2 --b AutomatosV1 -p 4 -m 2.5 --seed 3"),
+            Ok(Command::HeadToHead {
+                a: "AutomatosV2".to_string(),
+                b: "AutomatosV1".to_string(),
+                field: DEFAULT_FIELD_BOT.to_string(),
+                players: 4,
+                margin: Margin::Points (2.5),
+                seed: 3,
+            }),
+        );
+        assert_eq!(
+            parse("analyse records/game.txt"),
+            Ok(Command::Analyze {record: PathBuf::from("records/game.txt"), bot: DEFAULT_ANALYSIS_BOT.to_string()}),
+        );
+    }
+
+    #[test]
+    fn mistakes_in_the_arguments_are_errors() {
+        assert_eq!(parse("simulate --games 5"), Err("--players is required.".to_string()));
+        assert_eq!(parse("simulate -p Randy --games 0"), Err("--games must be a whole number above 0: 0".to_string()));
+        assert_eq!(parse("tournament -p Randy,Randy --deals 5"), Err("Unknown flag: --deals".to_string()));
+        assert_eq!(parse("duplicate -p Randy --seed"), Err("--seed needs a value.".to_string()));
+        assert_eq!(parse("head-to-head --a Randy --margin 5"), Err("--b is required.".to_string()));
+        assert!(parse("head-to-head --a Randy --b Randy --margin lots").is_err());
+        assert_eq!(parse("replay"), Err("The path of a game record is required.".to_string()));
+        assert_eq!(parse("play extra"), Err("Unexpected argument: extra".to_string()));
+        assert_eq!(parse("simulate -x 1"), Err("Unknown flag: -x".to_string()));
+        assert_eq!(parse("deal"), Err("Unknown command: deal".to_string()));
+    }
+}
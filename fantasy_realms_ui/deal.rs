@@ -0,0 +1,90 @@
+//! # Deal
+//! The random parts of a simulated game, decided up front from a single seed.
+//! The deck is shuffled once, the starting hands are dealt from the top of it,
+//! and every draw from the deck takes the next card, so the same seed deals the same game.
+//! Each bot is also given its own seed for the game, derived from the same seed.
+
+use std::collections::VecDeque;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{thread_rng, Rng, SeedableRng};
+use fantasy_realms_unofficial_api::card_collection::CardCollection;
+use fantasy_realms_unofficial_api::deck::Card;
+use fantasy_realms_unofficial_api::hand::Hand;
+
+/// The cards of a seeded game.
+/// # Fields
+/// * `seed` - A `u64` representing the seed the game was dealt from.
+/// * `hands` - A `Vec<Hand>` containing each players starting hand by seat.
+/// * `deck` - A `VecDeque<Card>` containing the cards left in the deck in the order they will be drawn.
+/// * `bot_seeds` - A `Vec<u64>` containing the seed given to the bot in each seat.
+pub struct Deal {
+    pub seed: u64,
+    pub hands: Vec<Hand>,
+    deck: VecDeque<Card>,
+    bot_seeds: Vec<u64>,
+}
+
+impl Deal {
+    /// Shuffles the deck and deals a starting hand to each player.
+    /// # Arguments
+    /// * `seed` - A `u64` representing the seed of the game.
+    /// * `players` - A `usize` representing the number of players.
+    /// # Returns
+    /// A `Deal` that is the same every time it is created with the same arguments.
+    pub fn new(seed: u64, players: usize) -> Self {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut deck: Vec<Card> = (!CardCollection::new()).iter().collect();
+        deck.shuffle(&mut rng);
+        let hands: Vec<Hand> = (0..players)
+            .map(|_| {
+                let cards: Vec<Card> = deck.drain(..7).collect();
+                Hand::new(cards.try_into().expect("Failed to convert to array."))
+            })
+            .collect();
+        let bot_seeds: Vec<u64> = (0..players).map(|_| rng.gen()).collect();
+        Deal {seed, hands, deck: deck.into(), bot_seeds}
+    }
+
+    /// Draws the next card from the deck.
+    /// # Returns
+    /// An `Option<Card>` which is `None` if the deck is empty.
+    pub fn draw(&mut self) -> Option<Card> {
+        self.deck.pop_front()
+    }
+
+    /// Gets the seed of the bot in a seat.
+    /// # Arguments
+    /// * `seat` - A `usize` representing the seat of the bot.
+    /// # Returns
+    /// A `u64` representing the bots seed.
+    pub fn bot_seed(&self, seat: usize) -> u64 {
+        self.bot_seeds[seat]
+    }
+}
+
+/// Picks a new seed for a game that was not given one.
+/// # Returns
+/// A random `u64`.
+pub fn random_seed() -> u64 {
+    thread_rng().gen()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_same_seed_deals_the_same_game() {
+        let mut first = Deal::new(42, 4);
+        let mut second = Deal::new(42, 4);
+        assert_eq!(first.hands, second.hands);
+        assert_eq!((0..4).map(|seat| first.bot_seed(seat)).collect::<Vec<u64>>(),
+                   (0..4).map(|seat| second.bot_seed(seat)).collect::<Vec<u64>>());
+        while let Some(card) = first.draw() {
+            assert_eq!(Some(card), second.draw());
+        }
+        assert_eq!(second.draw(), None);
+        assert_ne!(Deal::new(43, 4).hands, first.hands);
+    }
+}
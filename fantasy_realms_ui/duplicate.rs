@@ -0,0 +1,234 @@
+//! # Duplicate
+//! Compares bots the same way duplicate bridge compares players.
+//! Each deal is played once for every seat order of a balanced Latin square,
+//! with the same starting hands and the same deck order in every seat,
+//! so every bot plays every hand and the luck of the deal cancels out.
+//! Rotating the bots would also put every bot in every seat, but each bot would always play
+//! straight after the same opponent and pick up the same player's discards.
+//! The balanced square also puts every bot straight after every other bot equally often,
+//! using as many orders as there are bots, or twice as many for an odd number of bots,
+//! instead of every ordering, which would be 720 games a deal at a table of six.
+//! Bots are scored on each deal against the average of the table, and the deals are
+//! independent, so the differences between bots are measured with far fewer games than a tournament.
+
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use fantasy_realms_ai::{new_bot, validate_bot, FantasyRealmsBot};
+use super::simulated_game::play_simulated_game;
+use super::tournament::{MAX_PLAYERS, MIN_PLAYERS};
+
+/// The number of standard errors either side of a mean covering 95% of the likely means.
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// A match between bots played over duplicate deals.
+/// # Fields
+/// * `specs` - A `Vec<String>` containing the spec of the bot that starts in each seat.
+/// * `deal_scores` - A `Vec<Vec<f64>>` containing, for each deal, the average score of each bot
+///   over every seat it played the deal from.
+pub struct DuplicateMatch {
+    specs: Vec<String>,
+    deal_scores: Vec<Vec<f64>>,
+}
+
+impl DuplicateMatch {
+    /// Creates a match, checking that every bot can be created.
+    /// # Arguments
+    /// * `specs` - A `Vec<String>` containing the spec of each bot, one for each seat.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * There are not between 3 and 6 bots.
+    /// * Any of the specs is not a bot.
+    /// # Returns
+    /// A `Result<DuplicateMatch, String>` containing the match with no deals played.
+    pub fn new(specs: Vec<String>) -> Result<Self, String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&specs.len()) {
+            return Err(format!("A duplicate match needs between {MIN_PLAYERS} and {MAX_PLAYERS} bots."));
+        }
+        for spec in &specs {
+            validate_bot(spec)?;
+        }
+        Ok(DuplicateMatch {specs, deal_scores: Vec::new()})
+    }
+
+    /// Plays a number of deals, each in every seat order from `seat_orders`.
+    /// # Arguments
+    /// * `deals` - A `usize` representing the number of deals to play.
+    /// * `seed` - A `u64` representing the seed the deals are chosen from.
+    /// * `progress` - A `bool` that prints the number of deals played as the match runs.
+    /// # Errors
+    /// This function returns an `Err(String)` if any game fails.
+    pub fn play(&mut self, deals: usize, seed: u64, progress: bool) -> Result<(), String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        for played in 1..=deals {
+            self.play_deal(rng.gen())?;
+            if progress && played % (deals / 10).max(1) == 0 {
+                println!("Played {played} of {deals} deals.");
+            }
+        }
+        Ok(())
+    }
+
+    /// Plays one deal in every seat order from `seat_orders` and records the result.
+    /// # Arguments
+    /// * `seed` - A `u64` representing the seed of the deal, the same in every seat order.
+    /// # Errors
+    /// This function returns an `Err(String)` if any game fails.
+    pub fn play_deal(&mut self, seed: u64) -> Result<(), String> {
+        let players = self.specs.len();
+        let orders = seat_orders(players);
+        let mut scores: Vec<f64> = vec![0.0; players];
+        for seats in &orders {
+            let mut bots: Vec<Box<dyn FantasyRealmsBot>> = seats
+                .iter()
+                .map(|&bot| new_bot(self.specs[bot].clone()))
+                .collect::<Result<Vec<Box<dyn FantasyRealmsBot>>, String>>()?;
+            let result = play_simulated_game(&mut bots, seed, false)?;
+            for (seat, &bot) in seats.iter().enumerate() {
+                scores[bot] += result.scores[seat] as f64 / orders.len() as f64;
+            }
+        }
+        self.deal_scores.push(scores);
+        Ok(())
+    }
+
+    /// Gets how each bot scored against the table on every deal.
+    /// # Returns
+    /// A `Vec<Vec<f64>>` containing, for each bot, its score minus the average score of the table on each deal.
+    fn relative_scores(&self) -> Vec<Vec<f64>> {
+        (0..self.specs.len())
+            .map(|bot| {
+                self.deal_scores
+                    .iter()
+                    .map(|scores| scores[bot] - scores.iter().sum::<f64>() / scores.len() as f64)
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Displays how each bot scored against the table, and the difference between every pair of bots.
+    pub fn display(&self) {
+        let deals = self.deal_scores.len();
+        let width = self.specs.iter().map(|spec| spec.len()).max().unwrap_or(0).max(3);
+        println!("{deals} deals, each played {} times.", seat_orders(self.specs.len()).len());
+        println!("{:<width$} {:>9} {:>12} {:>10}", "Bot", "Avg score", "vs table", "Best deals");
+        for (bot, relative) in self.relative_scores().iter().enumerate() {
+            let average: f64 = self.deal_scores.iter().map(|scores| scores[bot]).sum::<f64>() / deals.max(1) as f64;
+            let (mean, margin) = mean_interval(relative);
+            let best = self.deal_scores
+                .iter()
+                .filter(|scores| scores.iter().all(|&score| score <= scores[bot]))
+                .count();
+            println!(
+                "{:<width$} {:>9.1} {:>12} {:>10}",
+                self.specs[bot],
+                average,
+                format!("{mean:+.1} ±{margin:.1}"),
+                best,
+            );
+        }
+        for a in 0..self.specs.len() {
+            for b in a + 1..self.specs.len() {
+                let differences: Vec<f64> = self.deal_scores.iter().map(|scores| scores[a] - scores[b]).collect();
+                let (mean, margin) = mean_interval(&differences);
+                let verdict = if mean - margin > 0.0 {
+                    format!("{} is stronger", self.specs[a])
+                } else if mean + margin < 0.0 {
+                    format!("{} is stronger", self.specs[b])
+                } else {
+                    "no significant difference".to_string()
+                };
+                println!(
+                    "{} vs {}: {mean:+.1} ±{margin:.1} points per deal, {verdict}.",
+                    self.specs[a],
+                    self.specs[b],
+                );
+            }
+        }
+    }
+}
+
+/// Gets the seat orders every deal is played in, the rows of a balanced Latin square.
+/// Every bot plays from every seat equally often, and straight after every other bot equally often.
+/// The first order seats the bots 0, 1, n-1, 2, n-2 and so on, and each order after it adds one to every bot.
+/// With an odd number of bots this only balances the bots that play straight after each other
+/// once the same orders are also played backwards, so there are twice as many orders.
+/// # Arguments
+/// * `players` - A `usize` representing the number of bots.
+/// # Returns
+/// A `Vec<Vec<usize>>` containing, for each game, the bot in each seat.
+pub fn seat_orders(players: usize) -> Vec<Vec<usize>> {
+    let first: Vec<usize> = (0..players)
+        .map(|seat| match seat % 2 {
+            0 => (players - seat / 2) % players,
+            _ => seat.div_ceil(2),
+        })
+        .collect();
+    let mut orders: Vec<Vec<usize>> = (0..players)
+        .map(|shift| first.iter().map(|bot| (bot + shift) % players).collect())
+        .collect();
+    if players % 2 == 1 {
+        let backwards: Vec<Vec<usize>> = orders.iter().map(|order| order.iter().rev().copied().collect()).collect();
+        orders.extend(backwards);
+    }
+    orders
+}
+
+/// Gets the mean of a sample and the distance either side of it covered by its 95% confidence interval.
+/// # Arguments
+/// * `values` - A `&[f64]` containing independent samples.
+/// # Returns
+/// A `(f64, f64)` containing the mean and the margin, which is infinite for fewer than 2 samples.
+pub fn mean_interval(values: &[f64]) -> (f64, f64) {
+    let count = values.len() as f64;
+    let mean = values.iter().sum::<f64>() / count.max(1.0);
+    if values.len() < 2 {
+        return (mean, f64::INFINITY);
+    }
+    let variance = values.iter().map(|value| (value - mean).powi(2)).sum::<f64>() / (count - 1.0);
+    (mean, CONFIDENCE_Z * (variance / count).sqrt())
+}
+
+/// Runs a duplicate match and displays the results.
+/// # Arguments
+/// * `specs` - A `Vec<String>` containing the spec of each bot.
+/// * `deals` - A `usize` representing the number of deals to play.
+/// * `seed` - A `u64` representing the seed the deals are chosen from.
+/// # Errors
+/// This function returns an `Err(String)` if a bot cannot be created or a game fails.
+pub fn run_duplicate(specs: Vec<String>, deals: usize, seed: u64) -> Result<(), String> {
+    let mut duplicate = DuplicateMatch::new(specs)?;
+    duplicate.play(deals, seed, true)?;
+    duplicate.display();
+    println!("Seed {seed}, enter it when starting a new duplicate match to replay these deals.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn seat_orders_are_balanced() {
+        for players in MIN_PLAYERS..=MAX_PLAYERS {
+            let orders = seat_orders(players);
+            let mut seats = vec![vec![0; players]; players];
+            let mut follows = vec![vec![0; players]; players];
+            for order in &orders {
+                for (seat, &bot) in order.iter().enumerate() {
+                    seats[bot][seat] += 1;
+                }
+                for pair in order.windows(2) {
+                    follows[pair[0]][pair[1]] += 1;
+                }
+            }
+            // A balanced square fills every seat and every pair of neighbours once per square.
+            let count = orders.len() / players;
+            assert!(seats.iter().flatten().all(|&seat_count| seat_count == count));
+            for (a, followers) in follows.iter().enumerate() {
+                for (b, &follow_count) in followers.iter().enumerate() {
+                    assert_eq!(follow_count, if a == b { 0 } else { count });
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,308 @@
+//! # Head to Head
+//! Tests whether bot A is stronger than bot B with a sequential probability ratio test.
+//! Games are played in pairs: A plays a deal against a field of opponents,
+//! then B plays the same deal from the same seat against the same field,
+//! so the difference between the two games is down to A and B and not the cards.
+//! After every pair the test checks if there is enough evidence to accept that
+//! A beats B by at least the margin given, or that it does not, and stops as soon as there is.
+//! Clear differences are decided in a few games while close ones keep playing.
+
+use std::fmt;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use fantasy_realms_ai::{new_bot, validate_bot, FantasyRealmsBot};
+use super::duplicate::mean_interval;
+use super::simulated_game::play_simulated_game;
+use super::tournament::{MAX_PLAYERS, MIN_PLAYERS};
+
+/// The default chance of accepting that A is stronger when it is not, and of missing that it is.
+pub const DEFAULT_ERROR_RATE: f64 = 0.05;
+/// The default most pairs played before the test gives up.
+pub const DEFAULT_MAX_PAIRS: usize = 10_000;
+/// The fewest pairs played before the variance of the points is trusted.
+const MIN_PAIRS: usize = 10;
+
+/// The margin A has to beat B by.
+/// # Variants
+/// * `Points` - A beats B by at least this many points per game on average.
+/// * `WinRate` - A beats B in at least this share of pairs more than half, so 0.05 is 55%.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Margin {
+    Points (f64),
+    WinRate (f64),
+}
+
+impl fmt::Display for Margin {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Margin::Points (points) => write!(f, "{points} points"),
+            Margin::WinRate (rate) => write!(f, "a {:.0}% win rate", 100.0 * (0.5 + rate)),
+        }
+    }
+}
+
+/// The outcome of a test.
+/// # Variants
+/// * `Stronger` - A beats B by at least the margin.
+/// * `NotStronger` - A does not beat B.
+/// * `Inconclusive` - The most pairs were played without deciding.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Verdict {
+    Stronger,
+    NotStronger,
+    Inconclusive,
+}
+
+/// A test between two bots.
+/// # Fields
+/// * `a` - A `String` representing the spec of bot A.
+/// * `b` - A `String` representing the spec of bot B.
+/// * `field` - A `String` representing the spec of the opponents both bots play against.
+/// * `players` - A `usize` representing the number of players at the table.
+/// * `margin` - A `Margin` A has to beat B by.
+/// * `error_rate` - A `f64` representing the chance of each kind of wrong verdict.
+/// * `max_pairs` - A `usize` representing the most pairs played.
+pub struct HeadToHead {
+    pub a: String,
+    pub b: String,
+    pub field: String,
+    pub players: usize,
+    pub margin: Margin,
+    pub error_rate: f64,
+    pub max_pairs: usize,
+}
+
+/// The result of a test.
+/// # Fields
+/// * `verdict` - A `Verdict` representing the outcome of the test.
+/// * `differences` - A `Vec<f64>` containing A's score minus B's score in each pair.
+/// * `log_likelihood_ratio` - A `f64` representing the evidence for A being stronger when the test stopped.
+pub struct TestResult {
+    pub verdict: Verdict,
+    pub differences: Vec<f64>,
+    pub log_likelihood_ratio: f64,
+}
+
+impl TestResult {
+    /// Gets the number of games played, two for each pair.
+    /// # Returns
+    /// A `usize` representing the number of games.
+    pub fn games(&self) -> usize {
+        2 * self.differences.len()
+    }
+
+    /// Counts the pairs A won, lost and tied.
+    /// # Returns
+    /// A `(usize, usize, usize)` containing the wins, losses and ties of A.
+    pub fn record(&self) -> (usize, usize, usize) {
+        let wins = self.differences.iter().filter(|&&difference| difference > 0.0).count();
+        let losses = self.differences.iter().filter(|&&difference| difference < 0.0).count();
+        (wins, losses, self.differences.len() - wins - losses)
+    }
+}
+
+impl HeadToHead {
+    /// Creates a test with the default error rate and most pairs, checking that every bot can be created.
+    /// # Arguments
+    /// * `a` - A `String` representing the spec of bot A.
+    /// * `b` - A `String` representing the spec of bot B.
+    /// * `field` - A `String` representing the spec of the opponents.
+    /// * `players` - A `usize` representing the number of players at the table.
+    /// * `margin` - A `Margin` A has to beat B by.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The number of players is not between 3 and 6.
+    /// * The margin is not positive, or the win rate is not below 50%.
+    /// * Any of the specs is not a bot.
+    /// # Returns
+    /// A `Result<HeadToHead, String>` containing the test.
+    pub fn new(a: String, b: String, field: String, players: usize, margin: Margin) -> Result<Self, String> {
+        if !(MIN_PLAYERS..=MAX_PLAYERS).contains(&players) {
+            return Err(format!("A head to head needs between {MIN_PLAYERS} and {MAX_PLAYERS} players."));
+        }
+        match margin {
+            Margin::Points (points) if points <= 0.0 => return Err("The margin must be positive.".to_string()),
+            Margin::WinRate (rate) if rate <= 0.0 || rate >= 0.5 => {
+                return Err("The win rate must be between 50% and 100%.".to_string());
+            }
+            _ => {}
+        }
+        for spec in [&a, &b, &field] {
+            validate_bot(spec)?;
+        }
+        Ok(HeadToHead {
+            a,
+            b,
+            field,
+            players,
+            margin,
+            error_rate: DEFAULT_ERROR_RATE,
+            max_pairs: DEFAULT_MAX_PAIRS,
+        })
+    }
+
+    /// Plays pairs of games until the test reaches a verdict.
+    /// # Arguments
+    /// * `seed` - A `u64` representing the seed the deals are chosen from.
+    /// * `progress` - A `bool` that prints the state of the test as it runs.
+    /// # Errors
+    /// This function returns an `Err(String)` if any game fails.
+    /// # Returns
+    /// A `Result<TestResult, String>` containing the verdict and every pair played.
+    pub fn run(&self, seed: u64, progress: bool) -> Result<TestResult, String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let lower = (self.error_rate / (1.0 - self.error_rate)).ln();
+        let upper = ((1.0 - self.error_rate) / self.error_rate).ln();
+        let mut differences: Vec<f64> = Vec::new();
+        let mut log_likelihood_ratio: f64 = 0.0;
+        while differences.len() < self.max_pairs {
+            let seat = differences.len() % self.players;
+            let deal_seed: u64 = rng.gen();
+            let a_score = self.play_game(&self.a, seat, deal_seed)?;
+            let b_score = self.play_game(&self.b, seat, deal_seed)?;
+            differences.push((a_score - b_score) as f64);
+            log_likelihood_ratio = self.log_likelihood_ratio(&differences);
+            if progress && differences.len().is_multiple_of(100) {
+                println!("Played {} pairs, log likelihood ratio {log_likelihood_ratio:.2}.", differences.len());
+            }
+            if differences.len() < MIN_PAIRS {
+                continue;
+            }
+            if log_likelihood_ratio >= upper {
+                return Ok(TestResult {verdict: Verdict::Stronger, differences, log_likelihood_ratio});
+            }
+            if log_likelihood_ratio <= lower {
+                return Ok(TestResult {verdict: Verdict::NotStronger, differences, log_likelihood_ratio});
+            }
+        }
+        Ok(TestResult {verdict: Verdict::Inconclusive, differences, log_likelihood_ratio})
+    }
+
+    /// Helper function for `run`.
+    /// Plays a deal with the bot being tested in a seat and the field in every other seat.
+    /// # Returns
+    /// A `Result<i16, String>` containing the score of the bot being tested.
+    fn play_game(&self, spec: &str, seat: usize, seed: u64) -> Result<i16, String> {
+        let mut bots: Vec<Box<dyn FantasyRealmsBot>> = (0..self.players)
+            .map(|i| new_bot(if i == seat { spec.to_string() } else { self.field.clone() }))
+            .collect::<Result<Vec<Box<dyn FantasyRealmsBot>>, String>>()?;
+        Ok(play_simulated_game(&mut bots, seed, false)?.scores[seat])
+    }
+
+    /// Helper function for `run`.
+    /// Gets the log of how much more likely the pairs are if A beats B by the margin than if it does not.
+    /// Points use a normal model with the variance of the pairs so far,
+    /// and win rates use the pairs A won and lost, ignoring ties.
+    fn log_likelihood_ratio(&self, differences: &[f64]) -> f64 {
+        match self.margin {
+            Margin::Points (points) => {
+                let count = differences.len() as f64;
+                let sum: f64 = differences.iter().sum();
+                let mean = sum / count;
+                let variance = differences.iter().map(|difference| (difference - mean).powi(2)).sum::<f64>()
+                    / (count - 1.0).max(1.0);
+                if variance <= 0.0 {
+                    return 0.0;
+                }
+                (points * sum - count * points * points / 2.0) / variance
+            }
+            Margin::WinRate (rate) => {
+                let wins = differences.iter().filter(|&&difference| difference > 0.0).count() as f64;
+                let losses = differences.iter().filter(|&&difference| difference < 0.0).count() as f64;
+                let win_chance = 0.5 + rate;
+                wins * (win_chance / 0.5).ln() + losses * ((1.0 - win_chance) / 0.5).ln()
+            }
+        }
+    }
+
+    /// Displays the result of a test.
+    /// # Arguments
+    /// * `result` - A `&TestResult` returned by `run`.
+    pub fn display(&self, result: &TestResult) {
+        let verdict = match result.verdict {
+            Verdict::Stronger => format!("{} beats {} by at least {}", self.a, self.b, self.margin),
+            Verdict::NotStronger => format!("{} does not beat {} by {}", self.a, self.b, self.margin),
+            Verdict::Inconclusive => format!("No verdict after the most pairs, {}", self.max_pairs),
+        };
+        let confidence = 100.0 * (1.0 - self.error_rate);
+        println!("{verdict}, with {confidence:.0}% confidence.");
+        println!("Played {} games in {} pairs.", result.games(), result.differences.len());
+        let (mean, margin) = mean_interval(&result.differences);
+        let deviation = (result.differences.iter().map(|difference| (difference - mean).powi(2)).sum::<f64>()
+            / (result.differences.len() as f64 - 1.0).max(1.0))
+            .sqrt();
+        let (wins, losses, ties) = result.record();
+        println!("{} scored {mean:+.1} ±{margin:.1} points per game against {}.", self.a, self.b);
+        if deviation > 0.0 {
+            println!("Effect size {:.2} standard deviations.", mean / deviation);
+        }
+        println!(
+            "{} won {wins}, lost {losses} and tied {ties} pairs, a {:.1}% win rate.",
+            self.a,
+            100.0 * wins as f64 / (wins + losses).max(1) as f64,
+        );
+    }
+}
+
+/// Parses a margin, a number of points such as `5` or a win rate such as `55%`.
+/// # Arguments
+/// * `input` - A `&str` containing the margin.
+/// # Returns
+/// An `Option<Margin>` which is `None` if the input is not a margin.
+pub fn parse_margin(input: &str) -> Option<Margin> {
+    let input = input.trim();
+    match input.strip_suffix('%') {
+        Some(rate) => rate.trim().parse::<f64>().ok().map(|rate| Margin::WinRate (rate / 100.0 - 0.5)),
+        None => input.parse::<f64>().ok().map(Margin::Points),
+    }
+}
+
+/// Runs a head to head test and displays the result.
+/// # Arguments
+/// * `a` - A `String` representing the spec of bot A.
+/// * `b` - A `String` representing the spec of bot B.
+/// * `field` - A `String` representing the spec of the opponents.
+/// * `players` - A `usize` representing the number of players at the table.
+/// * `margin` - A `Margin` A has to beat B by.
+/// * `seed` - A `u64` representing the seed the deals are chosen from.
+/// # Errors
+/// This function returns an `Err(String)` if a bot cannot be created or a game fails.
+pub fn run_head_to_head(
+    a: String,
+    b: String,
+    field: String,
+    players: usize,
+    margin: Margin,
+    seed: u64,
+) -> Result<(), String> {
+    let test = HeadToHead::new(a, b, field, players, margin)?;
+    let result = test.run(seed, true)?;
+    test.display(&result);
+    println!("Seed {seed}, enter it when starting a new head to head to replay these deals.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper function for the tests.
+    /// Creates a test between Randy and itself with a margin.
+    fn test(margin: Margin) -> HeadToHead {
+        HeadToHead::new("Randy".to_string(), "Randy".to_string(), "Randy".to_string(), 3, margin).unwrap()
+    }
+
+    #[test]
+    fn points_are_weighed_with_a_normal_model() {
+        // A sum of 30 over 5 pairs with a sample variance of 42.5: (5 * 30 - 5 * 5 * 5 / 2) / 42.5.
+        let ratio = test(Margin::Points (5.0)).log_likelihood_ratio(&[10.0, 0.0, 5.0, 15.0, 0.0]);
+        assert!((ratio - 87.5 / 42.5).abs() < 1e-12);
+        assert_eq!(test(Margin::Points (5.0)).log_likelihood_ratio(&[4.0, 4.0, 4.0]), 0.0);
+    }
+
+    #[test]
+    fn win_rates_count_wins_and_losses_but_not_ties() {
+        let ratio = test(Margin::WinRate (0.1)).log_likelihood_ratio(&[3.0, -1.0, 2.0, 0.0, 4.0]);
+        assert!((ratio - (3.0 * 1.2f64.ln() + 0.8f64.ln())).abs() < 1e-12);
+    }
+}
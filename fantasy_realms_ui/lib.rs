@@ -1,14 +1,52 @@
 pub mod terminal_interface;
 pub mod physical_game;
 pub mod simulated_game;
+pub mod deal;
+pub mod tournament;
+pub mod duplicate;
+pub mod head_to_head;
+pub mod batch;
+pub mod record;
+pub mod replay_viewer;
+pub mod cli;
 
 use fantasy_realms_ai::FantasyRealmsBot;
 
 /// Represents the type of a player.
 /// # Variants
 /// * 'Human' - Contains a `String` representing a name of a human player.
-/// * `Bot` - Contains a `Box<dyn FantasyRealmsBot>` a Fantasy Realms bot.
+/// * `Bot` - Contains a `Box<dyn FantasyRealmsBot>` a Fantasy Realms bot,
+///   and a `String` representing the spec it was created from.
 pub enum PlayerType {
     Human (String),
-    Bot (Box<dyn FantasyRealmsBot>),
+    Bot (Box<dyn FantasyRealmsBot>, String),
+}
+
+/// A fix for a mistake in a physical game, entered in place of a card.
+/// # Variants
+/// * `Undo` - Contains a `usize` representing the number of turns to take back.
+/// * `Correct` - Contains a `usize` representing the number of the turn to enter again, 1 is the first turn.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Correction {
+    Undo (usize),
+    Correct (usize),
+}
+
+/// Represents what the user wants to run.
+/// # Variants
+/// * `Game` - A single game with humans or bots.
+/// * `Tournament` - A tournament between bots.
+/// * `Duplicate` - A match between bots over duplicate deals.
+/// * `HeadToHead` - A test of whether one bot is stronger than another.
+/// * `Batch` - Many simulated games run at once without printing them.
+/// * `Replay` - A recorded game played again to check it.
+/// * `View` - A recorded game stepped through turn by turn.
+pub enum Session {
+    Game,
+    Tournament,
+    Duplicate,
+    HeadToHead,
+    Batch,
+    Replay,
+    View,
 }
\ No newline at end of file
@@ -1,7 +1,17 @@
+use std::io::IsTerminal;
+use std::process::ExitCode;
 use fantasy_realms_ui::terminal_interface::*;
-use fantasy_realms_ui::PlayerType;
-use fantasy_realms_ui::physical_game::run_physical_game;
+use fantasy_realms_ui::{PlayerType, Session};
+use fantasy_realms_ui::physical_game::{archive_autosave, autosave_path, resume_physical_game, run_physical_game};
 use fantasy_realms_ui::simulated_game::run_simulated_game;
+use fantasy_realms_ui::tournament::run_tournament;
+use fantasy_realms_ui::duplicate::run_duplicate;
+use fantasy_realms_ui::head_to_head::run_head_to_head;
+use fantasy_realms_ui::batch::run_quiet_batch;
+use fantasy_realms_ui::record::run_replay;
+use fantasy_realms_ui::replay_viewer::run_replay_viewer;
+use fantasy_realms_ui::cli::{Command, USAGE};
+use fantasy_realms_ai::plugin::{load_plugins, DEFAULT_PLUGIN_DIRECTORY};
 
 /// Gets user input to create a new game.
 /// Then runs the type of game based on input recieved from the user.
@@ -19,18 +29,200 @@ fn new_game() {
             eprintln!("Invalid game state: {}", e);
         }
     } else {
+        println!("Enter a seed to replay a game, or nothing for a random seed:");
+        let seed: u64 = get_seed_input();
         println!("running simulated game.");
-        if let Err(e) = run_simulated_game(player_types) {
+        if let Err(e) = run_simulated_game(player_types, seed) {
             eprintln!("Invalid game state: {}", e);
         }
     }
 }
 
-fn main() {
+/// Gets user input to create a new tournament.
+/// Then plays the tournament and displays the ratings of the bots.
+fn new_tournament() {
+    print_bot_list();
+    println!("Enter the bots to play separated by commas, such as Randy, AutomatosV1, AutomatosV2(depth=2):");
+    let specs: Vec<String> = get_specs_input(2, usize::MAX);
+    println!("Enter the number of rounds, each round is 18 games, (1 to 255):");
+    let rounds: u8 = get_int_input(1, 255);
+    println!("Enter a seed to replay a tournament, or nothing for a random seed:");
+    let seed: u64 = get_seed_input();
+    if let Err(e) = run_tournament(specs, rounds as usize, seed) {
+        eprintln!("Tournament failed: {}", e);
+    }
+}
+
+/// Gets user input to create a new duplicate match.
+/// Then plays every deal in every rotation of the bots and compares them deal by deal.
+fn new_duplicate() {
+    print_bot_list();
+    println!("Enter the bots to play separated by commas, one for each seat, (3 to 6):");
+    let specs: Vec<String> = get_specs_input(3, 6);
+    println!("Enter the number of deals, (1 to 255):");
+    let deals: u8 = get_int_input(1, 255);
+    println!("Enter a seed to replay the deals, or nothing for a random seed:");
+    let seed: u64 = get_seed_input();
+    if let Err(e) = run_duplicate(specs, deals as usize, seed) {
+        eprintln!("Duplicate match failed: {}", e);
+    }
+}
+
+/// Gets user input to create a new head to head test.
+/// Then plays pairs of games until it is known whether bot A is stronger than bot B.
+fn new_head_to_head() {
+    print_bot_list();
+    println!("Enter bot A, the bot that might be stronger:");
+    let a: String = get_spec_input();
+    println!("Enter bot B, the bot to compare it to:");
+    let b: String = get_spec_input();
+    println!("Enter the bot both bots play against:");
+    let field: String = get_spec_input();
+    println!("Enter the number of players, (3 to 6):");
+    let players: u8 = get_int_input(3, 6);
+    println!("Enter the margin A must beat B by, in points such as 5 or as a win rate such as 55%:");
+    let margin = get_margin_input();
+    println!("Enter a seed to replay the deals, or nothing for a random seed:");
+    let seed: u64 = get_seed_input();
+    if let Err(e) = run_head_to_head(a, b, field, players as usize, margin, seed) {
+        eprintln!("Head to head failed: {}", e);
+    }
+}
+
+/// Gets user input to create a new batch of games.
+/// Then plays the games on every core without printing them and displays the totals.
+fn new_batch() {
+    print_bot_list();
+    println!("Enter the bots to play separated by commas, one for each seat, (3 to 6):");
+    let specs: Vec<String> = get_specs_input(3, 6);
+    println!("Enter the number of games, (1 to 10000):");
+    let games: u16 = get_games_input();
+    println!("Enter a seed to replay the games, or nothing for a random seed:");
+    let seed: u64 = get_seed_input();
+    if let Err(e) = run_quiet_batch(specs, games as usize, seed) {
+        eprintln!("Batch failed: {}", e);
+    }
+}
+
+/// Gets the path of a game record from the user.
+/// Then replays the game and checks that it ends the way it was recorded.
+fn new_replay() {
+    println!("Enter the path of the game record, such as records/simulated-1700000000000.txt:");
+    let path: String = get_string_input();
+    if let Err(e) = run_replay(std::path::Path::new(path.trim())) {
+        eprintln!("Replay failed: {}", e);
+    }
+}
+
+/// Gets the path of a game record from the user.
+/// Then steps through the game turn by turn.
+fn new_viewer() {
+    println!("Enter the path of the game record, such as records/simulated-1700000000000.txt:");
+    let path: String = get_string_input();
+    if let Err(e) = run_replay_viewer(std::path::Path::new(path.trim())) {
+        eprintln!("Viewer failed: {}", e);
+    }
+}
+
+/// Offers to resume a physical game that did not finish, and moves it out of the way if it is not resumed,
+/// so that the next physical game cannot overwrite it.
+/// # Returns
+/// A `bool` which is `true` if a game was resumed.
+fn offer_autosave() -> bool {
+    let autosave = autosave_path();
+    if !autosave.exists() {
+        return false;
+    }
+    println!("An unfinished physical game was found, would you like to resume it, (yes, no):");
+    if get_new_game_input() {
+        if let Err(e) = resume_physical_game(&autosave) {
+            eprintln!("Invalid game state: {}", e);
+        }
+        return true;
+    }
+    match archive_autosave(&autosave) {
+        Ok(path) => println!("The unfinished game was moved to {}.", path.display()),
+        Err(e) => eprintln!("{e}"),
+    }
+    false
+}
+
+/// Runs the `play` command.
+/// Resumes the unfinished physical game if asked to, otherwise offers to resume it before starting a new game.
+/// # Arguments
+/// * `resume` - A `bool` that resumes the unfinished game without asking.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * `resume` is set and there is no unfinished game, or it cannot be resumed.
+/// * There is an unfinished game, `resume` is not set and there is no terminal to ask on.
+fn play(resume: bool) -> Result<(), String> {
+    let autosave = autosave_path();
+    if resume {
+        if !autosave.exists() {
+            return Err(format!("There is no unfinished game at {}.", autosave.display()));
+        }
+        return resume_physical_game(&autosave);
+    }
+    if autosave.exists() && !std::io::stdin().is_terminal() {
+        return Err(format!(
+            "An unfinished physical game was found at {}, run play --resume to resume it or move it away first.",
+            autosave.display(),
+        ));
+    }
+    if !offer_autosave() {
+        new_game();
+    }
+    Ok(())
+}
+
+/// Runs the interactive terminal interface until the user stops starting new sessions.
+/// Offers to resume a physical game that did not finish first.
+fn interactive() {
+    offer_autosave();
     let mut running: bool = true;
     while running {
-        new_game();
+        println!("Enter the type of session, (Game, Tournament, Duplicate, Test, Batch, Replay, View):");
+        match get_session_input() {
+            Session::Game => new_game(),
+            Session::Tournament => new_tournament(),
+            Session::Duplicate => new_duplicate(),
+            Session::HeadToHead => new_head_to_head(),
+            Session::Batch => new_batch(),
+            Session::Replay => new_replay(),
+            Session::View => new_viewer(),
+        }
         println!("Would you like to start a new game, (yes, no):");
         running = get_new_game_input();
     }
+}
+
+fn main() -> ExitCode {
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let command = match Command::parse(&args) {
+        Ok(command) => command,
+        Err(e) => {
+            eprintln!("{e}");
+            eprintln!("{USAGE}");
+            return ExitCode::from(2);
+        }
+    };
+    for error in load_plugins(std::path::Path::new(DEFAULT_PLUGIN_DIRECTORY)) {
+        eprintln!("{error}");
+    }
+    match command {
+        Command::Interactive => interactive(),
+        Command::Play {resume} => {
+            if let Err(e) = play(resume) {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+        command => {
+            if let Err(e) = command.run() {
+                eprintln!("{e}");
+                return ExitCode::FAILURE;
+            }
+        }
+    }
+    ExitCode::SUCCESS
 }
\ No newline at end of file
@@ -1,12 +1,452 @@
-use fantasy_realms_unofficial_api::{DrawCard, Player, PartialGame, PartialGamePlayer, PartialPlayer, PartialGameTurn};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use fantasy_realms_unofficial_api::{DrawCard, Player, PartialGame, PartialGamePlayer, PartialPlayer, PartialGameTurn, PartialTurn};
 use fantasy_realms_unofficial_api::hand::{Hand, Turn};
 use fantasy_realms_unofficial_api::deck::Card;
 use fantasy_realms_unofficial_api::card_collection::{CardCollection};
-use fantasy_realms_ai::FantasyRealmsBot;
+use fantasy_realms_ai::{new_bot, BotSpec, FantasyRealmsBot, GameView, ObservedTurn};
+use fantasy_realms_ai::registry::{bot_speed, Speed};
 use super::terminal_interface::*;
-use super::PlayerType;
+use super::{Correction, PlayerType};
+use super::deal::{random_seed, Deal};
+use super::record::{save_record, GameKind, GameRecord, RecordedPlayer, RecordedTurn, DEFAULT_RECORD_DIRECTORY, RECORD_VERSION};
+
+/// The file a physical game is saved to after every turn, in the default record directory.
+pub const AUTOSAVE_FILE: &str = "autosave.txt";
+
+/// A physical game being played.
+/// Every turn is kept so that the game can be saved after every turn and rebuilt from the save,
+/// and the game before every turn is kept so that turns entered by mistake can be taken back or corrected
+/// without asking any bot to decide its earlier turns again.
+/// # Fields
+/// * `player_types` - A `Vec<PlayerType>` containing each player by seat.
+/// * `game` - A `PartialGame` representing the game as known to the bots.
+/// * `seed` - A `u64` representing the seed the bots were given.
+/// * `starting_hands` - A `Vec<Option<Hand>>` containing the starting hand of each bot by seat, `None` for humans.
+/// * `turns` - A `Vec<RecordedTurn>` containing every turn played so far.
+/// * `snapshots` - A `Vec<(PartialGame, usize)>` containing the game before each turn and the number of turns played before it.
+pub struct PhysicalGame {
+    player_types: Vec<PlayerType>,
+    game: PartialGame,
+    seed: u64,
+    starting_hands: Vec<Option<Hand>>,
+    turns: Vec<RecordedTurn>,
+    snapshots: Vec<(PartialGame, usize)>,
+}
+
+impl PhysicalGame {
+    /// Starts a new game, asking for the starting hand of every bot.
+    /// # Arguments
+    /// * `player_types` - A `Vec<PlayerType>` representing a all players in the game.
+    /// # Returns
+    /// A `PhysicalGame` before the first turn.
+    pub fn new(player_types: Vec<PlayerType>) -> Self {
+        let players = create_players(&player_types);
+        PhysicalGame::start(player_types, players, random_seed())
+    }
+
+    /// Rebuilds a game from the record of an unfinished game.
+    /// The bots are created again from their specs, given the same seed and starting hand,
+    /// and shown every turn in order. Every bot decides each of its recorded turns again before it is played,
+    /// so its random numbers and anything it kept from its own searches are as they were when the game was saved,
+    /// and a seeded bot goes on to make the same moves as if the game had not been interrupted.
+    /// # Arguments
+    /// * `record` - A `&GameRecord` of the unfinished game.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The record is not of a physical game, or the game has finished.
+    /// * A bot cannot be created or its starting hand is missing.
+    /// * A turn is not valid.
+    /// # Returns
+    /// A `Result<PhysicalGame, String>` containing the game after the last turn recorded.
+    pub fn resume(record: &GameRecord) -> Result<Self, String> {
+        if record.kind != GameKind::Physical || record.is_finished() {
+            return Err("Only a physical game that has not finished can be resumed.".to_string());
+        }
+        let mut player_types: Vec<PlayerType> = Vec::new();
+        let mut players: Vec<PartialGamePlayer> = Vec::new();
+        for (player, hand) in record.players.iter().zip(&record.starting_hands) {
+            match (player, hand) {
+                (RecordedPlayer::Human (name), _) => {
+                    player_types.push(PlayerType::Human (name.clone()));
+                    players.push(PartialGamePlayer::Human(PartialPlayer::new(name.clone())));
+                }
+                (RecordedPlayer::Bot {name, spec}, Some(hand)) => {
+                    player_types.push(PlayerType::Bot (new_bot(spec.clone())?, spec.clone()));
+                    players.push(PartialGamePlayer::Bot(Player::new(name.clone(), *hand)));
+                }
+                (RecordedPlayer::Bot {name, ..}, None) => {
+                    return Err(format!("The starting hand of {name} is missing."));
+                }
+            }
+        }
+        let mut physical_game = PhysicalGame::start(player_types, players, record.seed.unwrap_or_else(random_seed));
+        for turn in &record.turns {
+            physical_game.decide_again(*turn);
+            physical_game.replay_turn(*turn)?;
+        }
+        Ok(physical_game)
+    }
+
+    /// Helper function for `resume`.
+    /// Asks the bot whose turn it is to decide a recorded turn again before it is played, and ignores its answers.
+    /// Nothing is asked if the turn is out of order, is not a bot's, or the card drawn is missing.
+    /// # Arguments
+    /// * `turn` - A `RecordedTurn` representing the turn about to be played.
+    fn decide_again(&mut self, turn: RecordedTurn) {
+        if self.game.over || turn.seat != self.game.current_turn {
+            return;
+        }
+        let card = match turn.draw {
+            DrawCard::Discard (card) => Some(card),
+            DrawCard::Deck => turn.card,
+        };
+        let history = self.history();
+        if let (PlayerType::Bot (bot, _), Some(card)) = (&mut self.player_types[turn.seat], card) {
+            let view = get_game_view(&self.game, &history);
+            bot.generate_draw(&view);
+            bot.generate_discard(&view, &card);
+        }
+    }
+
+    /// Helper function for `new` and `resume`.
+    /// Creates the game and gives every bot its seed and starting hand.
+    fn start(player_types: Vec<PlayerType>, players: Vec<PartialGamePlayer>, seed: u64) -> Self {
+        let game = PartialGame::new(players).unwrap();
+        let starting_hands: Vec<Option<Hand>> = game.players
+            .iter()
+            .map(|player| match player {
+                PartialGamePlayer::Human (_) => None,
+                PartialGamePlayer::Bot (player) => Some(player.hand),
+            })
+            .collect();
+        let mut physical_game = PhysicalGame {player_types, game, seed, starting_hands, turns: Vec::new(), snapshots: Vec::new()};
+        physical_game.restart_bots();
+        physical_game
+    }
+
+    /// Helper function for `start` and `restore`.
+    /// Starts every bot again with its seed and starting hand, and shows it every turn kept.
+    /// No bot is asked to decide a turn, so this is quick even for slow or external bots.
+    fn restart_bots(&mut self) {
+        let player_count = self.player_types.len();
+        let deal = Deal::new(self.seed, player_count);
+        for (seat, player_type) in self.player_types.iter_mut().enumerate() {
+            if let (PlayerType::Bot (bot, _), Some(hand)) = (player_type, &self.starting_hands[seat]) {
+                bot.seed(deal.bot_seed(seat));
+                bot.on_game_start(seat, player_count, hand);
+                for turn in &self.turns {
+                    bot.observe_turn(&turn.observed());
+                }
+            }
+        }
+    }
+
+    /// Plays the game to the end, saving it after every turn.
+    /// When the game ends the final hands of the humans are entered,
+    /// the record of the game is saved and the autosave is removed.
+    /// Instead of a card, `undo` followed by a number of turns takes those turns back,
+    /// and `correct` followed by the number of a turn enters that turn again.
+    /// # Errors
+    /// This function returns an `Err(String)` if a turn is not valid.
+    /// The game saved after the last valid turn can then be resumed.
+    pub fn play(&mut self) -> Result<(), String> {
+        println!("To fix a mistake, enter undo, undo followed by a number of turns, or correct followed by a turn, instead of a card.");
+        self.autosave();
+        while !self.game.over {
+            let history = self.history();
+            let before = self.game.clone();
+            let turn = match &mut self.player_types[self.game.current_turn] {
+                PlayerType::Human (name) => handle_human_turn(name, &mut self.game),
+                PlayerType::Bot (bot, _) => handle_bot_turn(&mut**bot, &mut self.game, &history),
+            }.map_err(|e| format!("{e} The game can be resumed from the last turn played."))?;
+            match turn {
+                Ok(turn) => self.observe_turn(turn, before),
+                Err(correction) => {
+                    if let Err(e) = self.apply_correction(correction) {
+                        println!("{e}");
+                    }
+                }
+            }
+            self.autosave();
+        }
+        let hands = get_final_hands(&self.game);
+        let scores: Vec<i16> = hands.iter().map(|hand| hand.score(&self.game.discard_pile)).collect();
+        for player_type in self.player_types.iter_mut() {
+            if let PlayerType::Bot (bot, _) = player_type {
+                bot.on_game_end(&hands, &scores);
+            }
+        }
+        display_final_scores(&self.game, &scores);
+        save_record(&self.record(hands, scores));
+        let _ = fs::remove_file(autosave_path());
+        Ok(())
+    }
+
+    /// Takes back turns or enters a turn again.
+    /// # Arguments
+    /// * `correction` - A `Correction` entered by the user.
+    /// # Errors
+    /// This function returns an `Err(String)` if there is no such turn, or the correction is cancelled.
+    /// The game is left as it was before the correction.
+    pub fn apply_correction(&mut self, correction: Correction) -> Result<(), String> {
+        match correction {
+            Correction::Undo (turns) => self.undo(turns),
+            Correction::Correct (number) => self.correct(number),
+        }
+    }
+
+    /// Takes back the last turns, restoring the game from before them and showing the bots only the turns kept.
+    /// # Arguments
+    /// * `turns` - A `usize` representing the number of turns to take back.
+    /// # Errors
+    /// This function returns an `Err(String)` if fewer turns have been played.
+    pub fn undo(&mut self, turns: usize) -> Result<(), String> {
+        if turns == 0 || turns > self.turns.len() {
+            return Err(format!("Enter a number of turns to undo between 1 and {}.", self.turns.len()));
+        }
+        self.restore(self.turns.len() - turns);
+        println!("Took back {turns} turns, it is {}'s turn.", self.name(self.game.current_turn));
+        Ok(())
+    }
+
+    /// Enters a turn again, replacing the cards entered for it.
+    /// Humans enter their whole turn again, bots only the card they drew from the deck.
+    /// Only the bot whos turn is corrected is asked for its discard again, with the new card,
+    /// unless it is registered as slow, in which case it keeps its discard so the table is not kept waiting.
+    /// The turns after it are played again as they were entered, and the first turn that is no longer valid
+    /// takes back every turn from it on, so those turns can be played again at the table.
+    /// # Arguments
+    /// * `number` - A `usize` representing the number of the turn, 1 is the first turn.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The turn has not been played.
+    /// * The turn is a bot drawing from the discard pile, which has no cards entered to correct.
+    /// * A correction is entered instead of the turn, which cancels it.
+    /// * The corrected turn is not valid.
+    pub fn correct(&mut self, number: usize) -> Result<(), String> {
+        if number == 0 || number > self.turns.len() {
+            return Err(format!("Enter a turn to correct between 1 and {}.", self.turns.len()));
+        }
+        let turns = self.turns.clone();
+        let original = turns[number - 1];
+        let name = self.name(original.seat);
+        let human = matches!(self.player_types[original.seat], PlayerType::Human (_));
+        if !human && original.draw != DrawCard::Deck {
+            return Err(format!("{name} drew from the discard pile on turn {number}, there is no card to correct."));
+        }
+        self.restore(number - 1);
+        let corrected = if human {
+            println!("Enter turn {number} for {name} again.");
+            get_turn_input().map(|turn| RecordedTurn {
+                seat: original.seat,
+                draw: turn.draw,
+                card: match turn.draw {
+                    DrawCard::Discard (card) => Some(card),
+                    DrawCard::Deck => None,
+                },
+                discard: turn.discard,
+            })
+        } else {
+            println!("Enter the name of the card {name} drew from the deck on turn {number}:");
+            get_card_or_correction_input().map(|card| self.redecide_discard(original, card))
+        };
+        let corrected = match corrected {
+            Ok(corrected) => corrected,
+            Err(_) => {
+                self.replay_turns(&turns[number - 1..]);
+                return Err("The correction was cancelled.".to_string());
+            }
+        };
+        let replayed = match self.replace_turn(corrected, &turns[number..]) {
+            Ok(replayed) => replayed,
+            Err(e) => {
+                self.replay_turns(&turns[number - 1..]);
+                return Err(format!("The corrected turn is not valid: {e}"));
+            }
+        };
+        if replayed < turns.len() - number {
+            println!(
+                "Took back the turns after turn {}, play them again at the table. It is {}'s turn.",
+                self.turns.len(),
+                self.name(self.game.current_turn),
+            );
+        } else {
+            println!("Corrected turn {number}.");
+        }
+        Ok(())
+    }
+
+    /// Helper function for `undo` and `correct`.
+    /// Restores the game from before a turn, and starts the bots again with only the turns before it.
+    /// # Arguments
+    /// * `turns` - A `usize` representing the number of turns to keep.
+    fn restore(&mut self, turns: usize) {
+        if turns >= self.turns.len() {
+            return;
+        }
+        self.snapshots.truncate(turns + 1);
+        let (game, played) = self.snapshots.pop().unwrap();
+        debug_assert_eq!(played, turns);
+        self.game = game;
+        self.turns.truncate(turns);
+        self.restart_bots();
+    }
+
+    /// Helper function for `correct`.
+    /// Asks a bot for its discard again, after the card it drew from the deck was corrected.
+    /// A slow bot, or a bot that would now draw from the discard pile, is not asked again.
+    /// It keeps its discard, or discards the new card if it had discarded the card it drew.
+    /// # Arguments
+    /// * `original` - A `RecordedTurn` representing the turn as it was first entered.
+    /// * `card` - A `Card` representing the card the bot really drew.
+    /// # Returns
+    /// A `RecordedTurn` representing the corrected turn.
+    fn redecide_discard(&mut self, original: RecordedTurn, card: Card) -> RecordedTurn {
+        let kept = RecordedTurn {
+            card: Some(card),
+            discard: if original.card == Some(original.discard) { card } else { original.discard },
+            ..original
+        };
+        let name = self.name(original.seat);
+        let number = self.turns.len() + 1;
+        let history = self.history();
+        let PlayerType::Bot (bot, spec) = &mut self.player_types[original.seat] else {
+            return kept;
+        };
+        if is_slow(spec) {
+            println!("{name} is a slow bot, so it keeps its discard.");
+            return kept;
+        }
+        let view = get_game_view(&self.game, &history);
+        if bot.generate_draw(&view) != DrawCard::Deck {
+            return kept;
+        }
+        let discard = bot.generate_discard(&view, &card);
+        if discard != kept.discard {
+            println!("{name} now discards {discard} instead of {} on turn {number}.", kept.discard);
+        }
+        RecordedTurn {discard, ..kept}
+    }
+
+    /// Helper function for `correct`.
+    /// Plays a corrected turn, then the turns that followed it as they were entered.
+    /// # Arguments
+    /// * `corrected` - A `RecordedTurn` representing the corrected turn, played after the turns kept.
+    /// * `later` - A `&[RecordedTurn]` containing the turns that followed it.
+    /// # Errors
+    /// This function returns an `Err(String)` if the corrected turn is not valid, and the game is left as it was.
+    /// # Returns
+    /// A `Result<usize, String>` containing the number of later turns that are still valid and were played.
+    fn replace_turn(&mut self, corrected: RecordedTurn, later: &[RecordedTurn]) -> Result<usize, String> {
+        self.replay_turn(corrected)?;
+        Ok(self.replay_turns(later))
+    }
+
+    /// Helper function for `correct`.
+    /// Plays recorded turns in order until one is no longer valid.
+    /// # Returns
+    /// A `usize` representing the number of turns played.
+    fn replay_turns(&mut self, turns: &[RecordedTurn]) -> usize {
+        for (played, turn) in turns.iter().enumerate() {
+            if let Err(e) = self.replay_turn(*turn) {
+                println!("Turn {} is no longer valid: {e}", self.turns.len() + 1);
+                return played;
+            }
+        }
+        turns.len()
+    }
+
+    /// Helper function for `resume` and `correct`.
+    /// Plays a recorded turn and shows it to every bot, without asking any bot to decide it.
+    /// A turn that is not valid leaves the game as it was.
+    fn replay_turn(&mut self, turn: RecordedTurn) -> Result<(), String> {
+        if self.game.over || turn.seat != self.game.current_turn {
+            return Err(format!("The turn of seat {} is out of order.", turn.seat));
+        }
+        let partial_turn = self.partial_turn(turn)?;
+        let before = self.game.clone();
+        if let Err(e) = self.game.play_turn(partial_turn) {
+            self.game = before;
+            return Err(e);
+        }
+        self.observe_turn(turn, before);
+        Ok(())
+    }
+
+    /// Helper function for `replay_turn`.
+    /// Gets a recorded turn as it is played in the partial game.
+    fn partial_turn(&self, turn: RecordedTurn) -> Result<PartialGameTurn, String> {
+        match (&self.player_types[turn.seat], turn.card) {
+            (PlayerType::Human (_), _) => Ok(PartialGameTurn::Human (PartialTurn::new(turn.draw, turn.discard))),
+            (PlayerType::Bot (..), Some(card)) => Ok(PartialGameTurn::Bot (Turn::new(card, turn.discard))),
+            (PlayerType::Bot (..), None) => Err("The card drawn by a bot is missing.".to_string()),
+        }
+    }
+
+    /// Gets the name of a player.
+    /// # Arguments
+    /// * `seat` - A `usize` representing the seat of the player.
+    /// # Returns
+    /// A `String` containing the name without any surrounding whitespace.
+    fn name(&self, seat: usize) -> String {
+        match &self.game.players[seat] {
+            PartialGamePlayer::Human (partial_player) => partial_player.name.trim().to_string(),
+            PartialGamePlayer::Bot (bot_player) => bot_player.name.trim().to_string(),
+        }
+    }
+
+    /// Helper function for `play` and `replay_turn`.
+    /// Shows a turn to every bot and keeps it, with the game from before it.
+    fn observe_turn(&mut self, turn: RecordedTurn, before: PartialGame) {
+        for player_type in self.player_types.iter_mut() {
+            if let PlayerType::Bot (bot, _) = player_type {
+                bot.observe_turn(&turn.observed());
+            }
+        }
+        self.snapshots.push((before, self.turns.len()));
+        self.turns.push(turn);
+    }
+
+    /// Gets every turn played so far as seen by every player.
+    /// # Returns
+    /// A `Vec<ObservedTurn>` containing the turns in order.
+    fn history(&self) -> Vec<ObservedTurn> {
+        self.turns.iter().map(RecordedTurn::observed).collect()
+    }
+
+    /// Creates a record of the game.
+    /// # Arguments
+    /// * `final_hands` - A `Vec<Hand>` containing each players final hand, empty if the game has not finished.
+    /// * `scores` - A `Vec<i16>` containing each players final score, empty if the game has not finished.
+    /// # Returns
+    /// A `GameRecord` of the game so far.
+    fn record(&self, final_hands: Vec<Hand>, scores: Vec<i16>) -> GameRecord {
+        GameRecord {
+            version: RECORD_VERSION,
+            kind: GameKind::Physical,
+            seed: Some(self.seed),
+            players: get_recorded_players(&self.player_types, &self.game),
+            starting_hands: self.starting_hands.clone(),
+            turns: self.turns.clone(),
+            discard_pile: self.game.discard_pile,
+            final_hands,
+            scores,
+        }
+    }
+
+    /// Saves the game so far over the last autosave.
+    /// A game that cannot be saved is reported but carries on.
+    fn autosave(&self) {
+        if let Err(e) = self.record(Vec::new(), Vec::new()).save_to(&autosave_path()) {
+            eprintln!("The game was not autosaved: {e}");
+        }
+    }
+}
 
 /// Runs a physical game.
+/// The game is saved after every turn, and the record of the game is saved to the default record directory.
 /// # Arguments 
 /// * `player_types` - A `Vec<PlayerType>` representing a all players in the game. 
 /// # Errors
@@ -16,33 +456,57 @@ use super::PlayerType;
 /// A `Result<Self, String>` which is:
 /// * `Ok(Game)` if the game is successfully run.
 /// * `Err(String)` containing an error message if validation fails.
-pub fn run_physical_game(mut player_types: Vec<PlayerType>) -> Result<(), String> {
-    let players = create_players(&player_types);
-    let mut game = PartialGame::new(players).unwrap();
-    while !game.over {
-        match &mut player_types[game.current_turn] {
-            PlayerType::Human (name) => handle_human_turn(name, &mut game)?,
-            PlayerType::Bot (bot) => handle_bot_turn(&mut**bot, &mut game)?,
-        }
-    }
-    display_final_scores(game);
-    Ok(())
+pub fn run_physical_game(player_types: Vec<PlayerType>) -> Result<(), String> {
+    PhysicalGame::new(player_types).play()
+}
+
+/// Resumes a physical game from its autosave and plays it to the end.
+/// # Arguments
+/// * `path` - A `&Path` to the autosave.
+/// # Errors
+/// This function returns an `Err(String)` if the autosave cannot be loaded or resumed, or a turn is not valid.
+pub fn resume_physical_game(path: &Path) -> Result<(), String> {
+    let mut physical_game = PhysicalGame::resume(&GameRecord::load(path)?)?;
+    println!("Resumed the game after {} turns.", physical_game.turns.len());
+    physical_game.play()
+}
+
+/// Gets the path of the autosave.
+/// # Returns
+/// A `PathBuf` to the autosave in the default record directory.
+pub fn autosave_path() -> PathBuf {
+    Path::new(DEFAULT_RECORD_DIRECTORY).join(AUTOSAVE_FILE)
+}
+
+/// Moves an autosave that will not be resumed out of the way, so the next physical game does not offer it again.
+/// It is kept next to the other records as an unfinished game, so it can still be viewed.
+/// # Arguments
+/// * `path` - A `&Path` to the autosave.
+/// # Errors
+/// This function returns an `Err(String)` if the autosave cannot be moved.
+/// # Returns
+/// A `Result<PathBuf, String>` containing the new path of the game.
+pub fn archive_autosave(path: &Path) -> Result<PathBuf, String> {
+    let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+    let archived = path.with_file_name(format!("physical-unfinished-{time}.txt"));
+    fs::rename(path, &archived).map_err(|e| format!("Failed to move {}: {e}", path.display()))?;
+    Ok(archived)
 }
 
-/// Helper function for `runs_physical_game`.
+/// Helper function for `PhysicalGame::new`.
 /// Creates a `PartialGamePlayer` for each player in the game
 /// # Arguments 
-/// * `player_types` - A `&Vec<PlayerType>` representing a all players in the game. 
+/// * `player_types` - A `&[PlayerType]` representing a all players in the game. 
 /// # Returns
 /// A `Vec<PartialGamePlayer>` representing a all players in the game.
-fn create_players(player_types: &Vec<PlayerType>) -> Vec<PartialGamePlayer> {
+fn create_players(player_types: &[PlayerType]) -> Vec<PartialGamePlayer> {
     player_types
         .iter()
         .map(|player_type| match player_type {
             PlayerType::Human(name) => {
                 PartialGamePlayer::Human(PartialPlayer::new(name.clone()))
             }
-            PlayerType::Bot(bot) => {
+            PlayerType::Bot(bot, _) => {
                 let name = bot.name();
                 println!("Enter the names of the cards in {}'s hand:", name.trim());
                 let hand = get_hand_input();
@@ -52,10 +516,10 @@ fn create_players(player_types: &Vec<PlayerType>) -> Vec<PartialGamePlayer> {
         .collect()
 }
 
-/// Helper function for `runs_physical_game`.
+/// Helper function for `PhysicalGame::play`.
 /// Interfaces with a bot to generate a turn and plays the turn generated.
 /// # Arguments 
-/// * `name` - A `&String` representing the bot whos turn it is to play. 
+/// * `name` - A `&str` representing the bot whos turn it is to play. 
 /// * `game` - A `&mut PartialGame` representing the game being played. 
 /// # Side Effects
 /// * **`PartialGame`**:
@@ -64,19 +528,33 @@ fn create_players(player_types: &Vec<PlayerType>) -> Vec<PartialGamePlayer> {
 /// This function returns an `Err(String)` if:
 /// * The turn generated is not valid. 
 /// # Returns
-/// A `Result<(), String>` which is:
-/// * `Ok(())` if the turn is prossesed sucessfully.
+/// A `Result<Result<RecordedTurn, Correction>, String>` which is:
+/// * `Ok(Ok(RecordedTurn))` containing the turn, with the card drawn if it was drawn from the discard pile,
+///   if the turn is prossesed sucessfully.
+/// * `Ok(Err(Correction))` containing a correction entered instead of the turn, which is not played.
 /// * `Err(String)` containing an error message if the turn validation failed.
-fn handle_human_turn(name: &String, game: &mut PartialGame) -> Result<(), String> {
+fn handle_human_turn(name: &str, game: &mut PartialGame) -> Result<Result<RecordedTurn, Correction>, String> {
     println!("{}'s turn.", name.trim());
-    game.play_turn(PartialGameTurn::Human (get_turn_input()))
+    let seat = game.current_turn;
+    let turn = match get_turn_input() {
+        Ok(turn) => turn,
+        Err(correction) => return Ok(Err(correction)),
+    };
+    let card = match turn.draw {
+        DrawCard::Discard (card) => Some(card),
+        DrawCard::Deck => None,
+    };
+    let recorded = RecordedTurn {seat, draw: turn.draw, card, discard: turn.discard};
+    game.play_turn(PartialGameTurn::Human (turn))?;
+    Ok(Ok(recorded))
 }
 
-/// Helper function for `runs_physical_game`.
+/// Helper function for `PhysicalGame::play`.
 /// Interfaces with a bot to generate a turn and plays the turn generated.
 /// # Arguments 
 /// * `bot` - A `&mut dyn FantasyRealmsBot` representing the bot whos turn it is to play. 
 /// * `game` - A `&mut PartialGame` representing the game being played. 
+/// * `history` - A `&[ObservedTurn]` containing every turn played so far.
 /// # Side Effects
 /// * **`PartialGame`**:
 ///    * plays a turn of the game.
@@ -84,21 +562,19 @@ fn handle_human_turn(name: &String, game: &mut PartialGame) -> Result<(), String
 /// This function returns an `Err(String)` if:
 /// * The turn generated is not valid. 
 /// # Returns
-/// A `Result<(), String>` which is:
-/// * `Ok(())` if the turn is prossesed sucessfully.
+/// A `Result<Result<RecordedTurn, Correction>, String>` which is:
+/// * `Ok(Ok(RecordedTurn))` containing the turn and the card drawn if the turn is prossesed sucessfully.
+/// * `Ok(Err(Correction))` containing a correction entered instead of the card drawn, the turn is not played.
 /// * `Err(String)` containing an error message if the turn validation failed.
-fn handle_bot_turn(bot: &mut dyn FantasyRealmsBot, game: &mut PartialGame) -> Result<(), String> {
+fn handle_bot_turn(
+    bot: &mut dyn FantasyRealmsBot,
+    game: &mut PartialGame,
+    history: &[ObservedTurn],
+) -> Result<Result<RecordedTurn, Correction>, String> {
     println!("{}'s turn.", bot.name().trim());
-    let hand = get_bot_hand(&game.players[game.current_turn]);
-    let opponent_cards = get_opponent_known_cards(game);
-    let min_turns_remaining = (10 - (game.discard_pile.len() as u8)) / (game.players.len() as u8);
-    let draw_card: DrawCard = bot
-        .generate_draw(
-            &hand,
-            &game.discard_pile,
-            &opponent_cards,
-            min_turns_remaining,
-    );
+    let seat = game.current_turn;
+    let view = get_game_view(game, history);
+    let draw_card: DrawCard = bot.generate_draw(&view);
     let draw = match draw_card {
         DrawCard::Discard(card) => {
             println!("{} draws {}.", bot.name().trim(), card);
@@ -106,23 +582,34 @@ fn handle_bot_turn(bot: &mut dyn FantasyRealmsBot, game: &mut PartialGame) -> Re
         }
         _ => {
             println!("{} draws from the deck. Enter the name of the card drawn:", bot.name().trim());
-            get_card_input()
+            match get_card_or_correction_input() {
+                Ok(card) => card,
+                Err(correction) => return Ok(Err(correction)),
+            }
         }
     };
-    let discard: Card = bot
-        .generate_discard(
-            &hand,
-            &game.discard_pile,
-            &opponent_cards,
-            min_turns_remaining,
-            &draw,
-    );
+    let discard: Card = bot.generate_discard(&view, &draw);
     println!("{} discards {}.", bot.name().trim(), discard);
     let turn = Turn::new(draw, discard);
-    game.play_turn(PartialGameTurn::Bot (turn))
+    game.play_turn(PartialGameTurn::Bot (turn))?;
+    Ok(Ok(RecordedTurn {seat, draw: draw_card, card: Some(draw), discard}))
 }
 
-/// Helper function for `handle_bot_turn`.
+/// Helper function for `PhysicalGame::redecide_discard`.
+/// Checks whether a bot is slow with the parameters it was created with.
+/// Plugin and external bots are not registered, so are not slow.
+/// # Arguments
+/// * `spec` - A `&str` representing the spec the bot was created from.
+/// # Returns
+/// A `bool` which is `true` if the bot is slow.
+fn is_slow(spec: &str) -> bool {
+    spec.parse::<BotSpec>()
+        .ok()
+        .and_then(|spec| bot_speed(&spec))
+        .is_some_and(|speed| speed == Speed::Slow)
+}
+
+/// Helper function for `get_game_view`.
 /// # Arguments 
 /// * `player` - A `&PartialGamePlayer` representing a bot.
 /// # Panics
@@ -136,46 +623,204 @@ fn get_bot_hand(player: &PartialGamePlayer) -> &Hand {
     }
 }
 
-/// Helper function for `handle_bot_turn`.
+/// Helper function for `handle_bot_turn` and `PhysicalGame::decide_again`.
+/// Gets the game as seen by the bot whos turn it is.
 /// # Arguments 
 /// * `game` - A `&PartialGame` representing the game being played.
+/// * `history` - A `&[ObservedTurn]` containing every turn played so far.
 /// # Returns
-/// A `Vec<CardCollection>` containing known cards from each opponents hands.  
-fn get_opponent_known_cards(game: &PartialGame) -> Vec<CardCollection> {
-    game.players
+/// A `GameView` for the current bot.  
+fn get_game_view(game: &PartialGame, history: &[ObservedTurn]) -> GameView {
+    let known_cards: Vec<CardCollection> = game.players
         .iter()
-        .enumerate()
-        .filter_map(|(i, player)| {
-            if i != game.current_turn {
-                Some(match player {
-                    PartialGamePlayer::Bot(p) => p.cards_known_to_opponents,
-                    PartialGamePlayer::Human(p) => p.cards_known_to_opponents,
-                })
-            } else {
-                None
-            }
+        .map(|player| match player {
+            PartialGamePlayer::Bot(p) => p.cards_known_to_opponents,
+            PartialGamePlayer::Human(p) => p.cards_known_to_opponents,
         })
-        .collect()
+        .collect();
+    GameView::new(
+        *get_bot_hand(&game.players[game.current_turn]),
+        game.current_turn,
+        known_cards,
+        game.discard_pile,
+        history.to_vec(),
+    )
 }
 
-/// A helper function for `run_physical_game`.
-/// Displays the final scores for each player in a game.
+/// A helper function for `PhysicalGame::play`.
+/// Gets the final hand of every player, asking for the hands of the human players.
 /// # Arguments
-/// * `game` - A `PartialGame` containing the hand information of all players.
-fn display_final_scores(game: PartialGame) {
+/// * `game` - A `&PartialGame` representing the finished game.
+/// # Returns
+/// A `Vec<Hand>` containing each players final hand by seat.
+fn get_final_hands(game: &PartialGame) -> Vec<Hand> {
     println!("The game has finished.");
-    let players: Vec<Player> = game.players
-        .into_iter()
+    game.players
+        .iter()
         .map(|player| match player {
             PartialGamePlayer::Human (partial_player) => {
                 println!("Enter cards in {}'s hand:", partial_player.name.trim());
-                Player::new(partial_player.name, get_hand_input())
+                get_hand_input()
             }
-            PartialGamePlayer::Bot(bot_player) => bot_player,
+            PartialGamePlayer::Bot(bot_player) => bot_player.hand,
         })
-        .collect();
-    for player in &players {
-        let score = player.hand.score(&game.discard_pile);
-        println!("{} scored {score} points.", player.name);
+        .collect()
+}
+
+/// A helper function for `PhysicalGame::record`.
+/// Gets every player as they are written in the record of the game.
+/// # Arguments
+/// * `player_types` - A `&[PlayerType]` containing the spec of each bot.
+/// * `game` - A `&PartialGame` containing the names of all players.
+/// # Returns
+/// A `Vec<RecordedPlayer>` containing each player by seat.
+fn get_recorded_players(player_types: &[PlayerType], game: &PartialGame) -> Vec<RecordedPlayer> {
+    player_types
+        .iter()
+        .zip(&game.players)
+        .map(|(player_type, player)| match (player_type, player) {
+            (PlayerType::Bot (_, spec), PartialGamePlayer::Bot (bot_player)) => {
+                RecordedPlayer::Bot {name: bot_player.name.clone(), spec: spec.clone()}
+            }
+            (_, PartialGamePlayer::Human (partial_player)) => RecordedPlayer::Human (partial_player.name.clone()),
+            (PlayerType::Human (name), _) => RecordedPlayer::Human (name.clone()),
+        })
+        .collect()
+}
+
+/// A helper function for `PhysicalGame::play`.
+/// Displays the final scores for each player in a game.
+/// # Arguments
+/// * `game` - A `&PartialGame` containing the names of all players.
+/// * `scores` - A `&[i16]` containing each players final score by seat.
+fn display_final_scores(game: &PartialGame, scores: &[i16]) {
+    for (player, score) in game.players.iter().zip(scores) {
+        let name = match player {
+            PartialGamePlayer::Human (partial_player) => &partial_player.name,
+            PartialGamePlayer::Bot(bot_player) => &bot_player.name,
+        };
+        println!("{} scored {score} points.", name);
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A game of Randy against two humans, with Randy's starting hand the first seven cards.
+    fn scripted_game() -> PhysicalGame {
+        let player_types = vec![
+            PlayerType::Bot (new_bot("Randy".to_string()).unwrap(), "Randy".to_string()),
+            PlayerType::Human ("Ann".to_string()),
+            PlayerType::Human ("Bob".to_string()),
+        ];
+        let hand = Hand::new(std::array::from_fn(|i| Card::from(i as u8)));
+        let players = vec![
+            PartialGamePlayer::Bot (Player::new("Randy".to_string(), hand)),
+            PartialGamePlayer::Human (PartialPlayer::new("Ann".to_string())),
+            PartialGamePlayer::Human (PartialPlayer::new("Bob".to_string())),
+        ];
+        PhysicalGame::start(player_types, players, 1)
+    }
+
+    /// Randy draws card 20 and discards it, Ann picks it up, Bob draws, then Randy discards card 0.
+    fn scripted_turns() -> Vec<RecordedTurn> {
+        vec![
+            RecordedTurn {seat: 0, draw: DrawCard::Deck, card: Some(Card::from(20)), discard: Card::from(20)},
+            RecordedTurn {seat: 1, draw: DrawCard::Discard (Card::from(20)), card: Some(Card::from(20)), discard: Card::from(21)},
+            RecordedTurn {seat: 2, draw: DrawCard::Deck, card: None, discard: Card::from(22)},
+            RecordedTurn {seat: 0, draw: DrawCard::Deck, card: Some(Card::from(23)), discard: Card::from(0)},
+        ]
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn undo_restores_the_game_before_the_turns_taken_back() {
+        let mut game = scripted_game();
+        let mut after = Vec::new();
+        for turn in scripted_turns() {
+            game.replay_turn(turn).unwrap();
+            after.push(game.game.clone());
+        }
+        game.undo(2).unwrap();
+        assert_eq!(game.turns, scripted_turns()[..2]);
+        assert_eq!(game.game, after[1]);
+        assert_eq!(game.snapshots.len(), 2);
+        assert!(game.undo(3).is_err());
+        game.replay_turn(scripted_turns()[2]).unwrap();
+        assert_eq!(game.game, after[2]);
+    }
+
+    #[test]
+    fn correcting_a_turn_replays_the_turns_still_valid() {
+        let mut game = scripted_game();
+        for turn in scripted_turns() {
+            game.replay_turn(turn).unwrap();
+        }
+        let turns = scripted_turns();
+
+        game.restore(2);
+        let corrected = RecordedTurn {discard: Card::from(24), ..turns[2]};
+        assert_eq!(game.replace_turn(corrected, &turns[3..]), Ok(1));
+        assert_eq!(game.turns, vec![turns[0], turns[1], corrected, turns[3]]);
+
+        game.restore(0);
+        let corrected = RecordedTurn {card: Some(Card::from(25)), discard: Card::from(0), ..turns[0]};
+        assert_eq!(game.replace_turn(corrected, &turns[1..]), Ok(2));
+        assert_eq!(game.turns, vec![corrected, turns[1], turns[2]]);
+        assert_eq!(game.game.current_turn, 0);
+    }
+
+    /// Plays turns until the game ends or a number of turns have been played, with the bots deciding their own turns.
+    /// Every card drawn from the deck is the next card of `deck`, and a human discards the card they drew.
+    fn play_turns(game: &mut PhysicalGame, deck: &mut impl Iterator<Item = Card>, count: usize) {
+        for _ in 0..count {
+            if game.game.over {
+                return;
+            }
+            let seat = game.game.current_turn;
+            let history = game.history();
+            let turn = match &mut game.player_types[seat] {
+                PlayerType::Bot (bot, _) => {
+                    let view = get_game_view(&game.game, &history);
+                    let draw = bot.generate_draw(&view);
+                    let card = match draw {
+                        DrawCard::Discard (card) => card,
+                        DrawCard::Deck => deck.next().unwrap(),
+                    };
+                    let discard = bot.generate_discard(&view, &card);
+                    RecordedTurn {seat, draw, card: Some(card), discard}
+                }
+                PlayerType::Human (_) => RecordedTurn {seat, draw: DrawCard::Deck, card: None, discard: deck.next().unwrap()},
+            };
+            game.replay_turn(turn).unwrap();
+        }
+    }
+
+    #[test]
+    fn a_resumed_game_makes_the_same_bot_moves_as_one_not_interrupted() {
+        let new_game = || {
+            let player_types = vec![
+                PlayerType::Bot (new_bot("Randy".to_string()).unwrap(), "Randy".to_string()),
+                PlayerType::Human ("Ann".to_string()),
+                PlayerType::Bot (new_bot("Randy".to_string()).unwrap(), "Randy".to_string()),
+            ];
+            let players = vec![
+                PartialGamePlayer::Bot (Player::new("Randy".to_string(), Hand::new(std::array::from_fn(|i| Card::from(i as u8))))),
+                PartialGamePlayer::Human (PartialPlayer::new("Ann".to_string())),
+                PartialGamePlayer::Bot (Player::new("Randy".to_string(), Hand::new(std::array::from_fn(|i| Card::from(i as u8 + 7))))),
+            ];
+            PhysicalGame::start(player_types, players, 7)
+        };
+
+        let mut uninterrupted = new_game();
+        play_turns(&mut uninterrupted, &mut (14..53).map(Card::from), 15);
+
+        let mut deck = (14..53).map(Card::from);
+        let mut interrupted = new_game();
+        play_turns(&mut interrupted, &mut deck, 6);
+        let mut resumed = PhysicalGame::resume(&interrupted.record(Vec::new(), Vec::new())).unwrap();
+        play_turns(&mut resumed, &mut deck, 9);
+
+        assert!(uninterrupted.turns.len() > 6);
+        assert_eq!(resumed.turns, uninterrupted.turns);
+    }
+}
@@ -0,0 +1,626 @@
+//! # Record
+//! A record of a finished game that can be saved, loaded and replayed.
+//! Records are plain text with one line for each part of the game, fields separated by `|`,
+//! so they can be read and compared by hand. The first line gives the version of the format,
+//! and records written by a newer version than this one are refused rather than misread.
+//!
+//! ```text
+//! fantasyrealms-record | 1
+//! kind | simulated
+//! seed | 42
+//! player | bot | AutomatosV1 | AutomatosV1(objective=win)
+//! player | human | Sam
+//! hand | 0 | Unicorn, Knights, Princess, ...
+//! turn | 0 | deck | Dragon | Knights
+//! turn | 1 | Knights | Knights | Unicorn
+//! discard | Knights, Unicorn, ...
+//! final | 0 | 54 | Unicorn, Dragon, Princess, ...
+//! ```
+//!
+//! Simulated games record every card. Physical games do not know the cards held by humans,
+//! so their starting hands and the cards they drew from the deck are written as `-`.
+//! A game that has not finished, such as the autosave of a physical game, has no `final` lines.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use fantasy_realms_unofficial_api::{DrawCard, Game, PartialGame, PartialGamePlayer, PartialGameTurn, PartialPlayer, PartialTurn, Player};
+use fantasy_realms_unofficial_api::hand::{Hand, Turn};
+use fantasy_realms_unofficial_api::deck::Card;
+use fantasy_realms_unofficial_api::card_collection::CardCollection;
+use fantasy_realms_ai::ObservedTurn;
+use fantasy_realms_ai::card_text::{decode_card, decode_cards, decode_draw, decode_hand, encode_cards, encode_draw, fields};
+use super::deal::Deal;
+
+/// The version of the record format written by this build.
+pub const RECORD_VERSION: u32 = 1;
+/// The directory records are saved to when no other directory is given.
+pub const DEFAULT_RECORD_DIRECTORY: &str = "records";
+/// The first field of the first line of every record.
+const RECORD_HEADER: &str = "fantasyrealms-record";
+
+/// How a recorded game was played.
+/// # Variants
+/// * `Simulated` - Every player was a bot and every card is known.
+/// * `Physical` - The game was played at a table, and the cards held by humans are not known.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum GameKind {
+    Simulated,
+    Physical,
+}
+
+/// A player in a recorded game.
+/// # Variants
+/// * `Human` - Contains a `String` representing the name of the player.
+/// * `Bot` - Contains the `name` of the bot and the `spec` it was created from.
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordedPlayer {
+    Human (String),
+    Bot {name: String, spec: String},
+}
+
+impl RecordedPlayer {
+    /// Gets the name of the player.
+    /// # Returns
+    /// A `&str` containing the name.
+    pub fn name(&self) -> &str {
+        match self {
+            RecordedPlayer::Human (name) => name,
+            RecordedPlayer::Bot {name, ..} => name,
+        }
+    }
+}
+
+/// A turn in a recorded game.
+/// # Fields
+/// * `seat` - A `usize` representing the seat of the player who played the turn.
+/// * `draw` - A `DrawCard` representing where the card was drawn from.
+/// * `card` - An `Option<Card>` representing the card drawn, `None` when a human drew it from the deck.
+/// * `discard` - A `Card` representing the card discarded.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RecordedTurn {
+    pub seat: usize,
+    pub draw: DrawCard,
+    pub card: Option<Card>,
+    pub discard: Card,
+}
+
+impl RecordedTurn {
+    /// Gets the turn as seen by every player at the table.
+    /// # Returns
+    /// An `ObservedTurn` with the card drawn from the deck hidden.
+    pub fn observed(&self) -> ObservedTurn {
+        ObservedTurn {seat: self.seat, draw: self.draw, discard: self.discard}
+    }
+}
+
+/// The state of a recorded game between two turns.
+/// # Fields
+/// * `current_turn` - A `usize` representing the seat of the player whos turn is next.
+/// * `discard_pile` - A `CardCollection` representing the discard pile.
+/// * `hands` - A `Vec<Option<Hand>>` containing each players hand by seat, `None` when it is not known.
+/// * `known_cards` - A `Vec<CardCollection>` containing the cards in each players hand known to their opponents.
+/// * `history` - A `Vec<ObservedTurn>` containing every turn played so far.
+#[derive(Clone)]
+pub struct Position {
+    pub current_turn: usize,
+    pub discard_pile: CardCollection,
+    pub hands: Vec<Option<Hand>>,
+    pub known_cards: Vec<CardCollection>,
+    pub history: Vec<ObservedTurn>,
+}
+
+/// A finished game.
+/// # Fields
+/// * `version` - A `u32` representing the version of the format the record was written in.
+/// * `kind` - A `GameKind` representing how the game was played.
+/// * `seed` - An `Option<u64>` representing the seed a simulated game was dealt from,
+///   or the seed the bots in a physical game were given.
+/// * `players` - A `Vec<RecordedPlayer>` containing each player by seat.
+/// * `starting_hands` - A `Vec<Option<Hand>>` containing each players starting hand by seat,
+///   `None` for humans.
+/// * `turns` - A `Vec<RecordedTurn>` containing every turn in the order played.
+/// * `discard_pile` - A `CardCollection` representing the final discard pile.
+/// * `final_hands` - A `Vec<Hand>` containing each players final hand by seat, empty if the game has not finished.
+/// * `scores` - A `Vec<i16>` containing each players final score by seat, empty if the game has not finished.
+#[derive(Clone)]
+pub struct GameRecord {
+    pub version: u32,
+    pub kind: GameKind,
+    pub seed: Option<u64>,
+    pub players: Vec<RecordedPlayer>,
+    pub starting_hands: Vec<Option<Hand>>,
+    pub turns: Vec<RecordedTurn>,
+    pub discard_pile: CardCollection,
+    pub final_hands: Vec<Hand>,
+    pub scores: Vec<i16>,
+}
+
+impl GameRecord {
+    /// Writes the record in the text format.
+    /// # Returns
+    /// A `String` containing one line for each part of the game.
+    pub fn encode(&self) -> String {
+        let mut lines: Vec<String> = Vec::new();
+        lines.push(format!("{RECORD_HEADER} | {}", self.version));
+        lines.push(format!("kind | {}", match self.kind {
+            GameKind::Simulated => "simulated",
+            GameKind::Physical => "physical",
+        }));
+        lines.push(format!("seed | {}", self.seed.map_or("-".to_string(), |seed| seed.to_string())));
+        for player in &self.players {
+            lines.push(match player {
+                RecordedPlayer::Human (name) => format!("player | human | {}", encode_text(name)),
+                RecordedPlayer::Bot {name, spec} => {
+                    format!("player | bot | {} | {}", encode_text(name), encode_text(spec))
+                }
+            });
+        }
+        for (seat, hand) in self.starting_hands.iter().enumerate() {
+            lines.push(format!("hand | {seat} | {}", hand.map_or("-".to_string(), |hand| encode_cards(hand.to_vec()))));
+        }
+        for turn in &self.turns {
+            lines.push(format!(
+                "turn | {} | {} | {} | {}",
+                turn.seat,
+                encode_draw(turn.draw),
+                turn.card.map_or("-".to_string(), |card| card.to_string()),
+                turn.discard,
+            ));
+        }
+        lines.push(format!("discard | {}", encode_cards(self.discard_pile)));
+        for (seat, (hand, score)) in self.final_hands.iter().zip(&self.scores).enumerate() {
+            lines.push(format!("final | {seat} | {score} | {}", encode_cards(hand.to_vec())));
+        }
+        lines.join("\n") + "\n"
+    }
+
+    /// Reads a record written by `encode`.
+    /// # Arguments
+    /// * `text` - A `&str` containing the record.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The record is from a newer version of the format.
+    /// * Any line cannot be read, with the number of the line.
+    /// * A part of the game is missing or does not match the number of players.
+    /// # Returns
+    /// A `Result<GameRecord, String>` containing the record.
+    pub fn decode(text: &str) -> Result<Self, String> {
+        let mut lines = text.lines().enumerate().filter(|(_, line)| !line.trim().is_empty());
+        let (_, header) = lines.next().ok_or("The record is empty.".to_string())?;
+        let version = match fields(header).as_slice() {
+            [RECORD_HEADER, version] => {
+                version.parse::<u32>().map_err(|_| format!("Invalid record version: {version}"))?
+            }
+            _ => return Err("Not a game record.".to_string()),
+        };
+        if version > RECORD_VERSION {
+            return Err(format!(
+                "The record is version {version}, this build reads up to version {RECORD_VERSION}."
+            ));
+        }
+        let mut kind: Option<GameKind> = None;
+        let mut seed: Option<u64> = None;
+        let mut players: Vec<RecordedPlayer> = Vec::new();
+        let mut starting_hands: Vec<Option<Hand>> = Vec::new();
+        let mut turns: Vec<RecordedTurn> = Vec::new();
+        let mut discard_pile: Option<CardCollection> = None;
+        let mut final_hands: Vec<Hand> = Vec::new();
+        let mut scores: Vec<i16> = Vec::new();
+        for (number, line) in lines {
+            let fields = fields(line);
+            let result: Result<(), String> = match fields.as_slice() {
+                ["kind", "simulated"] => {
+                    kind = Some(GameKind::Simulated);
+                    Ok(())
+                }
+                ["kind", "physical"] => {
+                    kind = Some(GameKind::Physical);
+                    Ok(())
+                }
+                ["seed", "-"] => Ok(()),
+                ["seed", value] => value
+                    .parse::<u64>()
+                    .map(|value| seed = Some(value))
+                    .map_err(|_| format!("Invalid seed: {value}")),
+                ["player", "human", name] => {
+                    players.push(RecordedPlayer::Human (name.to_string()));
+                    Ok(())
+                }
+                ["player", "bot", name, spec] => {
+                    players.push(RecordedPlayer::Bot {name: name.to_string(), spec: spec.to_string()});
+                    Ok(())
+                }
+                ["hand", seat, cards] => decode_seat(seat, starting_hands.len()).and_then(|_| {
+                    starting_hands.push(match *cards {
+                        "-" => None,
+                        cards => Some(decode_hand(cards)?),
+                    });
+                    Ok(())
+                }),
+                ["turn", seat, draw, card, discard] => decode_turn(seat, draw, card, discard).map(|turn| turns.push(turn)),
+                ["discard", cards] => decode_cards(cards).map(|cards| discard_pile = Some(collect(cards))),
+                ["final", seat, score, cards] => decode_seat(seat, final_hands.len()).and_then(|_| {
+                    scores.push(score.parse::<i16>().map_err(|_| format!("Invalid score: {score}"))?);
+                    final_hands.push(decode_hand(cards)?);
+                    Ok(())
+                }),
+                _ => Err("Unknown line.".to_string()),
+            };
+            result.map_err(|e| format!("Line {}: {e}", number + 1))?;
+        }
+        let kind = kind.ok_or("The record has no kind.".to_string())?;
+        let discard_pile = discard_pile.ok_or("The record has no final discard pile.".to_string())?;
+        if players.is_empty() {
+            return Err("The record has no players.".to_string());
+        }
+        if starting_hands.len() != players.len() {
+            return Err("The record does not have a starting hand for every player.".to_string());
+        }
+        if !final_hands.is_empty() && final_hands.len() != players.len() {
+            return Err("The record does not have a final hand for every player.".to_string());
+        }
+        Ok(GameRecord {version, kind, seed, players, starting_hands, turns, discard_pile, final_hands, scores})
+    }
+
+    /// Saves the record to a new file in a directory, creating the directory if needed.
+    /// # Arguments
+    /// * `directory` - A `&Path` to the directory to save the record in.
+    /// # Errors
+    /// This function returns an `Err(String)` if the directory or file cannot be written.
+    /// # Returns
+    /// A `Result<PathBuf, String>` containing the path of the file written.
+    pub fn save(&self, directory: &Path) -> Result<PathBuf, String> {
+        let time = SystemTime::now().duration_since(UNIX_EPOCH).unwrap_or_default().as_millis();
+        let kind = match self.kind {
+            GameKind::Simulated => "simulated",
+            GameKind::Physical => "physical",
+        };
+        let path = directory.join(format!("{kind}-{time}.txt"));
+        self.save_to(&path)?;
+        Ok(path)
+    }
+
+    /// Saves the record to a file, creating its directory if needed.
+    /// The record is written to a temporary file that then replaces the file,
+    /// so the file is never left half written if the program stops while saving.
+    /// # Arguments
+    /// * `path` - A `&Path` to the file to write.
+    /// # Errors
+    /// This function returns an `Err(String)` if the directory or file cannot be written.
+    pub fn save_to(&self, path: &Path) -> Result<(), String> {
+        if let Some(directory) = path.parent().filter(|directory| !directory.as_os_str().is_empty()) {
+            fs::create_dir_all(directory)
+                .map_err(|e| format!("Failed to create {}: {e}", directory.display()))?;
+        }
+        let temporary = path.with_extension("tmp");
+        fs::write(&temporary, self.encode())
+            .map_err(|e| format!("Failed to write {}: {e}", temporary.display()))?;
+        fs::rename(&temporary, path).map_err(|e| format!("Failed to write {}: {e}", path.display()))
+    }
+
+    /// Checks whether the game was played to the end.
+    /// # Returns
+    /// A `bool` which is `true` if the record has the final hands and scores.
+    pub fn is_finished(&self) -> bool {
+        !self.final_hands.is_empty()
+    }
+
+    /// Loads a record saved by `save`.
+    /// # Arguments
+    /// * `path` - A `&Path` to the record.
+    /// # Errors
+    /// This function returns an `Err(String)` if the file cannot be read or is not a record.
+    /// # Returns
+    /// A `Result<GameRecord, String>` containing the record.
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let text = fs::read_to_string(path).map_err(|e| format!("Failed to read {}: {e}", path.display()))?;
+        GameRecord::decode(&text).map_err(|e| format!("{}: {e}", path.display()))
+    }
+
+    /// Plays every turn of the record again and checks that the game ends the way it was recorded.
+    /// Simulated games are played through a `Game` and physical games through a `PartialGame`,
+    /// so every turn is validated by the same rules it was validated by when it was played.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The record is of a game that has not finished.
+    /// * A turn is played out of order or is not valid.
+    /// * In a simulated game, a card drawn from the discard pile is not in it,
+    ///   or a card drawn from the deck of a seeded game is not the next card dealt.
+    /// * The game ends before the last turn, or has not ended after it.
+    /// * The final discard pile, a bots final hand or a score is not the one recorded.
+    pub fn replay(&self) -> Result<(), String> {
+        if !self.is_finished() {
+            return Err("The record is of a game that has not finished.".to_string());
+        }
+        let positions = self.positions()?;
+        let last = positions.last().expect("Positions include the start of the game.");
+        let discard_pile = last.discard_pile;
+        if !same_cards(discard_pile, self.discard_pile) {
+            return Err("The final discard pile is not the one recorded.".to_string());
+        }
+        for (seat, hand) in last.hands.iter().enumerate() {
+            if let Some(hand) = hand {
+                if !same_cards(collect(hand.to_vec()), collect(self.final_hands[seat].to_vec())) {
+                    return Err(format!("The final hand of {} is not the one recorded.", self.players[seat].name()));
+                }
+            }
+        }
+        for (seat, (hand, score)) in self.final_hands.iter().zip(&self.scores).enumerate() {
+            let replayed = hand.score(&discard_pile);
+            if replayed != *score {
+                return Err(format!(
+                    "{} scores {replayed} points, the record says {score}.",
+                    self.players[seat].name(),
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Plays every turn of the record, keeping the state of the game before the first turn and after every turn.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * A turn is played out of order or is not valid.
+    /// * In a simulated game, a card drawn from the discard pile is not in it,
+    ///   or a card drawn from the deck of a seeded game is not the next card dealt.
+    /// * The game ends before the last turn, or has not ended after it.
+    /// # Returns
+    /// A `Result<Vec<Position>, String>` containing one more position than there are turns.
+    pub fn positions(&self) -> Result<Vec<Position>, String> {
+        match self.kind {
+            GameKind::Simulated => self.replay_simulated(),
+            GameKind::Physical => self.replay_physical(),
+        }
+    }
+
+    /// Helper function for `positions`.
+    /// Plays a simulated record through a `Game`.
+    fn replay_simulated(&self) -> Result<Vec<Position>, String> {
+        let players: Vec<Player> = self.players
+            .iter()
+            .zip(&self.starting_hands)
+            .map(|(player, hand)| {
+                let hand = hand.ok_or(format!("The starting hand of {} is missing.", player.name()))?;
+                Ok(Player::new(player.name().to_string(), hand))
+            })
+            .collect::<Result<Vec<Player>, String>>()?;
+        let mut game = Game::new(players).unwrap();
+        let position = |game: &Game, history: &[ObservedTurn]| Position {
+            current_turn: game.current_turn,
+            discard_pile: game.discard_pile,
+            hands: game.players.iter().map(|player| Some(player.hand)).collect(),
+            known_cards: game.players.iter().map(|player| player.cards_known_to_opponents).collect(),
+            history: history.to_vec(),
+        };
+        let mut positions: Vec<Position> = vec![position(&game, &[])];
+        let mut history: Vec<ObservedTurn> = Vec::new();
+        let mut deal = self.seed.map(|seed| Deal::new(seed, self.players.len()));
+        for (number, turn) in self.turns.iter().enumerate() {
+            check_turn(number, turn, game.current_turn, game.over)?;
+            let card = turn.card.ok_or(format!("Turn {}: the card drawn is missing.", number + 1))?;
+            check_draw(number, turn.draw, card, game.discard_pile, deal.as_mut())?;
+            game.play_turn(Turn::new(card, turn.discard)).map_err(|e| format!("Turn {}: {e}", number + 1))?;
+            history.push(turn.observed());
+            positions.push(position(&game, &history));
+        }
+        if !game.over {
+            return Err("The game has not ended after the last turn.".to_string());
+        }
+        Ok(positions)
+    }
+
+    /// Helper function for `positions`.
+    /// Plays a physical record through a `PartialGame`.
+    fn replay_physical(&self) -> Result<Vec<Position>, String> {
+        let players: Vec<PartialGamePlayer> = self.players
+            .iter()
+            .zip(&self.starting_hands)
+            .map(|(player, hand)| match (player, hand) {
+                (RecordedPlayer::Human (name), _) => Ok(PartialGamePlayer::Human(PartialPlayer::new(name.clone()))),
+                (RecordedPlayer::Bot {name, ..}, Some(hand)) => Ok(PartialGamePlayer::Bot(Player::new(name.clone(), *hand))),
+                (RecordedPlayer::Bot {name, ..}, None) => Err(format!("The starting hand of {name} is missing.")),
+            })
+            .collect::<Result<Vec<PartialGamePlayer>, String>>()?;
+        let mut game = PartialGame::new(players).unwrap();
+        let position = |game: &PartialGame, history: &[ObservedTurn]| Position {
+            current_turn: game.current_turn,
+            discard_pile: game.discard_pile,
+            hands: game.players
+                .iter()
+                .map(|player| match player {
+                    PartialGamePlayer::Human (_) => None,
+                    PartialGamePlayer::Bot (player) => Some(player.hand),
+                })
+                .collect(),
+            known_cards: game.players
+                .iter()
+                .map(|player| match player {
+                    PartialGamePlayer::Human (player) => player.cards_known_to_opponents,
+                    PartialGamePlayer::Bot (player) => player.cards_known_to_opponents,
+                })
+                .collect(),
+            history: history.to_vec(),
+        };
+        let mut positions: Vec<Position> = vec![position(&game, &[])];
+        let mut history: Vec<ObservedTurn> = Vec::new();
+        for (number, turn) in self.turns.iter().enumerate() {
+            check_turn(number, turn, game.current_turn, game.over)?;
+            let partial_turn = match (&self.players[turn.seat], turn.card) {
+                (RecordedPlayer::Human (_), _) => PartialGameTurn::Human (PartialTurn::new(turn.draw, turn.discard)),
+                (RecordedPlayer::Bot {..}, Some(card)) => PartialGameTurn::Bot (Turn::new(card, turn.discard)),
+                (RecordedPlayer::Bot {..}, None) => {
+                    return Err(format!("Turn {}: the card drawn is missing.", number + 1));
+                }
+            };
+            game.play_turn(partial_turn).map_err(|e| format!("Turn {}: {e}", number + 1))?;
+            history.push(turn.observed());
+            positions.push(position(&game, &history));
+        }
+        if !game.over {
+            return Err("The game has not ended after the last turn.".to_string());
+        }
+        Ok(positions)
+    }
+}
+
+/// Saves a record to the default directory and prints where it was saved.
+/// A record that cannot be saved is reported but does not stop the program.
+/// # Arguments
+/// * `record` - A `&GameRecord` of the finished game.
+pub fn save_record(record: &GameRecord) {
+    match record.save(Path::new(DEFAULT_RECORD_DIRECTORY)) {
+        Ok(path) => println!("Saved the game record to {}.", path.display()),
+        Err(e) => eprintln!("The game record was not saved: {e}"),
+    }
+}
+
+/// Loads a record and replays it, printing whether it ends the way it was recorded.
+/// # Arguments
+/// * `path` - A `&Path` to the record.
+/// # Errors
+/// This function returns an `Err(String)` if the record cannot be loaded or does not replay.
+pub fn run_replay(path: &Path) -> Result<(), String> {
+    let record = GameRecord::load(path)?;
+    record.replay()?;
+    println!(
+        "Replayed {} turns between {}, the game ends the way it was recorded.",
+        record.turns.len(),
+        record.players.iter().map(|player| player.name().trim().to_string()).collect::<Vec<String>>().join(", "),
+    );
+    Ok(())
+}
+
+/// Helper function for `positions`.
+/// Checks that a turn is played by the player whos turn it is, before the game has ended.
+fn check_turn(number: usize, turn: &RecordedTurn, current_turn: usize, over: bool) -> Result<(), String> {
+    if over {
+        return Err(format!("Turn {}: the game had already ended.", number + 1));
+    }
+    if turn.seat != current_turn {
+        return Err(format!("Turn {}: it was seat {current_turn}'s turn, not seat {}'s.", number + 1, turn.seat));
+    }
+    Ok(())
+}
+
+/// Helper function for `positions`.
+/// Checks that the card drawn on a simulated turn came from where the turn says.
+/// A card drawn from the discard pile must be in it, and a card drawn from the deck of a seeded game
+/// must be the next card dealt from the seed.
+/// # Arguments
+/// * `number` - A `usize` representing the number of turns played before the turn.
+/// * `draw` - A `DrawCard` representing where the card was drawn from.
+/// * `card` - A `Card` representing the card drawn.
+/// * `discard_pile` - A `CardCollection` representing the discard pile before the turn.
+/// * `deal` - An `Option<&mut Deal>` holding the cards left in the deck, `None` if the game was not seeded.
+fn check_draw(number: usize, draw: DrawCard, card: Card, discard_pile: CardCollection, deal: Option<&mut Deal>) -> Result<(), String> {
+    match (draw, deal) {
+        (DrawCard::Discard (drawn), _) if drawn != card => {
+            Err(format!("Turn {}: {drawn} was drawn from the discard pile, but the card drawn is {card}.", number + 1))
+        }
+        (DrawCard::Discard (drawn), _) if !discard_pile.iter().any(|other| other == drawn) => {
+            Err(format!("Turn {}: {drawn} is not in the discard pile.", number + 1))
+        }
+        (DrawCard::Discard (_), _) | (DrawCard::Deck, None) => Ok(()),
+        (DrawCard::Deck, Some(deal)) => match deal.draw() {
+            Some(next) if next == card => Ok(()),
+            Some(next) => Err(format!("Turn {}: {card} was drawn from the deck, but the next card dealt is {next}.", number + 1)),
+            None => Err(format!("Turn {}: {card} was drawn from the deck, but the deck is empty.", number + 1)),
+        },
+    }
+}
+
+/// Helper function for `replay`.
+/// Checks that two collections hold the same cards.
+fn same_cards(a: CardCollection, b: CardCollection) -> bool {
+    a.len() == b.len() && a.iter().all(|card| b.iter().any(|other| other == card))
+}
+
+/// Helper function for `replay`.
+/// Collects cards into a collection.
+fn collect<I: IntoIterator<Item = Card>>(cards: I) -> CardCollection {
+    cards.into_iter().fold(CardCollection::new(), |collection, card| collection + card)
+}
+
+/// Helper function to write records.
+/// Writes a name or spec, which cannot contain the field separator or a new line.
+fn encode_text(text: &str) -> String {
+    text.trim().replace(['|', '\n', '\r'], " ")
+}
+
+/// Helper function to read records.
+/// Reads a seat, which must be the next seat in order.
+fn decode_seat(field: &str, expected: usize) -> Result<(), String> {
+    match field.parse::<usize>() {
+        Ok(seat) if seat == expected => Ok(()),
+        _ => Err(format!("Expected seat {expected}, found {field}.")),
+    }
+}
+
+/// Helper function to read records.
+/// Reads the seat, draw, card drawn and discard of a turn.
+fn decode_turn(seat: &str, draw: &str, card: &str, discard: &str) -> Result<RecordedTurn, String> {
+    Ok(RecordedTurn {
+        seat: seat.parse::<usize>().map_err(|_| format!("Invalid seat: {seat}"))?,
+        draw: decode_draw(draw)?,
+        card: match card {
+            "-" => None,
+            card => Some(decode_card(card)?),
+        },
+        discard: decode_card(discard)?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use fantasy_realms_ai::{new_bot, FantasyRealmsBot};
+    use crate::simulated_game::play_simulated_game;
+    use super::*;
+
+    #[test]
+    fn records_are_read_back_as_they_were_written() {
+        let specs: Vec<String> = vec!["Randy".to_string(); 4];
+        let mut bots: Vec<Box<dyn FantasyRealmsBot>> = specs
+            .iter()
+            .map(|spec| new_bot(spec.clone()).unwrap())
+            .collect();
+        let text = play_simulated_game(&mut bots, 5, false).unwrap().record(&specs).encode();
+        let record = GameRecord::decode(&text).unwrap();
+        assert_eq!(record.encode(), text);
+        assert!(record.replay().is_ok());
+    }
+
+    #[test]
+    fn records_from_a_newer_version_are_refused() {
+        let text = format!("{RECORD_HEADER} | {}\nkind | simulated\n", RECORD_VERSION + 1);
+        match GameRecord::decode(&text) {
+            Err(e) => assert!(e.contains("this build reads up to version")),
+            Ok(_) => panic!("A record from a newer version was read."),
+        }
+    }
+
+    #[test]
+    fn draws_that_did_not_come_from_where_they_say_are_refused() {
+        let specs: Vec<String> = vec!["Randy".to_string(); 3];
+        let mut bots: Vec<Box<dyn FantasyRealmsBot>> = specs
+            .iter()
+            .map(|spec| new_bot(spec.clone()).unwrap())
+            .collect();
+        let record = play_simulated_game(&mut bots, 9, false).unwrap().record(&specs);
+        assert!(record.positions().is_ok());
+        let first = record.turns[0];
+        let other = (0..53).map(Card::from).find(|&card| Some(card) != first.card).unwrap();
+
+        let mut wrong_card = GameRecord::decode(&record.encode()).unwrap();
+        wrong_card.turns[0].card = Some(other);
+        assert!(wrong_card.positions().err().unwrap().contains("the next card dealt is"));
+
+        let mut not_in_pile = GameRecord::decode(&record.encode()).unwrap();
+        not_in_pile.turns[0].draw = DrawCard::Discard (first.card.unwrap());
+        assert!(not_in_pile.positions().err().unwrap().contains("is not in the discard pile"));
+
+        let mut mismatched = GameRecord::decode(&record.encode()).unwrap();
+        mismatched.turns[0].draw = DrawCard::Discard (other);
+        assert!(mismatched.positions().err().unwrap().contains("but the card drawn is"));
+    }
+}
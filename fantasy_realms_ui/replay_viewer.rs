@@ -0,0 +1,447 @@
+//! # Replay Viewer
+//! Steps forwards and backwards through a recorded game in the terminal.
+//! Every turn of the record is played again up front, so moving to any point in the game is instant.
+//! At each point the viewer shows the discard pile, the hand of the player whos turn it is,
+//! or the cards known about it when a human held it, and the score of every hand that is known.
+//! Any bot can be asked what it would have played instead, seeing the game as that player saw it.
+
+use std::path::Path;
+use fantasy_realms_unofficial_api::DrawCard;
+use fantasy_realms_unofficial_api::deck::Card;
+use fantasy_realms_unofficial_api::hand::Hand;
+use fantasy_realms_ai::{new_bot, validate_bot, FantasyRealmsBot, GameView};
+use super::deal::{random_seed, Deal};
+use super::record::{GameKind, GameRecord, Position};
+use super::terminal_interface::{get_string_input, print_bot_list};
+
+/// A recorded game being viewed.
+/// # Fields
+/// * `record` - A `GameRecord` of the game.
+/// * `positions` - A `Vec<Position>` containing the state of the game before the first turn and after every turn.
+/// * `index` - A `usize` representing the number of turns played at the position being viewed.
+pub struct ReplayViewer {
+    record: GameRecord,
+    positions: Vec<Position>,
+    index: usize,
+}
+
+/// What a bot would have played at a position.
+/// # Fields
+/// * `draw` - A `DrawCard` representing where the bot would draw from.
+/// * `card` - An `Option<Card>` representing the card it would draw, `None` if the top of the deck is not known.
+/// * `discard` - An `Option<Card>` representing the card it would discard, `None` if the card drawn is not known.
+/// * `caught_up` - A `bool` which is `false` if the bot could not make every earlier decision of the seat itself,
+///   because the hand or the card drawn was not known, so it may remember less than if it had played them.
+pub struct Suggestion {
+    pub draw: DrawCard,
+    pub card: Option<Card>,
+    pub discard: Option<Card>,
+    pub caught_up: bool,
+}
+
+impl ReplayViewer {
+    /// Creates a viewer at the start of a recorded game.
+    /// # Arguments
+    /// * `record` - A `GameRecord` of the game to view.
+    /// # Errors
+    /// This function returns an `Err(String)` if the record does not replay.
+    /// # Returns
+    /// A `Result<ReplayViewer, String>` containing the viewer.
+    pub fn new(record: GameRecord) -> Result<Self, String> {
+        let positions = record.positions()?;
+        Ok(ReplayViewer {record, positions, index: 0})
+    }
+
+    /// Gets the number of turns played at the position being viewed.
+    /// # Returns
+    /// A `usize` between 0 and the number of turns in the game.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Gets the position being viewed.
+    /// # Returns
+    /// A `&Position` representing the state of the game.
+    pub fn position(&self) -> &Position {
+        &self.positions[self.index]
+    }
+
+    /// Checks whether the position being viewed is the end of the game.
+    /// # Returns
+    /// A `bool` which is `true` after the last turn.
+    pub fn is_over(&self) -> bool {
+        self.index == self.record.turns.len()
+    }
+
+    /// Moves to any point in the game.
+    /// # Arguments
+    /// * `index` - A `usize` representing the number of turns played, 0 is the start of the game.
+    /// # Errors
+    /// This function returns an `Err(String)` if the game does not have that many turns.
+    pub fn go_to(&mut self, index: usize) -> Result<(), String> {
+        if index > self.record.turns.len() {
+            return Err(format!("The game only has {} turns.", self.record.turns.len()));
+        }
+        self.index = index;
+        Ok(())
+    }
+
+    /// Moves forward one turn.
+    /// # Returns
+    /// A `bool` which is `false` if the game had already ended.
+    pub fn step_forward(&mut self) -> bool {
+        self.go_to(self.index + 1).is_ok()
+    }
+
+    /// Moves back one turn.
+    /// # Returns
+    /// A `bool` which is `false` if the position was already the start of the game.
+    pub fn step_back(&mut self) -> bool {
+        match self.index.checked_sub(1) {
+            Some(index) => self.go_to(index).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Gets the hand of every player at the position being viewed.
+    /// At the end of the game every hand is known, as the final hands of humans are recorded.
+    /// # Returns
+    /// A `Vec<Option<Hand>>` containing each players hand by seat, `None` when it is not known.
+    fn hands(&self) -> Vec<Option<Hand>> {
+        if self.is_over() {
+            return self.record.final_hands.iter().map(|&hand| Some(hand)).collect();
+        }
+        self.position().hands.clone()
+    }
+
+    /// Gets the card on top of the deck at the position being viewed.
+    /// Seeded simulated games deal the deck in the same order every time, so the card is always known.
+    /// Otherwise it is only known if the card was drawn from the deck by a bot on the next turn.
+    /// # Returns
+    /// An `Option<Card>` which is `None` if the card is not known.
+    fn top_of_deck(&self) -> Option<Card> {
+        if let (GameKind::Simulated, Some(seed)) = (self.record.kind, self.record.seed) {
+            let mut deal = Deal::new(seed, self.record.players.len());
+            let drawn = self.record.turns[..self.index]
+                .iter()
+                .filter(|turn| matches!(turn.draw, DrawCard::Deck))
+                .count();
+            for _ in 0..drawn {
+                deal.draw();
+            }
+            return deal.draw();
+        }
+        self.record.turns
+            .get(self.index)
+            .filter(|turn| matches!(turn.draw, DrawCard::Deck))
+            .and_then(|turn| turn.card)
+    }
+
+    /// Asks a bot what it would play at the position being viewed, in the seat of the player whos turn it is.
+    /// The bot is started with the players starting hand and shown every turn played so far,
+    /// so it sees the game as that player saw it. On each earlier turn of the seat it is also asked
+    /// for the turn and then shown the turn that was played, so that its random choices and anything it
+    /// keeps between turns are the same as if it had been playing the seat. A bot asked in the seat it played
+    /// in a seeded simulated game therefore suggests the turn it played.
+    /// # Arguments
+    /// * `spec` - A `&str` containing the spec of the bot, such as `AutomatosV2(depth=2)`.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * The game has ended.
+    /// * The hand of the player whos turn it is, is not known.
+    /// * The spec is not a bot.
+    /// # Returns
+    /// A `Result<Suggestion, String>` containing what the bot would play.
+    pub fn ask(&self, spec: &str) -> Result<Suggestion, String> {
+        if self.is_over() {
+            return Err("The game has ended, there is no turn to play.".to_string());
+        }
+        let position = self.position();
+        let seat = position.current_turn;
+        let name = self.record.players[seat].name().trim().to_string();
+        let (hand, starting_hand) = match (position.hands[seat], self.positions[0].hands[seat]) {
+            (Some(hand), Some(starting_hand)) => (hand, starting_hand),
+            _ => return Err(format!("The hand of {name} is not known, so a bot cannot play it.")),
+        };
+        let mut bot = new_bot(spec.to_string())?;
+        let players = self.record.players.len();
+        bot.seed(match self.record.seed {
+            Some(seed) => Deal::new(seed, players).bot_seed(seat),
+            None => random_seed(),
+        });
+        bot.on_game_start(seat, players, &starting_hand);
+        let mut caught_up = true;
+        for (index, turn) in position.history.iter().enumerate() {
+            if self.record.turns[index].seat == seat {
+                caught_up &= self.decide_again(&mut *bot, index);
+            }
+            bot.observe_turn(turn);
+        }
+        let view = GameView::new(
+            hand,
+            seat,
+            position.known_cards.clone(),
+            position.discard_pile,
+            position.history.clone(),
+        );
+        let draw = bot.generate_draw(&view);
+        let card = match draw {
+            DrawCard::Discard (card) => Some(card),
+            DrawCard::Deck => self.top_of_deck(),
+        };
+        let discard = card.map(|card| bot.generate_discard(&view, &card));
+        Ok(Suggestion {draw, card, discard, caught_up})
+    }
+
+    /// Helper function for `ask`.
+    /// Has a bot decide an earlier turn of its seat, ignoring what it decides.
+    /// # Arguments
+    /// * `bot` - A `&mut dyn FantasyRealmsBot` being caught up to the position being viewed.
+    /// * `index` - A `usize` representing the number of turns played before the turn.
+    /// # Returns
+    /// A `bool` which is `false` if the hand or the card drawn on the turn is not known, so the bot was not asked.
+    fn decide_again(&self, bot: &mut dyn FantasyRealmsBot, index: usize) -> bool {
+        let position = &self.positions[index];
+        let turn = self.record.turns[index];
+        let card = match turn.draw {
+            DrawCard::Discard (card) => Some(card),
+            DrawCard::Deck => turn.card,
+        };
+        let (Some(hand), Some(card)) = (position.hands[turn.seat], card) else {
+            return false;
+        };
+        let view = GameView::new(
+            hand,
+            turn.seat,
+            position.known_cards.clone(),
+            position.discard_pile,
+            position.history.clone(),
+        );
+        bot.generate_draw(&view);
+        bot.generate_discard(&view, &card);
+        true
+    }
+
+    /// Displays the position being viewed.
+    pub fn display(&self) {
+        let position = self.position();
+        let turns = self.record.turns.len();
+        println!();
+        if let Some(turn) = self.index.checked_sub(1).map(|last| self.record.turns[last]) {
+            let name = self.record.players[turn.seat].name().trim();
+            match (turn.draw, turn.card) {
+                (DrawCard::Discard (card), _) => println!("{name} drew {card} from the discard pile."),
+                (DrawCard::Deck, Some(card)) => println!("{name} drew {card} from the deck."),
+                (DrawCard::Deck, None) => println!("{name} drew from the deck."),
+            }
+            println!("{name} discarded {}.", turn.discard);
+        }
+        if self.is_over() {
+            println!("The game has ended after {turns} turns.");
+        } else {
+            let seat = position.current_turn;
+            let name = self.record.players[seat].name().trim();
+            println!("Turn {} of {turns}, {name}'s turn.", self.index + 1);
+        }
+        println!("Discard pile: {}", cards_text(position.discard_pile));
+        if !self.is_over() {
+            let seat = position.current_turn;
+            match position.hands[seat] {
+                Some(hand) => println!("Hand: {}", cards_text(hand.to_vec())),
+                None => println!("Hand not known, cards known about it: {}", cards_text(position.known_cards[seat])),
+            }
+        }
+        println!("Scores:");
+        for (seat, hand) in self.hands().iter().enumerate() {
+            let name = self.record.players[seat].name().trim();
+            match hand {
+                Some(hand) => println!("  {name}: {} points", hand.score(&position.discard_pile)),
+                None => println!("  {name}: not known, holds {}", cards_text(position.known_cards[seat])),
+            }
+        }
+    }
+}
+
+/// Loads a record and lets the user step through it in the terminal.
+/// # Arguments
+/// * `path` - A `&Path` to the record.
+/// # Errors
+/// This function returns an `Err(String)` if the record cannot be loaded or does not replay.
+pub fn run_replay_viewer(path: &Path) -> Result<(), String> {
+    let mut viewer = ReplayViewer::new(GameRecord::load(path)?)?;
+    viewer.display();
+    loop {
+        println!("Enter next, back, go <turn>, ask <bot>, list or quit:");
+        let input = get_string_input();
+        let input = input.trim();
+        let (command, argument) = input.split_once(' ').unwrap_or((input, ""));
+        match command.to_lowercase().as_str() {
+            "" | "n" | "next" => {
+                if !viewer.step_forward() {
+                    println!("The game has ended.");
+                    continue;
+                }
+            }
+            "b" | "back" | "p" | "previous" => {
+                if !viewer.step_back() {
+                    println!("This is the start of the game.");
+                    continue;
+                }
+            }
+            "g" | "go" => match argument.trim().parse::<usize>() {
+                Ok(turn) => {
+                    if let Err(e) = viewer.go_to(turn.saturating_sub(1)) {
+                        println!("{e}");
+                        continue;
+                    }
+                }
+                Err(_) => {
+                    println!("Invalid input. Enter go followed by the number of a turn:");
+                    continue;
+                }
+            },
+            "a" | "ask" => {
+                match viewer.ask(argument.trim()) {
+                    Ok(suggestion) => display_suggestion(&viewer, argument.trim(), &suggestion),
+                    Err(e) => println!("{e}"),
+                }
+                continue;
+            }
+            "list" | "help" => {
+                print_bot_list();
+                continue;
+            }
+            "q" | "quit" | "exit" => {
+                return Ok(());
+            }
+            _ => {
+                println!("Invalid input.");
+                continue;
+            }
+        }
+        viewer.display();
+    }
+}
+
+/// Compares every turn of a recorded game with what a bot would have played, printing the turns that differ
+/// and how often each player played the way the bot would. Turns by players whos hand is not known are skipped.
+/// # Arguments
+/// * `path` - A `&Path` to the record.
+/// * `spec` - A `&str` containing the spec of the bot to compare with.
+/// # Errors
+/// This function returns an `Err(String)` if the spec is not a bot, or the record cannot be loaded or does not replay.
+pub fn run_analysis(path: &Path, spec: &str) -> Result<(), String> {
+    validate_bot(spec)?;
+    let mut viewer = ReplayViewer::new(GameRecord::load(path)?)?;
+    let players = viewer.record.players.len();
+    let mut agreed: Vec<usize> = vec![0; players];
+    let mut compared: Vec<usize> = vec![0; players];
+    for index in 0..viewer.record.turns.len() {
+        viewer.go_to(index)?;
+        let suggestion = match viewer.ask(spec) {
+            Ok(suggestion) => suggestion,
+            Err(_) => continue,
+        };
+        let played = viewer.record.turns[index];
+        compared[played.seat] += 1;
+        let same_draw = match (suggestion.draw, played.draw) {
+            (DrawCard::Deck, DrawCard::Deck) => true,
+            (DrawCard::Discard (card), DrawCard::Discard (played_card)) => card == played_card,
+            _ => false,
+        };
+        if same_draw && suggestion.discard.is_none_or(|discard| discard == played.discard) {
+            agreed[played.seat] += 1;
+            continue;
+        }
+        println!(
+            "Turn {}, {} drew {} and discarded {}, {spec} would draw {} and discard {}.",
+            index + 1,
+            viewer.record.players[played.seat].name().trim(),
+            draw_text(played.draw),
+            played.discard,
+            draw_text(suggestion.draw),
+            suggestion.discard.map_or("a card not known".to_string(), |discard| discard.to_string()),
+        );
+    }
+    for (seat, player) in viewer.record.players.iter().enumerate() {
+        if compared[seat] == 0 {
+            println!("{}'s hand is not known, so their turns were not compared.", player.name().trim());
+            continue;
+        }
+        println!(
+            "{} played {} of {} turns the way {spec} would, {:.0}%.",
+            player.name().trim(),
+            agreed[seat],
+            compared[seat],
+            100.0 * agreed[seat] as f64 / compared[seat] as f64,
+        );
+    }
+    Ok(())
+}
+
+/// Helper function for `run_analysis`.
+/// Writes where a card was drawn from.
+fn draw_text(draw: DrawCard) -> String {
+    match draw {
+        DrawCard::Deck => "from the deck".to_string(),
+        DrawCard::Discard (card) => card.to_string(),
+    }
+}
+
+/// Helper function for `run_replay_viewer`.
+/// Displays what a bot would have played, and what was played.
+fn display_suggestion(viewer: &ReplayViewer, spec: &str, suggestion: &Suggestion) {
+    match (suggestion.draw, suggestion.card) {
+        (DrawCard::Discard (card), _) => println!("{spec} would draw {card} from the discard pile."),
+        (DrawCard::Deck, Some(card)) => println!("{spec} would draw {card} from the deck."),
+        (DrawCard::Deck, None) => {
+            println!("{spec} would draw from the deck, the card on top of it is not known so it cannot choose a discard.");
+        }
+    }
+    if let Some(discard) = suggestion.discard {
+        println!("{spec} would discard {discard}.");
+    }
+    if !suggestion.caught_up {
+        println!("Some earlier turns of this player are not known, so {spec} did not decide them and may remember less than if it had played them.");
+    }
+    let played = viewer.record.turns[viewer.index()];
+    match played.draw {
+        DrawCard::Discard (card) => println!("The player drew {card} from the discard pile and discarded {}.", played.discard),
+        DrawCard::Deck => println!("The player drew from the deck and discarded {}.", played.discard),
+    }
+}
+
+/// Helper function for `ReplayViewer::display`.
+/// Writes a list of cards, or none if there are no cards.
+fn cards_text<I: IntoIterator<Item = Card>>(cards: I) -> String {
+    let names: Vec<String> = cards.into_iter().map(|card| card.to_string()).collect();
+    if names.is_empty() {
+        "none".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::simulated_game::play_simulated_game;
+
+    #[test]
+    fn bots_asked_in_their_own_seat_suggest_the_turn_they_played() {
+        let specs: Vec<String> = ["AutomatosV1", "Randy", "Randy"].map(String::from).to_vec();
+        let mut bots: Vec<Box<dyn FantasyRealmsBot>> = specs
+            .iter()
+            .map(|spec| new_bot(spec.clone()).unwrap())
+            .collect();
+        let result = play_simulated_game(&mut bots, 11, false).unwrap();
+        let mut viewer = ReplayViewer::new(result.record(&specs)).unwrap();
+        for (index, turn) in result.turns.iter().enumerate() {
+            viewer.go_to(index).unwrap();
+            let suggestion = viewer.ask(&specs[turn.seat]).unwrap();
+            assert!(suggestion.caught_up);
+            assert_eq!(suggestion.draw, turn.draw);
+            assert_eq!(suggestion.discard, Some(turn.discard));
+        }
+    }
+}
@@ -1,13 +1,72 @@
 use fantasy_realms_unofficial_api::{DrawCard, Game, Player};
 use fantasy_realms_unofficial_api::hand::{Hand, Turn};
 use fantasy_realms_unofficial_api::deck::{Card};
-use fantasy_realms_unofficial_api::card_collection::{CardCollection};
-use fantasy_realms_ai::FantasyRealmsBot;
+use fantasy_realms_unofficial_api::card_collection::CardCollection;
+use fantasy_realms_ai::{FantasyRealmsBot, GameView, ObservedTurn, ScoreCache};
 use super::PlayerType;
+use super::deal::Deal;
+use super::record::{save_record, GameKind, GameRecord, RecordedPlayer, RecordedTurn, RECORD_VERSION};
+
+/// The outcome of a simulated game.
+/// # Fields
+/// * `seed` - A `u64` representing the seed the game was dealt from.
+/// * `names` - A `Vec<String>` containing the name of each bot by seat.
+/// * `starting_hands` - A `Vec<Hand>` containing each bots starting hand by seat.
+/// * `hands` - A `Vec<Hand>` containing each bots final hand by seat.
+/// * `scores` - A `Vec<i16>` containing each bots final score by seat.
+/// * `discard_pile` - A `CardCollection` representing the final discard pile.
+/// * `turns` - A `Vec<RecordedTurn>` containing every turn in the order played.
+pub struct GameResult {
+    pub seed: u64,
+    pub names: Vec<String>,
+    pub starting_hands: Vec<Hand>,
+    pub hands: Vec<Hand>,
+    pub scores: Vec<i16>,
+    pub discard_pile: CardCollection,
+    pub turns: Vec<RecordedTurn>,
+}
+
+impl GameResult {
+    /// Gets the place each bot finished in, tied bots share the best place.
+    /// # Returns
+    /// A `Vec<usize>` containing each bots place by seat, where 1 is first.
+    pub fn placements(&self) -> Vec<usize> {
+        self.scores
+            .iter()
+            .map(|score| 1 + self.scores.iter().filter(|other| *other > score).count())
+            .collect()
+    }
+
+    /// Creates a record of the game that can be saved and replayed.
+    /// # Arguments
+    /// * `specs` - A `&[String]` containing the spec each bot was created from by seat.
+    /// # Returns
+    /// A `GameRecord` of the game.
+    pub fn record(&self, specs: &[String]) -> GameRecord {
+        GameRecord {
+            version: RECORD_VERSION,
+            kind: GameKind::Simulated,
+            seed: Some(self.seed),
+            players: self.names
+                .iter()
+                .zip(specs)
+                .map(|(name, spec)| RecordedPlayer::Bot {name: name.clone(), spec: spec.clone()})
+                .collect(),
+            starting_hands: self.starting_hands.iter().map(|&hand| Some(hand)).collect(),
+            turns: self.turns.clone(),
+            discard_pile: self.discard_pile,
+            final_hands: self.hands.clone(),
+            scores: self.scores.clone(),
+        }
+    }
+}
 
 /// Runs a simulated game.
+/// The record of the game is saved to the default record directory.
 /// # Arguments
 /// * `player_types` - A `Vec<PlayerType>` representing a all players in the game. 
+/// * `seed` - A `u64` representing the seed the game is dealt from.
+///   Games with the same players and seed are played the same.
 /// # Errors
 /// This function returns an `Err(String)` if:
 /// * Any of the players are human.
@@ -16,90 +75,140 @@ use super::PlayerType;
 /// A `Result<Self, String>` which is:
 /// * `Ok(Game)` if the game is successfully run.
 /// * `Err(String)` containing an error message if validation fails.
-pub fn run_simulated_game(player_types: Vec<PlayerType>) -> Result<(), String> {
-    let mut deck = !CardCollection::new();
-    let mut players: Vec<Player> = Vec::new();
+pub fn run_simulated_game(player_types: Vec<PlayerType>, seed: u64) -> Result<(), String> {
     let mut bots: Vec<Box<dyn FantasyRealmsBot>> = Vec::new();
-    for (i, player) in player_types.into_iter().enumerate() {
+    let mut specs: Vec<String> = Vec::new();
+    for player in player_types {
         match player {
             PlayerType::Human (_) => {
                 return Err("Human found in simulated game players".to_string());
             }
-            PlayerType::Bot (bot) => {
+            PlayerType::Bot (bot, spec) => {
                 bots.push(bot);
+                specs.push(spec);
             }
         }
-        let name: String = bots[i as usize].name();
-        let hand: Hand = deck.draw_hand();
-        players.push(Player::new(name, hand));
     }
+    let result = play_simulated_game(&mut bots, seed, true)?;
+    display_final_scores(&result);
+    println!("Seed {}, enter it when starting a new game to replay this game.", result.seed);
+    save_record(&result.record(&specs));
+    Ok(())
+}
+
+/// Plays a simulated game between bots.
+/// # Arguments
+/// * `bots` - A `&mut [Box<dyn FantasyRealmsBot>]` containing the bots in seat order.
+/// * `seed` - A `u64` representing the seed the game is dealt from.
+/// * `verbose` - A `bool` that prints every turn when set.
+/// # Errors
+/// This function returns an `Err(String)` if:
+/// * Turn validation fails for a generated turn.
+/// # Returns
+/// A `Result<GameResult, String>` containing the outcome of the game.
+pub fn play_simulated_game(
+    bots: &mut [Box<dyn FantasyRealmsBot>],
+    seed: u64,
+    verbose: bool,
+) -> Result<GameResult, String> {
+    let mut deal = Deal::new(seed, bots.len());
+    let players: Vec<Player> = bots
+        .iter()
+        .enumerate()
+        .map(|(seat, bot)| Player::new(bot.name(), deal.hands[seat]))
+        .collect();
     let mut game = Game::new(players).unwrap();
+    let player_count = bots.len();
+    for (seat, bot) in bots.iter_mut().enumerate() {
+        bot.seed(deal.bot_seed(seat));
+        bot.on_game_start(seat, player_count, &game.players[seat].hand);
+    }
+    let mut history: Vec<ObservedTurn> = Vec::new();
+    let mut turns: Vec<RecordedTurn> = Vec::new();
     while !game.over {
-        handle_bot_turn(&mut*bots[game.current_turn], &mut game)?;
-        println!("discard pile size = {}", game.discard_pile.len());
+        let turn = handle_bot_turn(&mut*bots[game.current_turn], &mut game, &mut deal, &history, verbose)?;
+        for bot in bots.iter_mut() {
+            bot.observe_turn(&turn.observed());
+        }
+        history.push(turn.observed());
+        turns.push(turn);
+        if verbose {
+            println!("discard pile size = {}", game.discard_pile.len());
+        }
     }
-    display_final_scores(game);
-    Ok(())
+    let hands: Vec<Hand> = game.players.iter().map(|player| player.hand).collect();
+    let scores: Vec<i16> = hands.iter().map(|hand| hand.score(&game.discard_pile)).collect();
+    for bot in bots.iter_mut() {
+        bot.on_game_end(&hands, &scores);
+    }
+    Ok(GameResult {
+        seed: deal.seed,
+        names: game.players.iter().map(|player| player.name.clone()).collect(),
+        starting_hands: deal.hands.clone(),
+        hands,
+        scores,
+        discard_pile: game.discard_pile,
+        turns,
+    })
 }
 
-/// A helper function for `run_simpulated_game`.
+/// A helper function for `play_simulated_game`.
 /// Interfaces with a bot to generate a turn and plays the turn generated.
 /// # Arguments 
 /// * `bot` - A `&mut dyn FantasyRealmsBot` representing the bot whos turn it is to play. 
 /// * `game` - A `&mut Game` representing the game being played. 
+/// * `deal` - A `&mut Deal` representing the order of the cards left in the deck.
+/// * `history` - A `&[ObservedTurn]` containing every turn played so far.
+/// * `verbose` - A `bool` that prints the turn when set.
 /// # Side Effects
 /// * **`Game`**:
 ///    * plays a turn of the game.
 /// # Errors
 /// This function returns an `Err(String)` if:
 /// * The turn generated is not valid. 
+/// * The deck is empty.
 /// # Returns
-/// A `Result<(), String>` which is:
-/// * `Ok(())` if the turn is prossesed sucessfully.
+/// A `Result<RecordedTurn, String>` which is:
+/// * `Ok(RecordedTurn)` containing the turn and the card drawn if the turn is prossesed sucessfully.
 /// * `Err(String)` containing an error message if the turn validation failed.
-fn handle_bot_turn(bot: &mut dyn FantasyRealmsBot, game: &mut Game) -> Result<(), String> {
-    let opponent_cards = get_opponent_known_cards(game);
-    let min_turns_remaining = (10 - (game.discard_pile.len() as u8)) / (game.players.len() as u8);
-    let draw_card: DrawCard = bot
-        .generate_draw(
-            &game.players[game.current_turn].hand,
-            &game.discard_pile,
-            &opponent_cards,
-            min_turns_remaining,
-    );
+fn handle_bot_turn(
+    bot: &mut dyn FantasyRealmsBot,
+    game: &mut Game,
+    deal: &mut Deal,
+    history: &[ObservedTurn],
+    verbose: bool,
+) -> Result<RecordedTurn, String> {
+    let seat = game.current_turn;
+    let view = get_game_view(game, history);
+    let draw_card: DrawCard = bot.generate_draw(&view);
     let draw = match draw_card {
         DrawCard::Discard(card) => card,
-        _ => game.deck.get_random_card().unwrap(),
+        _ => deal.draw().ok_or("The deck is empty.".to_string())?,
     };
-    let discard: Card = bot
-        .generate_discard(
-            &game.players[game.current_turn].hand,
-            &game.discard_pile,
-            &opponent_cards,
-            min_turns_remaining,
-            &draw,
-    );
+    let discard: Card = bot.generate_discard(&view, &draw);
     let turn = Turn::new(draw, discard);
-    display_bot_turn_info(bot, &turn);
-    game.play_turn(turn)
+    if verbose {
+        display_bot_turn_info(bot, &turn);
+    }
+    game.play_turn(turn)?;
+    Ok(RecordedTurn {seat, draw: draw_card, card: Some(draw), discard})
 }
 
 /// A helper function for `handle_bot_turn`.
-/// Gets the known cards for all opponents.
+/// Gets the game as seen by the player whos turn it is.
 /// # Arguments
-/// * `game` - A `Game` representing the current game being played
-fn get_opponent_known_cards(game: &Game) -> Vec<CardCollection> {
-    game.players
-        .iter()
-        .enumerate()
-        .filter_map(|(i, player)| {
-            if i != game.current_turn {
-                Some(player.cards_known_to_opponents)
-            } else {
-                None
-            }
-        })
-        .collect()
+/// * `game` - A `&Game` representing the current game being played.
+/// * `history` - A `&[ObservedTurn]` containing every turn played so far.
+/// # Returns
+/// A `GameView` for the current player.
+fn get_game_view(game: &Game, history: &[ObservedTurn]) -> GameView {
+    GameView::new(
+        game.players[game.current_turn].hand,
+        game.current_turn,
+        game.players.iter().map(|player| player.cards_known_to_opponents).collect(),
+        game.discard_pile,
+        history.to_vec(),
+    )
 }
 
 /// A helper function for `handle_bot_turn`.
@@ -117,16 +226,42 @@ fn display_bot_turn_info(bot: &dyn FantasyRealmsBot, turn: &Turn) {
 /// A helper function for `run_simpulated_game`.
 /// Displays the final scores for each player in a game.
 /// # Arguments
-/// * `game` - A `Game` containing the hand information of all players.
-fn display_final_scores(game: Game) {
-    for player in game.players.iter() {
-        let name: String = player.clone().name;
-        let score: i16 = player.hand.score(&game.discard_pile);
+/// * `result` - A `&GameResult` containing the hand information of all players.
+fn display_final_scores(result: &GameResult) {
+    for ((name, hand), score) in result.names.iter().zip(&result.hands).zip(&result.scores) {
         println!("{} scored {score} points.", name.trim());
         println!("{} had the hand: ", name.trim());
-        for card in player.hand {
-            print!("{}, ", card.to_string())
+        for card in *hand {
+            print!("{}, ", card)
         }
         println!();
     }
-}
\ No newline at end of file
+    let statistics = ScoreCache::global().statistics();
+    println!(
+        "Score cache hit rate {:.1}% ({} hits, {} misses).",
+        statistics.hit_rate() * 100.0,
+        statistics.hits,
+        statistics.misses,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use fantasy_realms_ai::new_bot;
+    use super::*;
+
+    #[test]
+    fn seeded_games_replay_exactly() {
+        let play = || {
+            let mut bots: Vec<Box<dyn FantasyRealmsBot>> = ["ISMCTS(iterations=20)", "AutomatosV1", "Randy"]
+                .into_iter()
+                .map(|spec| new_bot(spec.to_string()).unwrap())
+                .collect();
+            play_simulated_game(&mut bots, 7, false).unwrap()
+        };
+        let (first, second) = (play(), play());
+        assert_eq!(first.turns, second.turns);
+        assert_eq!(first.hands, second.hands);
+        assert_eq!(first.scores, second.scores);
+    }
+}
@@ -1,7 +1,11 @@
 use std::io;
 use fantasy_realms_unofficial_api::{DrawCard, deck::Card, hand::Hand, PartialTurn};
-use fantasy_realms_ai::{FantasyRealmsBot, new_bot};
-use super::PlayerType;
+use fantasy_realms_ai::{FantasyRealmsBot, new_bot, validate_bot};
+use fantasy_realms_ai::{plugin, registry};
+use fantasy_realms_ai::registry::split_specs;
+use super::{Correction, PlayerType, Session};
+use super::deal::random_seed;
+use super::head_to_head::{parse_margin, Margin};
 
 /// Gets an integer from the user via the terminal
 /// # Arguments
@@ -57,23 +61,64 @@ pub fn get_card_input() -> Card {
     }
 }
 
-/// Gets a DrawCard from the user via the terminal
+/// Gets a card, or a correction to a physical game, from the user via the terminal
 /// # Returns
-/// A `DrawCard` that can be a specific card or an uknown card.
-fn get_draw_card_input() -> DrawCard {
+/// A `Result<Card, Correction>` which is `Err` if a correction was entered instead of a card.
+pub fn get_card_or_correction_input() -> Result<Card, Correction> {
     loop {
         let mut input = String::new();
         io::stdin().read_line(&mut input).expect("Failed to read line.");
+        if let Some(correction) = parse_correction(&input) {
+            return Err(correction);
+        }
+        match input.trim().parse::<Card>() {
+            Ok(card) => {
+                return Ok(card);
+            }
+            Err(_) => {
+                println!("Invalid input. Enter the name of the card, undo or correct followed by a turn:");
+                continue;
+            }
+        }
+    }
+}
+
+/// Reads a correction, `undo` takes back one turn, `undo 3` three turns and `correct 5` enters turn 5 again.
+/// # Arguments
+/// * `input` - A `&str` containing the input.
+/// # Returns
+/// An `Option<Correction>` which is `None` if the input is not a correction.
+pub fn parse_correction(input: &str) -> Option<Correction> {
+    let input = input.trim().to_lowercase();
+    let (command, argument) = input.split_once(' ').unwrap_or((input.as_str(), ""));
+    match (command, argument.trim().parse::<usize>()) {
+        ("undo", _) if argument.trim().is_empty() => Some(Correction::Undo (1)),
+        ("undo", Ok(turns)) => Some(Correction::Undo (turns)),
+        ("correct" | "fix", Ok(turn)) => Some(Correction::Correct (turn)),
+        _ => None,
+    }
+}
+
+/// Gets a DrawCard, or a correction to a physical game, from the user via the terminal
+/// # Returns
+/// A `Result<DrawCard, Correction>` where the `DrawCard` can be a specific card or an uknown card.
+fn get_draw_card_input() -> Result<DrawCard, Correction> {
+    loop {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect("Failed to read line.");
+        if let Some(correction) = parse_correction(&input) {
+            return Err(correction);
+        }
         if matches!(input.trim().to_lowercase().as_str(), 
             "none" | 
             "nothing" | 
             "deck" |
             "hidden") {
-            return DrawCard::Deck;
+            return Ok(DrawCard::Deck);
         }
         match input.trim().parse::<Card>() {
             Ok(card) => {
-                return DrawCard::Discard (card);
+                return Ok(DrawCard::Discard (card));
             }
             Err(_) => {
                 println!("Invalid input. Enter the name of the card:");
@@ -90,34 +135,38 @@ pub fn get_hand_input() -> Hand {
     let mut hand: Vec<Card> = Vec::new();
     while hand.len() < 7 {
         let new_card = get_card_input();
-        if !hand.iter().any(|card| *card == new_card) {
+        if !hand.contains(&new_card) {
             hand.push(new_card);
         }
     }
     Hand::new(hand.try_into().expect("Failed to convert to array."))
 }
 
-/// Gets a PartialTurn from the user via the terminal
+/// Gets a PartialTurn, or a correction to a physical game, from the user via the terminal
 /// # Returns
-/// A `PartialTurn` 
-pub fn get_turn_input() -> PartialTurn {
+/// A `Result<PartialTurn, Correction>` which is `Err` if a correction was entered instead of a card.
+pub fn get_turn_input() -> Result<PartialTurn, Correction> {
     println!("Enter the name of the card drawn:");
-    let draw: DrawCard = get_draw_card_input();
+    let draw: DrawCard = get_draw_card_input()?;
     println!("Enter the name of the card discarded:");
-    let discard: Card = get_card_input();
-    PartialTurn::new(draw, discard)
+    let discard: Card = get_card_or_correction_input()?;
+    Ok(PartialTurn::new(draw, discard))
 }
 
 /// Gets a bot from the user via the terminal
 /// # Returns
-/// A `Box<dyn FantacyRealmsBot>` 
-pub fn get_bot_input() -> Box<dyn FantasyRealmsBot> {
+/// A `Box<dyn FantacyRealmsBot>` and the spec it was created from.
+pub fn get_bot_input() -> (Box<dyn FantasyRealmsBot>, String) {
     loop {
         let mut input = String::new();
         io::stdin().read_line(&mut input).expect("Failed to read line.");
-        match new_bot(input) {
+        if matches!(input.trim().to_lowercase().as_str(), "list" | "help") {
+            print_bot_list();
+            continue;
+        }
+        match new_bot(input.clone()) {
             Ok(ai) => {
-                return ai;
+                return (ai, input.trim().to_string());
             }
             Err(e) => {
                 println!("{e}");
@@ -127,6 +176,26 @@ pub fn get_bot_input() -> Box<dyn FantasyRealmsBot> {
     }
 }
 
+/// Prints every bot that can be added to a game with its description and parameters.
+pub fn print_bot_list() {
+    println!("Available bots:");
+    for entry in registry::registered_bots() {
+        println!("  {} ({}, {}) - {}", entry.name, entry.strength, entry.speed, entry.description);
+        if !entry.aliases.is_empty() {
+            let aliases: Vec<&str> = entry.aliases.iter().map(|alias| alias.name).collect();
+            println!("      Also known as: {}", aliases.join(", "));
+        }
+        for parameter in entry.parameters {
+            println!("      {} - {}, default {}", parameter.name, parameter.description, parameter.default);
+        }
+    }
+    for plugin in plugin::loaded_plugins() {
+        println!("  {} (plugin) - Loaded from {}", plugin.name(), plugin.path().display());
+    }
+    println!("  external:<command> - A bot running in another process");
+    println!("  wasm:<path> - A bot compiled to WebAssembly");
+}
+
 /// Gets a PlayerType from the user via the terminal
 /// # Returns
 /// A `PartialType` 
@@ -140,8 +209,10 @@ pub fn get_player_type_input() -> PlayerType {
                 return PlayerType::Human (get_string_input());
             }
             "bot" | "ai" | "robot" | "computer" => {
-                println!("Enter the name of the bot:");
-                return PlayerType::Bot (get_bot_input());
+                print_bot_list();
+                println!("Enter the name of the bot, with any parameters such as AutomatosV2(depth=2):");
+                let (bot, spec) = get_bot_input();
+                return PlayerType::Bot (bot, spec);
             }
             _ => {
                 println!("Invalid input. Enter either Human or Bot:");
@@ -151,6 +222,138 @@ pub fn get_player_type_input() -> PlayerType {
     }
 }
 
+/// Gets a Session from the user via the terminal
+/// # Returns
+/// A `Session` representing what to run.
+pub fn get_session_input() -> Session {
+    loop {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect("Failed to read line.");
+        match input.trim().to_lowercase().as_str() {
+            "game" | "play" => {
+                return Session::Game;
+            }
+            "tournament" => {
+                return Session::Tournament;
+            }
+            "duplicate" => {
+                return Session::Duplicate;
+            }
+            "test" | "head to head" => {
+                return Session::HeadToHead;
+            }
+            "batch" => {
+                return Session::Batch;
+            }
+            "replay" => {
+                return Session::Replay;
+            }
+            "view" | "viewer" => {
+                return Session::View;
+            }
+            _ => {
+                println!("Invalid input. Enter either Game, Tournament, Duplicate, Test, Batch, Replay or View:");
+                continue;
+            }
+        }
+    }
+}
+
+/// Gets the bots for a tournament or duplicate match from the user via the terminal
+/// # Arguments
+/// * `min` - A `usize` representing the fewest bots accepted.
+/// * `max` - A `usize` representing the most bots accepted.
+/// # Returns
+/// A `Vec<String>` containing the spec of each bot.
+pub fn get_specs_input(min: usize, max: usize) -> Vec<String> {
+    loop {
+        let specs = split_specs(&get_string_input());
+        if (min..=max).contains(&specs.len()) {
+            return specs;
+        }
+        println!("Invalid input. Enter between {} and {} bots separated by commas:", min, max);
+    }
+}
+
+/// Gets a bot spec from the user via the terminal, checking the spec without creating the bot
+/// # Returns
+/// A `String` containing the spec.
+pub fn get_spec_input() -> String {
+    loop {
+        let input = get_string_input();
+        match validate_bot(&input) {
+            Ok(_) => {
+                return input.trim().to_string();
+            }
+            Err(e) => {
+                println!("{e}");
+                continue;
+            }
+        }
+    }
+}
+
+/// Gets the margin of a head to head test from the user via the terminal
+/// # Returns
+/// A `Margin` in points or as a win rate.
+pub fn get_margin_input() -> Margin {
+    loop {
+        match parse_margin(&get_string_input()) {
+            Some(Margin::Points (points)) if points > 0.0 => {
+                return Margin::Points (points);
+            }
+            Some(Margin::WinRate (rate)) if rate > 0.0 && rate < 0.5 => {
+                return Margin::WinRate (rate);
+            }
+            _ => {
+                println!("Invalid input. Enter a number of points such as 5, or a win rate above 50% such as 55%:");
+                continue;
+            }
+        }
+    }
+}
+
+/// Gets the number of games in a batch from the user via the terminal
+/// # Returns
+/// A `u16` between 1 and 10000.
+pub fn get_games_input() -> u16 {
+    loop {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect("Failed to read line.");
+        match input.trim().parse::<u16>() {
+            Ok(games) if (1..=10_000).contains(&games) => {
+                return games;
+            }
+            _ => {
+                println!("Invalid input. Enter a number between 1 and 10000:");
+                continue;
+            }
+        }
+    }
+}
+
+/// Gets the seed of a simulated game from the user via the terminal
+/// # Returns
+/// A `u64` which is random if no seed is entered.
+pub fn get_seed_input() -> u64 {
+    loop {
+        let mut input = String::new();
+        io::stdin().read_line(&mut input).expect("Failed to read line.");
+        if input.trim().is_empty() {
+            return random_seed();
+        }
+        match input.trim().parse::<u64>() {
+            Ok(seed) => {
+                return seed;
+            }
+            Err(_) => {
+                println!("Invalid input. Enter a whole number, or nothing for a random seed:");
+                continue;
+            }
+        }
+    }
+}
+
 /// Gets a String from the user via the terminal
 /// # Returns
 /// A `bool` representing weaher or not to start a new game. 
@@ -0,0 +1,345 @@
+//! # Tournament
+//! Plays a round robin between bots to find out which is the strongest.
+//! Every round seats a table of each size from 3 to 6 players, and plays it once in each seat order
+//! of a balanced Latin square, the same orders a duplicate match uses, so every bot at the table plays
+//! from every seat and straight after every other bot equally often, and no bot gains from where it sits.
+//! Each game is split into a result between every pair of bots at the table,
+//! and the pairs are fitted to a Bradley-Terry model, giving each bot an Elo style rating
+//! with a 95% confidence interval.
+//! The pairs from one game are not independent, since they all come from the same deal,
+//! so the interval counts each game a bot plays as one independent result instead of one for every opponent.
+//! The seat orders of each table are played as a batch, so the games run on every core.
+
+use std::f64::consts::LN_10;
+use rand::rngs::StdRng;
+use rand::seq::SliceRandom;
+use rand::{Rng, SeedableRng};
+use fantasy_realms_ai::validate_bot;
+use super::batch::{run_tables, BatchGameResult};
+use super::duplicate::seat_orders;
+
+/// The smallest table played.
+pub const MIN_PLAYERS: usize = 3;
+/// The largest table played.
+pub const MAX_PLAYERS: usize = 6;
+/// The rating of a bot that wins exactly half of its pairs.
+const BASE_RATING: f64 = 1500.0;
+/// The number of tied games added between every pair of bots,
+/// so that a bot that never wins or never loses still has a finite rating.
+const PRIOR_GAMES: f64 = 1.0;
+/// The most iterations used to fit the ratings.
+const FIT_ITERATIONS: usize = 10_000;
+/// The change in strength at which the fit has converged.
+const FIT_TOLERANCE: f64 = 1e-9;
+/// The number of standard errors either side of a rating covering 95% of the likely ratings.
+const CONFIDENCE_Z: f64 = 1.96;
+
+/// The record of one bot in a tournament.
+/// # Fields
+/// * `spec` - A `String` representing the spec the bot was created from.
+/// * `games` - A `usize` representing the number of games played.
+/// * `wins` - A `f64` representing the number of games finished first, ties share a win.
+/// * `total_score` - An `i64` representing the sum of the bots final scores.
+/// * `placements` - A `[usize; MAX_PLAYERS]` containing the number of times the bot finished in each place.
+#[derive(Clone)]
+pub struct Standing {
+    pub spec: String,
+    pub games: usize,
+    pub wins: f64,
+    pub total_score: i64,
+    pub placements: [usize; MAX_PLAYERS],
+}
+
+impl Standing {
+    /// Gets the average place the bot finished in.
+    /// # Returns
+    /// A `f64` where 1 is always finishing first.
+    pub fn average_placement(&self) -> f64 {
+        let total: usize = self.placements
+            .iter()
+            .enumerate()
+            .map(|(place, count)| (place + 1) * count)
+            .sum();
+        total as f64 / self.games.max(1) as f64
+    }
+}
+
+/// A tournament between bots.
+/// # Fields
+/// * `standings` - A `Vec<Standing>` containing the record of each bot.
+/// * `pair_wins` - A `Vec<Vec<f64>>` where `pair_wins[a][b]` is the number of games
+///   bot `a` finished ahead of bot `b`, ties count as half.
+pub struct Tournament {
+    standings: Vec<Standing>,
+    pair_wins: Vec<Vec<f64>>,
+}
+
+impl Tournament {
+    /// Creates a tournament, checking that every bot can be created.
+    /// The same spec can be entered more than once to measure the noise in the ratings.
+    /// # Arguments
+    /// * `specs` - A `Vec<String>` containing the spec of each bot, such as `AutomatosV2(depth=2)`.
+    /// # Errors
+    /// This function returns an `Err(String)` if:
+    /// * There are fewer than 2 bots.
+    /// * Any of the specs is not a bot.
+    /// # Returns
+    /// A `Result<Tournament, String>` containing the tournament with no games played.
+    pub fn new(specs: Vec<String>) -> Result<Self, String> {
+        if specs.len() < 2 {
+            return Err("A tournament needs at least 2 bots.".to_string());
+        }
+        for spec in &specs {
+            validate_bot(spec)?;
+        }
+        let bots = specs.len();
+        Ok(Tournament {
+            standings: specs
+                .into_iter()
+                .map(|spec| Standing {spec, games: 0, wins: 0.0, total_score: 0, placements: [0; MAX_PLAYERS]})
+                .collect(),
+            pair_wins: vec![vec![0.0; bots]; bots],
+        })
+    }
+
+    /// Plays every round of the tournament.
+    /// Each round plays a table of every size from `MIN_PLAYERS` to `MAX_PLAYERS`
+    /// in every seat order from `seat_orders`, as a batch with one game for each order.
+    /// Tables with more seats than bots seat some bots twice.
+    /// # Arguments
+    /// * `rounds` - A `usize` representing the number of rounds to play.
+    /// * `seed` - A `u64` representing the seed of the tournament.
+    ///   The tables and the deal of every game are chosen from it.
+    /// * `progress` - A `bool` that prints the number of games played as the tournament runs.
+    /// # Errors
+    /// This function returns an `Err(String)` if any game fails.
+    pub fn play(&mut self, rounds: usize, seed: u64, progress: bool) -> Result<(), String> {
+        let mut rng = StdRng::seed_from_u64(seed);
+        let games_per_round: usize = (MIN_PLAYERS..=MAX_PLAYERS).map(|players| seat_orders(players).len()).sum();
+        let total_games = rounds * games_per_round;
+        let mut played: usize = 0;
+        let step = (total_games / 10).max(1);
+        for _ in 0..rounds {
+            for players in MIN_PLAYERS..=MAX_PLAYERS {
+                let table = self.seat_table(players, &mut rng);
+                let games: Vec<Vec<usize>> = seat_orders(players)
+                    .iter()
+                    .map(|order| order.iter().map(|&place| table[place]).collect())
+                    .collect();
+                let specs: Vec<Vec<String>> = games
+                    .iter()
+                    .map(|seats| seats.iter().map(|&bot| self.standings[bot].spec.clone()).collect())
+                    .collect();
+                let report = run_tables(&specs, rng.gen(), 0)?;
+                for result in &report.results {
+                    self.record(&games[result.game], result);
+                }
+                if progress && (played + games.len()) / step > played / step {
+                    println!("Played {} of {total_games} games.", played + games.len());
+                }
+                played += games.len();
+            }
+        }
+        Ok(())
+    }
+
+    /// Helper function for `play`.
+    /// Chooses the bots at a table, each bot is seated once before any is seated twice.
+    /// # Returns
+    /// A `Vec<usize>` containing the index of the bot in each seat.
+    fn seat_table(&self, players: usize, rng: &mut StdRng) -> Vec<usize> {
+        let mut order: Vec<usize> = (0..self.standings.len()).collect();
+        order.shuffle(rng);
+        (0..players).map(|seat| order[seat % order.len()]).collect()
+    }
+
+    /// Records the result of a game.
+    /// # Arguments
+    /// * `seats` - A `&[usize]` containing the index of the bot in each seat.
+    /// * `result` - A `&BatchGameResult` representing the outcome of the game.
+    pub fn record(&mut self, seats: &[usize], result: &BatchGameResult) {
+        let winners = result.seats.iter().filter(|seat| seat.placement == 1).count();
+        for (seat, &bot) in result.seats.iter().zip(seats) {
+            let standing = &mut self.standings[bot];
+            standing.games += 1;
+            standing.total_score += seat.score as i64;
+            standing.placements[seat.placement - 1] += 1;
+            if seat.placement == 1 {
+                standing.wins += 1.0 / winners as f64;
+            }
+        }
+        for (a, &bot_a) in result.seats.iter().zip(seats) {
+            for (b, &bot_b) in result.seats.iter().zip(seats) {
+                if bot_a == bot_b {
+                    continue;
+                }
+                if a.score > b.score {
+                    self.pair_wins[bot_a][bot_b] += 1.0;
+                } else if a.score == b.score {
+                    self.pair_wins[bot_a][bot_b] += 0.5;
+                }
+            }
+        }
+    }
+
+    /// Fits the rating of every bot to the results between each pair of bots.
+    /// The information in each bots pairs is scaled down by the number of pairs it has in each game,
+    /// so the interval is as wide as if every game the bot played were a single independent result.
+    /// # Returns
+    /// A `Vec<(f64, f64)>` containing the rating of each bot and the distance
+    /// either side of it covered by the 95% confidence interval.
+    pub fn ratings(&self) -> Vec<(f64, f64)> {
+        let bots = self.standings.len();
+        let wins = |a: usize, b: usize| self.pair_wins[a][b] + PRIOR_GAMES / 2.0;
+        let games = |a: usize, b: usize| wins(a, b) + wins(b, a);
+        let total_wins: Vec<f64> = (0..bots)
+            .map(|a| (0..bots).filter(|&b| b != a).map(|b| wins(a, b)).sum())
+            .collect();
+        let mut strengths: Vec<f64> = vec![1.0; bots];
+        for _ in 0..FIT_ITERATIONS {
+            let mut next: Vec<f64> = (0..bots)
+                .map(|a| {
+                    let expected: f64 = (0..bots)
+                        .filter(|&b| b != a)
+                        .map(|b| games(a, b) / (strengths[a] + strengths[b]))
+                        .sum();
+                    total_wins[a] / expected
+                })
+                .collect();
+            let mean_log = next.iter().map(|strength| strength.ln()).sum::<f64>() / bots as f64;
+            next.iter_mut().for_each(|strength| *strength /= mean_log.exp());
+            let change = next
+                .iter()
+                .zip(&strengths)
+                .map(|(a, b)| (a - b).abs())
+                .fold(0.0, f64::max);
+            strengths = next;
+            if change < FIT_TOLERANCE {
+                break;
+            }
+        }
+        let scale = 400.0 / LN_10;
+        (0..bots)
+            .map(|a| {
+                let information: f64 = (0..bots)
+                    .filter(|&b| b != a)
+                    .map(|b| {
+                        let total = strengths[a] + strengths[b];
+                        games(a, b) * strengths[a] * strengths[b] / (total * total)
+                    })
+                    .sum();
+                let pairs: f64 = (0..bots)
+                    .filter(|&b| b != a)
+                    .map(|b| self.pair_wins[a][b] + self.pair_wins[b][a])
+                    .sum();
+                let games_per_pair = if pairs > 0.0 { self.standings[a].games as f64 / pairs } else { 1.0 };
+                let information = information * games_per_pair.min(1.0);
+                let rating = BASE_RATING + scale * strengths[a].ln();
+                (rating, CONFIDENCE_Z * scale / information.sqrt())
+            })
+            .collect()
+    }
+
+    /// Gets the standings with their ratings, highest rated first.
+    /// # Returns
+    /// A `Vec<(Standing, f64, f64)>` containing each bots standing, rating and 95% interval.
+    pub fn ranked(&self) -> Vec<(Standing, f64, f64)> {
+        let mut ranked: Vec<(Standing, f64, f64)> = self.standings
+            .iter()
+            .cloned()
+            .zip(self.ratings())
+            .map(|(standing, (rating, margin))| (standing, rating, margin))
+            .collect();
+        ranked.sort_by(|(_, a, _), (_, b, _)| b.total_cmp(a));
+        ranked
+    }
+
+    /// Displays the ranked table of every bot.
+    pub fn display(&self) {
+        let width = self.standings.iter().map(|standing| standing.spec.len()).max().unwrap_or(0).max(3);
+        println!(
+            "{:<4} {:<width$} {:>7} {:>6} {:>6} {:>6} {:>9} {:>9}",
+            "Rank", "Bot", "Rating", "95%", "Games", "Wins", "Avg score", "Avg place",
+        );
+        for (rank, (standing, rating, margin)) in self.ranked().iter().enumerate() {
+            println!(
+                "{:<4} {:<width$} {:>7.0} {:>6} {:>6} {:>5.1}% {:>9.1} {:>9.2}",
+                rank + 1,
+                standing.spec,
+                rating,
+                format!("±{margin:.0}"),
+                standing.games,
+                100.0 * standing.wins / standing.games.max(1) as f64,
+                standing.total_score as f64 / standing.games.max(1) as f64,
+                standing.average_placement(),
+            );
+        }
+    }
+}
+
+/// Runs a tournament and displays the results.
+/// # Arguments
+/// * `specs` - A `Vec<String>` containing the spec of each bot.
+/// * `rounds` - A `usize` representing the number of rounds to play.
+/// * `seed` - A `u64` representing the seed of the tournament.
+/// # Errors
+/// This function returns an `Err(String)` if a bot cannot be created or a game fails.
+pub fn run_tournament(specs: Vec<String>, rounds: usize, seed: u64) -> Result<(), String> {
+    let mut tournament = Tournament::new(specs)?;
+    tournament.play(rounds, seed, true)?;
+    tournament.display();
+    println!("Seed {seed}, enter it when starting a new tournament to replay this tournament.");
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Helper function for the tests.
+    /// Creates a tournament from the pair results, with every bot having played `games` games.
+    fn tournament(pair_wins: Vec<Vec<f64>>, games: usize) -> Tournament {
+        let mut tournament = Tournament::new((0..pair_wins.len()).map(|_| "Randy".to_string()).collect()).unwrap();
+        tournament.standings.iter_mut().for_each(|standing| standing.games = games);
+        tournament.pair_wins = pair_wins;
+        tournament
+    }
+
+    #[test]
+    fn ratings_fit_the_pair_results() {
+        // 29 wins to 9, plus a tied prior game, is 29.5 to 9.5.
+        let ratings = tournament(vec![vec![0.0, 29.0], vec![9.0, 0.0]], 38).ratings();
+        let difference = 400.0 * (29.5f64 / 9.5).log10();
+        assert!((ratings[0].0 - (BASE_RATING + difference / 2.0)).abs() < 1e-6);
+        assert!((ratings[1].0 - (BASE_RATING - difference / 2.0)).abs() < 1e-6);
+        let p: f64 = 29.5 / 39.0;
+        let margin = CONFIDENCE_Z * 400.0 / LN_10 / (39.0 * p * (1.0 - p)).sqrt();
+        assert!((ratings[0].1 - margin).abs() < 1e-6);
+        assert!((ratings[1].1 - margin).abs() < 1e-6);
+    }
+
+    #[test]
+    fn pairs_from_the_same_game_are_not_counted_as_independent() {
+        let pair_wins = vec![vec![0.0, 6.0, 7.0], vec![4.0, 0.0, 5.0], vec![3.0, 5.0, 0.0]];
+        // Every bot has 20 pairs, from 20 games of two players or 10 games of three.
+        let heads_up = tournament(pair_wins.clone(), 20).ratings();
+        let tables = tournament(pair_wins, 10).ratings();
+        for ((rating, margin), (table_rating, table_margin)) in heads_up.into_iter().zip(tables) {
+            assert!((rating - table_rating).abs() < 1e-9);
+            assert!((table_margin / margin - 2f64.sqrt()).abs() < 1e-9);
+        }
+    }
+
+    #[test]
+    fn every_table_is_played_in_every_seat_order() {
+        let mut tournament = Tournament::new(vec!["Randy".to_string(); MAX_PLAYERS]).unwrap();
+        tournament.play(1, 3, false).unwrap();
+        let games: usize = (MIN_PLAYERS..=MAX_PLAYERS).map(|players| players * seat_orders(players).len()).sum();
+        assert_eq!(tournament.standings.iter().map(|standing| standing.games).sum::<usize>(), games);
+        let pairs: f64 = tournament.pair_wins.iter().flatten().sum();
+        let expected: usize = (MIN_PLAYERS..=MAX_PLAYERS)
+            .map(|players| players * (players - 1) / 2 * seat_orders(players).len())
+            .sum();
+        assert_eq!(pairs, expected as f64);
+    }
+}